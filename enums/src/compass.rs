@@ -0,0 +1,241 @@
+// Compass roses with 8 and 16 points, for movement that is not limited to the four Directions.
+// The variants are listed clockwise starting at north, so `variant as usize` is the position of the point
+// on the rose and `position * 360 / number of points` is its bearing in degrees.
+// Everything that works the same for both roses lives in the default methods of the CompassRose trait.
+
+use std::{fmt, str::FromStr};
+
+use crate::direction::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compass8 {
+  North,
+  NorthEast,
+  East,
+  SouthEast,
+  South,
+  SouthWest,
+  West,
+  NorthWest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compass16 {
+  North,
+  NorthNorthEast,
+  NorthEast,
+  EastNorthEast,
+  East,
+  EastSouthEast,
+  SouthEast,
+  SouthSouthEast,
+  South,
+  SouthSouthWest,
+  SouthWest,
+  WestSouthWest,
+  West,
+  WestNorthWest,
+  NorthWest,
+  NorthNorthWest,
+}
+
+pub trait CompassRose: Sized + Copy + PartialEq + 'static {
+  // Every point of the rose, clockwise from north
+  const ALL: &'static [Self];
+  // Abbreviations and names in the same order as ALL
+  const ABBREVIATIONS: &'static [&'static str];
+  const NAMES: &'static [&'static str];
+
+  // Position on the rose, north is 0
+  fn index(self) -> usize;
+
+  fn abbreviation(self) -> &'static str {
+    Self::ABBREVIATIONS[self.index()]
+  }
+
+  fn name(self) -> &'static str {
+    Self::NAMES[self.index()]
+  }
+
+  // Bearing in degrees clockwise from north, between 0 and 360
+  fn degrees(self) -> f64 {
+    self.index() as f64 * 360.0 / Self::ALL.len() as f64
+  }
+
+  // The point closest to the bearing, any angle works (-90 is west, 450 is east). None for NaN or infinity.
+  // A bearing exactly halfway between two points goes to the one further clockwise.
+  fn from_degrees(degrees: f64) -> Option<Self> {
+    if !degrees.is_finite() {
+      return None;
+    }
+    let step = 360.0 / Self::ALL.len() as f64;
+    let index = (degrees.rem_euclid(360.0) / step).round() as usize % Self::ALL.len();
+    Some(Self::ALL[index])
+  }
+
+  // Accepts abbreviations ("NE") and names ("north-east", "North East", "northeast"), ignoring case
+  fn parse(text: &str) -> Option<Self> {
+    let normalize = |s: &str| -> String {
+      s.chars().filter(|c| !matches!(c, '-' | '_' | ' ')).collect::<String>().to_ascii_lowercase()
+    };
+    let wanted = normalize(text.trim());
+    Self::ALL.iter().copied().find(|point| {
+      point.abbreviation().eq_ignore_ascii_case(&wanted) || normalize(point.name()) == wanted
+    })
+  }
+
+  // Moves `steps` points round the rose, clockwise for positive steps
+  fn rotate(self, steps: i32) -> Self {
+    let len = Self::ALL.len() as i64;
+    let index = (self.index() as i64 + steps as i64).rem_euclid(len);
+    Self::ALL[index as usize]
+  }
+
+  fn opposite(self) -> Self {
+    self.rotate(Self::ALL.len() as i32 / 2)
+  }
+
+  // All points of the rose in clockwise order
+  fn iter() -> std::iter::Copied<std::slice::Iter<'static, Self>> {
+    Self::ALL.iter().copied()
+  }
+}
+
+impl CompassRose for Compass8 {
+  const ALL: &'static [Compass8] = &[
+    Compass8::North,
+    Compass8::NorthEast,
+    Compass8::East,
+    Compass8::SouthEast,
+    Compass8::South,
+    Compass8::SouthWest,
+    Compass8::West,
+    Compass8::NorthWest,
+  ];
+  const ABBREVIATIONS: &'static [&'static str] = &["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+  const NAMES: &'static [&'static str] =
+    &["north", "north-east", "east", "south-east", "south", "south-west", "west", "north-west"];
+
+  fn index(self) -> usize {
+    self as usize
+  }
+}
+
+impl CompassRose for Compass16 {
+  const ALL: &'static [Compass16] = &[
+    Compass16::North,
+    Compass16::NorthNorthEast,
+    Compass16::NorthEast,
+    Compass16::EastNorthEast,
+    Compass16::East,
+    Compass16::EastSouthEast,
+    Compass16::SouthEast,
+    Compass16::SouthSouthEast,
+    Compass16::South,
+    Compass16::SouthSouthWest,
+    Compass16::SouthWest,
+    Compass16::WestSouthWest,
+    Compass16::West,
+    Compass16::WestNorthWest,
+    Compass16::NorthWest,
+    Compass16::NorthNorthWest,
+  ];
+  const ABBREVIATIONS: &'static [&'static str] =
+    &["N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW"];
+  const NAMES: &'static [&'static str] = &[
+    "north",
+    "north-north-east",
+    "north-east",
+    "east-north-east",
+    "east",
+    "east-south-east",
+    "south-east",
+    "south-south-east",
+    "south",
+    "south-south-west",
+    "south-west",
+    "west-south-west",
+    "west",
+    "west-north-west",
+    "north-west",
+    "north-north-west",
+  ];
+
+  fn index(self) -> usize {
+    self as usize
+  }
+}
+
+impl Compass8 {
+  // One step on a grid as (dx, dy), diagonal points move along both axes at once
+  pub fn unit_vector(self) -> (i32, i32) {
+    match self {
+      Compass8::North => (0, 1),
+      Compass8::NorthEast => (1, 1),
+      Compass8::East => (1, 0),
+      Compass8::SouthEast => (1, -1),
+      Compass8::South => (0, -1),
+      Compass8::SouthWest => (-1, -1),
+      Compass8::West => (-1, 0),
+      Compass8::NorthWest => (-1, 1),
+    }
+  }
+
+  pub fn is_diagonal(self) -> bool {
+    self.index() % 2 == 1
+  }
+}
+
+// Every Direction is also a point of the bigger roses, so these conversions can't fail
+impl From<Direction> for Compass8 {
+  fn from(direction: Direction) -> Compass8 {
+    match direction {
+      Direction::North => Compass8::North,
+      Direction::East => Compass8::East,
+      Direction::South => Compass8::South,
+      Direction::West => Compass8::West,
+    }
+  }
+}
+
+impl From<Compass8> for Compass16 {
+  fn from(point: Compass8) -> Compass16 {
+    Compass16::ALL[point.index() * 2]
+  }
+}
+
+impl From<Direction> for Compass16 {
+  fn from(direction: Direction) -> Compass16 {
+    Compass16::from(Compass8::from(direction))
+  }
+}
+
+// Display writes the abbreviation, `{:#}` writes the full name
+impl fmt::Display for Compass8 {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(if f.alternate() { self.name() } else { self.abbreviation() })
+  }
+}
+
+impl fmt::Display for Compass16 {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(if f.alternate() { self.name() } else { self.abbreviation() })
+  }
+}
+
+// FromStr lets the standard `str::parse` produce compass points: "north-east".parse::<Compass8>()
+impl FromStr for Compass8 {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Compass8, String> {
+    <Compass8 as CompassRose>::parse(s).ok_or(format!("`{s}` is not one of the 8 compass points"))
+  }
+}
+
+impl FromStr for Compass16 {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Compass16, String> {
+    <Compass16 as CompassRose>::parse(s).ok_or(format!("`{s}` is not one of the 16 compass points"))
+  }
+}
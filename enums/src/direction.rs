@@ -0,0 +1,59 @@
+// Enum without values: a Direction is exactly one of the four compass points.
+// Deriving Copy makes a Direction behave like a number, passing it around copies it instead of moving it.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+  North,
+  South,
+  East,
+  West
+}
+
+impl Direction {
+  pub const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+  // 90 degrees counter-clockwise
+  pub fn turn_left(self) -> Direction {
+    match self {
+      Direction::North => Direction::West,
+      Direction::West => Direction::South,
+      Direction::South => Direction::East,
+      Direction::East => Direction::North,
+    }
+  }
+
+  // 90 degrees clockwise
+  pub fn turn_right(self) -> Direction {
+    self.turn_left().opposite()
+  }
+
+  pub fn opposite(self) -> Direction {
+    match self {
+      Direction::North => Direction::South,
+      Direction::South => Direction::North,
+      Direction::East => Direction::West,
+      Direction::West => Direction::East,
+    }
+  }
+
+  // One step in this direction as (dx, dy), with x growing to the east and y growing to the north
+  pub fn unit_vector(self) -> (i32, i32) {
+    match self {
+      Direction::North => (0, 1),
+      Direction::South => (0, -1),
+      Direction::East => (1, 0),
+      Direction::West => (-1, 0),
+    }
+  }
+
+  // Single letter used in robot programs and reports
+  pub fn from_letter(letter: char) -> Option<Direction> {
+    match letter.to_ascii_uppercase() {
+      'N' => Some(Direction::North),
+      'S' => Some(Direction::South),
+      'E' => Some(Direction::East),
+      'W' => Some(Direction::West),
+      _ => None,
+    }
+  }
+}
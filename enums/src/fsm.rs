@@ -0,0 +1,187 @@
+// A small finite state machine where the states and the events are both enums.
+// Instead of a hand written `match` over (state, event) the allowed transitions are listed in a table of
+// (from, event, to) rows. Firing an event that has no row for the current state is an error the caller has to
+// handle, and the table can be drawn as a Graphviz DOT graph (`dot -Tpng machine.dot -o machine.png`).
+
+use std::fmt::{self, Debug};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsmError<S, E> {
+  // the event has no transition out of the current state
+  InvalidTransition { state: S, event: E },
+  // the table lists two different targets for the same state and event
+  AmbiguousTransition { state: S, event: E },
+}
+
+impl<S: Debug, E: Debug> fmt::Display for FsmError<S, E> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      FsmError::InvalidTransition { state, event } => write!(f, "event {event:?} is not allowed in state {state:?}"),
+      FsmError::AmbiguousTransition { state, event } => {
+        write!(f, "event {event:?} in state {state:?} leads to more than one state")
+      },
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Machine<S, E> {
+  state: S,
+  transitions: Vec<(S, E, S)>,
+}
+
+// Fieldless enums can derive Copy, PartialEq and Debug, which is all the machine needs from them
+impl<S: Copy + PartialEq + Debug, E: Copy + PartialEq + Debug> Machine<S, E> {
+  pub fn new(initial: S, table: &[(S, E, S)]) -> Result<Machine<S, E>, FsmError<S, E>> {
+    for (idx, (from, event, to)) in table.iter().enumerate() {
+      let conflict = table[..idx].iter().any(|(f, e, t)| f == from && e == event && t != to);
+      if conflict {
+        return Err(FsmError::AmbiguousTransition { state: *from, event: *event });
+      }
+    }
+    Ok(Machine { state: initial, transitions: table.to_vec() })
+  }
+
+  pub fn state(&self) -> S {
+    self.state
+  }
+
+  // Where the event would lead from the current state, without moving there
+  pub fn target(&self, event: E) -> Option<S> {
+    self.transitions.iter().find(|(from, e, _)| *from == self.state && *e == event).map(|(_, _, to)| *to)
+  }
+
+  pub fn can_fire(&self, event: E) -> bool {
+    self.target(event).is_some()
+  }
+
+  // Moves to the next state and returns it. On an invalid event the machine stays where it was.
+  pub fn fire(&mut self, event: E) -> Result<S, FsmError<S, E>> {
+    let next = self.target(event).ok_or(FsmError::InvalidTransition { state: self.state, event })?;
+    self.state = next;
+    Ok(next)
+  }
+
+  // Events that have a transition out of the current state
+  pub fn allowed_events(&self) -> Vec<E> {
+    let mut events: Vec<E> = Vec::new();
+    for (from, event, _) in &self.transitions {
+      if *from == self.state && !events.contains(event) {
+        events.push(*event);
+      }
+    }
+    events
+  }
+
+  // The transition table as a Graphviz digraph. The current state is filled in.
+  pub fn to_dot(&self, name: &str) -> String {
+    let mut states: Vec<S> = Vec::new();
+    for (from, _, to) in &self.transitions {
+      for state in [from, to] {
+        if !states.contains(state) {
+          states.push(*state);
+        }
+      }
+    }
+    if !states.contains(&self.state) {
+      states.push(self.state);
+    }
+
+    let mut dot = format!("digraph {} {{\n  rankdir=LR;\n  node [shape=ellipse];\n", quote_id(name));
+    for state in &states {
+      let style = if *state == self.state { " style=filled fillcolor=lightblue" } else { "" };
+      dot.push_str(&format!("  {} [label={}{}];\n", quote_id(&format!("{state:?}")), quote_id(&format!("{state:?}")), style));
+    }
+    for (from, event, to) in &self.transitions {
+      dot.push_str(&format!(
+        "  {} -> {} [label={}];\n",
+        quote_id(&format!("{from:?}")),
+        quote_id(&format!("{to:?}")),
+        quote_id(&format!("{event:?}"))
+      ));
+    }
+    dot.push_str("}\n");
+    dot
+  }
+}
+
+// DOT identifiers are written as quoted strings so any Debug output is safe to use
+fn quote_id(text: &str) -> String {
+  format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+  enum Door {
+    Open,
+    Closed,
+    Locked,
+  }
+
+  #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+  enum Action {
+    Push,
+    Pull,
+    Lock,
+    Unlock,
+  }
+
+  const DOOR: [(Door, Action, Door); 4] = [
+    (Door::Closed, Action::Pull, Door::Open),
+    (Door::Open, Action::Push, Door::Closed),
+    (Door::Closed, Action::Lock, Door::Locked),
+    (Door::Locked, Action::Unlock, Door::Closed),
+  ];
+
+  #[test]
+  fn conflicting_rows_are_rejected() {
+    let mut table = DOOR.to_vec();
+    table.push((Door::Closed, Action::Pull, Door::Locked));
+    let error = Machine::new(Door::Closed, &table).unwrap_err();
+    assert_eq!(error, FsmError::AmbiguousTransition { state: Door::Closed, event: Action::Pull });
+    assert_eq!(error.to_string(), "event Pull in state Closed leads to more than one state");
+
+    // the same row twice is not ambiguous
+    let mut table = DOOR.to_vec();
+    table.push((Door::Open, Action::Push, Door::Closed));
+    assert!(Machine::new(Door::Open, &table).is_ok());
+    // neither is the same event leading elsewhere from another state
+    assert!(Machine::new(Door::Open, &[(Door::Open, Action::Push, Door::Closed), (Door::Closed, Action::Push, Door::Open)]).is_ok());
+  }
+
+  #[test]
+  fn firing_follows_the_table() {
+    let mut door = Machine::new(Door::Open, &DOOR).unwrap();
+    assert_eq!(door.allowed_events(), vec![Action::Push]);
+    assert_eq!(door.fire(Action::Push), Ok(Door::Closed));
+    assert_eq!(door.allowed_events(), vec![Action::Pull, Action::Lock]);
+    assert_eq!(door.target(Action::Lock), Some(Door::Locked));
+    assert_eq!(door.state(), Door::Closed);
+    assert_eq!(door.fire(Action::Lock), Ok(Door::Locked));
+
+    let error = door.fire(Action::Pull).unwrap_err();
+    assert_eq!(error, FsmError::InvalidTransition { state: Door::Locked, event: Action::Pull });
+    assert_eq!(error.to_string(), "event Pull is not allowed in state Locked");
+    assert_eq!(door.state(), Door::Locked);
+    assert!(!door.can_fire(Action::Push));
+  }
+
+  #[test]
+  fn dot_output_of_a_small_machine() {
+    let door = Machine::new(Door::Closed, &DOOR[..2]).unwrap();
+    assert_eq!(
+      door.to_dot("door"),
+      "digraph \"door\" {\n  rankdir=LR;\n  node [shape=ellipse];\n  \"Closed\" [label=\"Closed\" style=filled fillcolor=lightblue];\n  \"Open\" [label=\"Open\"];\n  \"Closed\" -> \"Open\" [label=\"Pull\"];\n  \"Open\" -> \"Closed\" [label=\"Push\"];\n}\n"
+    );
+
+    // a starting state without any rows is still drawn, and names are quoted
+    let stuck = Machine::new(Door::Locked, &DOOR[..1]).unwrap();
+    let dot = stuck.to_dot("a \"quoted\" name");
+    assert!(dot.starts_with("digraph \"a \\\"quoted\\\" name\" {"));
+    assert!(dot.contains("  \"Locked\" [label=\"Locked\" style=filled fillcolor=lightblue];\n"));
+    assert_eq!(dot.matches("->").count(), 1);
+  }
+}
@@ -0,0 +1,213 @@
+// Two example state machines built with fsm.rs
+
+use crate::fsm::{FsmError, Machine};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Light {
+  Red,
+  Green,
+  Yellow,
+  FlashingYellow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightEvent {
+  Timer,
+  Fault,
+  Repaired,
+}
+
+// Cycles red -> green -> yellow -> red on every timer tick. A fault from any colour makes the light flash
+// yellow until it is repaired, after which it starts again at red.
+pub fn traffic_light() -> Machine<Light, LightEvent> {
+  let table = [
+    (Light::Red, LightEvent::Timer, Light::Green),
+    (Light::Green, LightEvent::Timer, Light::Yellow),
+    (Light::Yellow, LightEvent::Timer, Light::Red),
+    (Light::Red, LightEvent::Fault, Light::FlashingYellow),
+    (Light::Green, LightEvent::Fault, Light::FlashingYellow),
+    (Light::Yellow, LightEvent::Fault, Light::FlashingYellow),
+    (Light::FlashingYellow, LightEvent::Repaired, Light::Red),
+  ];
+  Machine::new(Light::Red, &table).expect("traffic light table has no conflicting rows")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vending {
+  Idle,
+  HasCoin,
+  Dispensing,
+  Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VendingEvent {
+  InsertCoin,
+  Refund,
+  Select,
+  ItemTaken,
+  SoldOut,
+  Restock,
+}
+
+pub fn vending_machine() -> Machine<Vending, VendingEvent> {
+  let table = [
+    (Vending::Idle, VendingEvent::InsertCoin, Vending::HasCoin),
+    (Vending::HasCoin, VendingEvent::Refund, Vending::Idle),
+    (Vending::HasCoin, VendingEvent::Select, Vending::Dispensing),
+    (Vending::Dispensing, VendingEvent::ItemTaken, Vending::Idle),
+    (Vending::Idle, VendingEvent::SoldOut, Vending::Empty),
+    (Vending::Empty, VendingEvent::Restock, Vending::Idle),
+  ];
+  Machine::new(Vending::Idle, &table).expect("vending machine table has no conflicting rows")
+}
+
+// A vending machine together with the number of items left in it. Every event is passed to the machine as
+// it is, and the stock only changes once the machine has accepted the event. When the last item is taken
+// the machine fires SoldOut on its own and goes from idle to empty.
+pub struct StockedVending {
+  machine: Machine<Vending, VendingEvent>,
+  stock: u32,
+}
+
+impl StockedVending {
+  // How many items a restock adds
+  pub const RESTOCK: u32 = 5;
+
+  pub fn new(stock: u32) -> StockedVending {
+    let mut machine = vending_machine();
+    if stock == 0 {
+      machine.fire(VendingEvent::SoldOut).expect("an idle machine can be sold out");
+    }
+    StockedVending { machine, stock }
+  }
+
+  pub fn state(&self) -> Vending {
+    self.machine.state()
+  }
+
+  pub fn stock(&self) -> u32 {
+    self.stock
+  }
+
+  pub fn fire(&mut self, event: VendingEvent) -> Result<Vending, FsmError<Vending, VendingEvent>> {
+    // SoldOut is guarded on the stock, a machine with items left must not claim to be empty
+    if event == VendingEvent::SoldOut && self.stock > 0 {
+      return Err(FsmError::InvalidTransition { state: self.machine.state(), event });
+    }
+    let state = self.machine.fire(event)?;
+    match event {
+      VendingEvent::ItemTaken => {
+        // a machine with no stock is empty and never gets to dispensing
+        self.stock = self.stock.saturating_sub(1);
+        if self.stock == 0 {
+          return self.machine.fire(VendingEvent::SoldOut);
+        }
+      },
+      VendingEvent::Restock => self.stock = self.stock.saturating_add(StockedVending::RESTOCK),
+      _ => {},
+    }
+    Ok(state)
+  }
+}
+
+// A vending machine holding `stock` items, run through a list of customer actions
+pub fn run_vending(stock: u32, actions: &[VendingEvent]) -> Vec<Result<Vending, FsmError<Vending, VendingEvent>>> {
+  let mut machine = StockedVending::new(stock);
+  actions.iter().map(|action| machine.fire(*action)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use VendingEvent::*;
+
+  fn buy(machine: &mut StockedVending) -> Result<Vending, FsmError<Vending, VendingEvent>> {
+    machine.fire(InsertCoin)?;
+    machine.fire(Select)?;
+    machine.fire(ItemTaken)
+  }
+
+  #[test]
+  fn taking_the_last_item_sells_out() {
+    let mut machine = StockedVending::new(2);
+    assert_eq!(buy(&mut machine), Ok(Vending::Idle));
+    assert_eq!(machine.stock(), 1);
+    assert_eq!(buy(&mut machine), Ok(Vending::Empty));
+    assert_eq!(machine.stock(), 0);
+    assert_eq!(machine.fire(InsertCoin), Err(FsmError::InvalidTransition { state: Vending::Empty, event: InsertCoin }));
+    assert_eq!(machine.fire(Restock), Ok(Vending::Idle));
+    assert_eq!(machine.stock(), StockedVending::RESTOCK);
+  }
+
+  #[test]
+  fn an_empty_machine_starts_sold_out() {
+    let mut machine = StockedVending::new(0);
+    assert_eq!(machine.state(), Vending::Empty);
+    assert!(machine.fire(InsertCoin).is_err());
+    assert_eq!(machine.fire(Restock), Ok(Vending::Idle));
+  }
+
+  #[test]
+  fn rejected_events_leave_the_stock_alone() {
+    let mut machine = StockedVending::new(3);
+    // taking an item that was never paid for
+    assert_eq!(machine.fire(ItemTaken), Err(FsmError::InvalidTransition { state: Vending::Idle, event: ItemTaken }));
+    assert_eq!(machine.stock(), 3);
+    // restocking a machine that isn't empty
+    assert_eq!(machine.fire(Restock), Err(FsmError::InvalidTransition { state: Vending::Idle, event: Restock }));
+    assert_eq!(machine.stock(), 3);
+    machine.fire(InsertCoin).unwrap();
+    assert!(machine.fire(ItemTaken).is_err());
+    assert!(machine.fire(Restock).is_err());
+    assert_eq!((machine.state(), machine.stock()), (Vending::HasCoin, 3));
+    machine.fire(Refund).unwrap();
+    assert_eq!((machine.state(), machine.stock()), (Vending::Idle, 3));
+  }
+
+  #[test]
+  fn the_last_item_taken_twice_is_reported_as_item_taken() {
+    let mut machine = StockedVending::new(1);
+    assert_eq!(buy(&mut machine), Ok(Vending::Empty));
+    // the error names the event that was sent, not the SoldOut the machine fired itself
+    assert_eq!(machine.fire(ItemTaken), Err(FsmError::InvalidTransition { state: Vending::Empty, event: ItemTaken }));
+    assert_eq!(machine.stock(), 0);
+  }
+
+  #[test]
+  fn sold_out_is_refused_while_items_are_left() {
+    let mut machine = StockedVending::new(2);
+    assert_eq!(machine.fire(SoldOut), Err(FsmError::InvalidTransition { state: Vending::Idle, event: SoldOut }));
+    assert_eq!((machine.state(), machine.stock()), (Vending::Idle, 2));
+    assert_eq!(buy(&mut machine), Ok(Vending::Idle));
+    assert!(machine.fire(SoldOut).is_err());
+    assert_eq!(buy(&mut machine), Ok(Vending::Empty));
+    // once the stock is gone the machine has sold out by itself already
+    assert_eq!(machine.fire(SoldOut), Err(FsmError::InvalidTransition { state: Vending::Empty, event: SoldOut }));
+    assert_eq!(machine.stock(), 0);
+  }
+
+  #[test]
+  fn restock_saturates() {
+    let mut machine = StockedVending { machine: vending_machine(), stock: u32::MAX - 2 };
+    machine.machine.fire(SoldOut).unwrap();
+    assert_eq!(machine.fire(Restock), Ok(Vending::Idle));
+    assert_eq!(machine.stock(), u32::MAX);
+  }
+
+  #[test]
+  fn run_vending_reports_every_action() {
+    let results = run_vending(1, &[Select, InsertCoin, Select, ItemTaken, InsertCoin, Restock]);
+    assert_eq!(
+      results,
+      vec![
+        Err(FsmError::InvalidTransition { state: Vending::Idle, event: Select }),
+        Ok(Vending::HasCoin),
+        Ok(Vending::Dispensing),
+        Ok(Vending::Empty),
+        Err(FsmError::InvalidTransition { state: Vending::Empty, event: InsertCoin }),
+        Ok(Vending::Idle),
+      ]
+    );
+  }
+}
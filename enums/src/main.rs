@@ -1,36 +1,85 @@
-// Enum without values
-#[derive(Debug)]
-enum Direction {
-  North,
-  South,
-  East,
-  West
+mod compass;
+mod direction;
+mod fsm;
+mod machines;
+mod pathfinding;
+mod robot;
+mod scene;
+mod shape;
+
+use std::{env, fs, process};
+
+use compass::{Compass16, Compass8, CompassRose};
+use direction::Direction;
+use machines::{LightEvent, StockedVending, VendingEvent};
+use pathfinding::{Algorithm, Maze};
+use robot::{Blocked, Grid, Robot};
+use shape::Shape;
+
+// Moving around means turning clockwise to the next compass point
+fn move_around(dir: &Direction) -> Direction {
+  dir.turn_right()
 }
 
-// Enum with values
-#[derive(Debug)]
-enum Shape {
-  Circle(f64), // f64 radius of circle
-  Square(f64), // f64 side of square
-  Rectangle(f64, f64) // f64 length and width of the rectangle
+// `cargo run -- shapes <file>` prints the area / perimeter report of a scene file, see scene.rs for the format
+fn run_shapes_file(path: &str) -> Result<(), String> {
+  let text = fs::read_to_string(path).map_err(|e| format!("could not read `{path}`: {e}"))?;
+  let shapes = scene::parse_scene(&text).map_err(|errors| format!("{path}:\n{}", errors.join("\n")))?;
+  print!("{}", scene::report(&shapes));
+  Ok(())
 }
 
-fn move_around(dir: &Direction) -> Direction {
-  Direction::East
+// `cargo run -- robot <file>` runs a robot program file, see robot.rs for the format
+fn run_robot_file(path: &str) -> Result<(), String> {
+  let text = fs::read_to_string(path).map_err(|e| format!("could not read `{path}`: {e}"))?;
+  let scenario = robot::parse_scenario(&text).map_err(|e| format!("{path}: {e}"))?;
+  print_report(&scenario.run());
+  Ok(())
 }
 
-fn calc_area(shape: &Shape) -> f64 {
-  match shape {
-    Shape::Circle(r) => 3.14 * r * r,
-    Shape::Square(s) => s * s,
-    Shape::Rectangle(l, w) => l * w
+fn print_report(report: &robot::Report) {
+  for collision in &report.collisions {
+    let what = match collision.blocked_by {
+      Blocked::Wall => "the edge of the grid",
+      Blocked::Obstacle => "an obstacle",
+    };
+    println!(
+      "step {}: bumped into {} at {:?} while standing on {:?}",
+      collision.step + 1,
+      what,
+      collision.target,
+      collision.at
+    );
   }
+  println!(
+    "robot ended at ({}, {}) facing {:?} after {} collision(s)",
+    report.robot.x,
+    report.robot.y,
+    report.robot.heading,
+    report.collisions.len()
+  );
 }
 
 fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  if !args.is_empty() {
+    let result = match args.as_slice() {
+      [command, path] if command == "shapes" => run_shapes_file(path),
+      [command, path] if command == "robot" => run_robot_file(path),
+      _ => Err(String::from("usage: enums shapes <scene file> | enums robot <program file>")),
+    };
+    if let Err(e) = result {
+      eprintln!("error: {e}");
+      process::exit(1);
+    }
+    return;
+  }
+
   let curr_dir = Direction::North;
   let mut inc_dir = Direction::North;
   // inc_dir = "temp" won't work since inc_dir has type Direction
+  inc_dir = move_around(&inc_dir);
+  println!("Incremented direction: {:?}", inc_dir);
 
   let new_dir = move_around(&curr_dir);
   println!("Old direction: {:?}", curr_dir);
@@ -38,9 +87,142 @@ fn main() {
 
   let circle = Shape::Circle(5.0);
   let square: Shape = Shape::Square(4.5);
-  let rectangle: Shape = Shape::Rectangle(3.0, 4.0);
 
-  println!("Area of circle: {}", calc_area(&circle));
-  println!("Area of circle: {}", calc_area(&square));
-  println!("Area of circle: {}", calc_area(&rectangle));
+  // Shapes described as text, each line becomes one Shape value
+  let scene_text = "circle 5\nsquare 4.5\nrect 3 4\n";
+  match scene::parse_scene(scene_text) {
+    Ok(shapes) => print!("{}", scene::report(&shapes)),
+    Err(errors) => errors.iter().for_each(|e| println!("error: {e}")),
+  }
+  // Mistakes are reported with the line they are on
+  if let Err(errors) = scene::parse_scene("circle 5\nhexagon 2\nrect 3\nsquare -1\n") {
+    errors.iter().for_each(|e| println!("error: {e}"));
+  }
+
+  // The constructors return a Result so impossible shapes are caught right away
+  let shapes = [
+    Shape::triangle(3.0, 4.0, 5.0),
+    Shape::ellipse(5.0, 3.0),
+    Shape::regular_polygon(6, 2.0),
+    Shape::polygon(vec![(0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (0.0, 3.0)]),
+    Shape::circle(-1.0),
+    Shape::square(0.0),
+    Shape::rectangle(f64::NAN, 2.0),
+    Shape::triangle(1.0, 2.0, 10.0),
+  ];
+  for shape in shapes {
+    match shape {
+      Ok(shape) => {
+        let bbox = shape.bounding_box();
+        println!(
+          "{:?}: area {:.3}, perimeter {:.3}, bounding box {:.3} x {:.3}",
+          shape,
+          shape.area(),
+          shape.perimeter(),
+          bbox.width,
+          bbox.height
+        );
+      },
+      Err(e) => println!("invalid shape: {e}"),
+    }
+  }
+
+  match circle.scaled(2.0) {
+    Ok(bigger) => println!("circle scaled by 2: {:?} with area {:.3}", bigger, bigger.area()),
+    Err(e) => println!("could not scale: {e}"),
+  }
+  println!("square is valid: {:?}", square.validate());
+
+  for dir in Direction::ALL {
+    println!("{:?}: left {:?}, right {:?}, opposite {:?}, step {:?}", dir, dir.turn_left(), dir.turn_right(), dir.opposite(), dir.unit_vector());
+  }
+
+  // A robot on a 5x5 grid with one obstacle in its way
+  let mut grid = Grid::new(5, 5).expect("grid size is positive");
+  grid.add_obstacle(2, 2).expect("obstacle is on the grid");
+  let commands = robot::parse_commands("FFRFFLB FFFFF").expect("only known commands");
+  print_report(&Robot::new(0, 0, Direction::North).run(&commands, &grid));
+  if let Err(e) = robot::parse_commands("FFXR") {
+    println!("error: {e}");
+  }
+
+  // 8 and 16 point compass roses
+  for point in Compass8::iter() {
+    println!("{point} ({point:#}) is at {} degrees, one step moves by {:?}", point.degrees(), point.unit_vector());
+  }
+  println!("all 16 points: {}", Compass16::iter().map(|p| p.to_string()).collect::<Vec<_>>().join(" "));
+  println!("200 degrees is closest to {:?} / {:?}", Compass8::from_degrees(200.0), Compass16::from_degrees(200.0));
+  println!("-30 degrees is closest to {:?}", Compass16::from_degrees(-30.0).map(|p| p.name()));
+  for text in ["NE", "north-east", "South West", "nnw", "up"] {
+    println!("{text:?} as Compass8: {:?}, as Compass16: {:?}", text.parse::<Compass8>(), text.parse::<Compass16>());
+  }
+  let heading = Compass8::from(Direction::East);
+  println!(
+    "east turned 3 points clockwise: {}, opposite: {}, diagonal: {}, as Compass16: {}",
+    heading.rotate(3),
+    heading.opposite(),
+    heading.rotate(3).is_diagonal(),
+    Compass16::from(heading.rotate(3))
+  );
+  println!("west on the 16 point rose is at {} degrees", Compass16::from(Direction::West).degrees());
+
+  // Shortest paths through a maze, the 9s are swamp that is expensive to walk through
+  let maze = Maze::parse(
+    "##########\n\
+     #S...#...#\n\
+     #.##.#.#.#\n\
+     #.#..999.#\n\
+     #.#.##.#.#\n\
+     #...#..#G#\n\
+     ##########\n",
+  )
+  .expect("maze is well formed");
+  for algorithm in [Algorithm::Bfs, Algorithm::Dijkstra, Algorithm::AStar] {
+    match maze.solve(algorithm) {
+      Some(path) => {
+        println!("{:?}: {} steps costing {}, looked at {} cells", algorithm, path.moves.len(), path.cost, path.visited);
+        println!("{:?}", path.moves);
+        print!("{}", maze.render(&path));
+      },
+      None => println!("{:?}: no path", algorithm),
+    }
+  }
+  let walled_in = Maze::parse("S.#..\n..#.G\n").expect("maze is well formed");
+  println!("walled in goal reachable: {}", walled_in.solve(Algorithm::AStar).is_some());
+
+  // State machines declared as transition tables
+  let mut light = machines::traffic_light();
+  for event in [LightEvent::Timer, LightEvent::Timer, LightEvent::Repaired, LightEvent::Fault, LightEvent::Repaired] {
+    match light.fire(event) {
+      Ok(state) => println!("{:?} -> light is {:?}", event, state),
+      Err(e) => println!("error: {e} (still {:?}, allowed: {:?})", light.state(), light.allowed_events()),
+    }
+  }
+  print!("{}", light.to_dot("traffic_light"));
+
+  let actions = [
+    VendingEvent::Select,
+    VendingEvent::InsertCoin,
+    VendingEvent::Select,
+    VendingEvent::ItemTaken,
+    VendingEvent::InsertCoin,
+    VendingEvent::Refund,
+  ];
+  for (action, result) in actions.iter().zip(machines::run_vending(1, &actions)) {
+    match result {
+      Ok(state) => println!("{:?} -> vending machine is {:?}", action, state),
+      Err(e) => println!("{:?} -> error: {e}", action),
+    }
+  }
+  let mut stocked = StockedVending::new(0);
+  println!("a machine with no stock starts {:?}", stocked.state());
+  if stocked.fire(VendingEvent::Restock).is_ok() {
+    println!("restocked, now {:?} with {} items", stocked.state(), stocked.stock());
+  }
+  let vending = machines::vending_machine();
+  println!("can select without a coin: {}", vending.can_fire(VendingEvent::Select));
+  println!("a coin leads to {:?}", vending.target(VendingEvent::InsertCoin));
+  if let Err(e) = fsm::Machine::new(Direction::North, &[(Direction::North, 'L', Direction::West), (Direction::North, 'L', Direction::East)]) {
+    println!("error: {e}");
+  }
 }
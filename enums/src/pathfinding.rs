@@ -0,0 +1,336 @@
+// Finding a way through a maze drawn as ASCII text, moving one Direction at a time.
+//   #  wall                      S  start (cost 1)
+//   .  open ground (cost 1)      G  goal (cost 1)
+//   1-9  terrain that costs that much to step onto
+//   space  open ground too
+// The first line of the map is the northern edge, so moving North goes one line up. Every line is a row, blank
+// ones included, and lines shorter than the longest one are padded with walls.
+//
+// Three algorithms are available. BFS finds the path with the fewest steps and ignores terrain costs,
+// Dijkstra finds the cheapest path, A* finds the cheapest path too but looks at fewer cells by heading
+// towards the goal first. Neighbours are always tried in the order North, East, South, West, so when
+// several paths are equally good the same one is picked every time.
+
+use std::{cmp::Reverse, collections::BinaryHeap, collections::VecDeque};
+
+use crate::direction::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+  Bfs,
+  Dijkstra,
+  AStar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pos {
+  pub x: usize,
+  pub y: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+  Wall,
+  // cost of stepping onto the cell
+  Open(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Maze {
+  width: usize,
+  height: usize,
+  cells: Vec<Cell>,
+  pub start: Option<Pos>,
+  pub goal: Option<Pos>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+  pub start: Pos,
+  pub moves: Vec<Direction>,
+  // sum of the costs of every cell stepped onto (the start cell is free)
+  pub cost: u32,
+  // how many cells the algorithm looked at, to compare how much work each one did
+  pub visited: usize,
+}
+
+impl Maze {
+  pub fn parse(text: &str) -> Result<Maze, String> {
+    let mut lines: Vec<&str> = text.lines().collect();
+    // blank lines after the map are not rows of it, a blank line inside the map is a row of walls
+    while lines.last().is_some_and(|line| line.is_empty()) {
+      lines.pop();
+    }
+    let height = lines.len();
+    let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    if width == 0 {
+      return Err(String::from("the map is empty"));
+    }
+
+    let mut maze = Maze { width, height, cells: vec![Cell::Wall; width * height], start: None, goal: None };
+    for (y, line) in lines.iter().enumerate() {
+      // cells past the end of a short line stay walls
+      for (x, ch) in line.chars().enumerate() {
+        let cell = match ch {
+          '#' => Cell::Wall,
+          '.' | ' ' => Cell::Open(1),
+          'S' | 'G' => {
+            let marker = if ch == 'S' { &mut maze.start } else { &mut maze.goal };
+            if marker.is_some() {
+              return Err(format!("line {}: the map has more than one `{ch}`", y + 1));
+            }
+            *marker = Some(Pos { x, y });
+            Cell::Open(1)
+          },
+          '1'..='9' => Cell::Open(ch.to_digit(10).unwrap()),
+          _ => return Err(format!("line {}, column {}: unknown map character `{ch}`", y + 1, x + 1)),
+        };
+        maze.cells[y * width + x] = cell;
+      }
+    }
+    Ok(maze)
+  }
+
+  fn index(&self, pos: Pos) -> usize {
+    pos.y * self.width + pos.x
+  }
+
+  // Cost of entering the cell, None for walls
+  fn cost(&self, pos: Pos) -> Option<u32> {
+    match self.cells[self.index(pos)] {
+      Cell::Wall => None,
+      Cell::Open(cost) => Some(cost),
+    }
+  }
+
+  // The neighbour in the given direction, None at the edge of the map
+  fn step(&self, pos: Pos, direction: Direction) -> Option<Pos> {
+    let (dx, dy) = direction.unit_vector();
+    // y grows to the north in unit_vector but lines are counted from the top of the map
+    let x = pos.x.checked_add_signed(dx as isize)?;
+    let y = pos.y.checked_add_signed(-dy as isize)?;
+    if x < self.width && y < self.height {
+      Some(Pos { x, y })
+    } else {
+      None
+    }
+  }
+
+  // Open neighbours in the order North, East, South, West together with the direction leading to them
+  fn neighbours(&self, pos: Pos) -> impl Iterator<Item = (Direction, Pos, u32)> + '_ {
+    Direction::ALL.into_iter().filter_map(move |dir| {
+      let next = self.step(pos, dir)?;
+      Some((dir, next, self.cost(next)?))
+    })
+  }
+
+  // The path from the `S` to the `G` of the map
+  pub fn solve(&self, algorithm: Algorithm) -> Option<Path> {
+    self.find_path(self.start?, self.goal?, algorithm)
+  }
+
+  pub fn find_path(&self, from: Pos, to: Pos, algorithm: Algorithm) -> Option<Path> {
+    if from.x >= self.width || from.y >= self.height || to.x >= self.width || to.y >= self.height {
+      return None;
+    }
+    self.cost(from)?;
+    self.cost(to)?;
+    match algorithm {
+      Algorithm::Bfs => self.bfs(from, to),
+      Algorithm::Dijkstra => self.cheapest(from, to, |_| 0),
+      // Manhattan distance never overestimates since every step costs at least 1
+      Algorithm::AStar => self.cheapest(from, to, |pos| (pos.x.abs_diff(to.x) + pos.y.abs_diff(to.y)) as u32),
+    }
+  }
+
+  fn bfs(&self, from: Pos, to: Pos) -> Option<Path> {
+    let mut came_from: Vec<Option<(Pos, Direction)>> = vec![None; self.cells.len()];
+    let mut seen = vec![false; self.cells.len()];
+    let mut queue = VecDeque::from([from]);
+    seen[self.index(from)] = true;
+    let mut visited = 0;
+
+    while let Some(pos) = queue.pop_front() {
+      visited += 1;
+      if pos == to {
+        return Some(self.build_path(from, to, &came_from, visited));
+      }
+      for (dir, next, _) in self.neighbours(pos) {
+        let idx = self.index(next);
+        if !seen[idx] {
+          seen[idx] = true;
+          came_from[idx] = Some((pos, dir));
+          queue.push_back(next);
+        }
+      }
+    }
+    None
+  }
+
+  // Dijkstra when `heuristic` is always 0, A* otherwise. The heap is ordered by estimated total cost, then by
+  // the heuristic (prefer cells closer to the goal) and then by insertion order to keep ties predictable.
+  fn cheapest(&self, from: Pos, to: Pos, heuristic: impl Fn(Pos) -> u32) -> Option<Path> {
+    let mut best_cost = vec![u32::MAX; self.cells.len()];
+    let mut came_from: Vec<Option<(Pos, Direction)>> = vec![None; self.cells.len()];
+    let mut done = vec![false; self.cells.len()];
+    let mut heap = BinaryHeap::new();
+    let mut pushed = 0usize;
+    best_cost[self.index(from)] = 0;
+    heap.push(Reverse((heuristic(from), heuristic(from), pushed, from.y, from.x)));
+    let mut visited = 0;
+
+    while let Some(Reverse((_, _, _, y, x))) = heap.pop() {
+      let pos = Pos { x, y };
+      let idx = self.index(pos);
+      if done[idx] {
+        continue;
+      }
+      done[idx] = true;
+      visited += 1;
+      if pos == to {
+        return Some(self.build_path(from, to, &came_from, visited));
+      }
+      for (dir, next, step_cost) in self.neighbours(pos) {
+        let next_idx = self.index(next);
+        let cost = best_cost[idx] + step_cost;
+        if cost < best_cost[next_idx] {
+          best_cost[next_idx] = cost;
+          came_from[next_idx] = Some((pos, dir));
+          pushed += 1;
+          let h = heuristic(next);
+          heap.push(Reverse((cost + h, h, pushed, next.y, next.x)));
+        }
+      }
+    }
+    None
+  }
+
+  fn build_path(&self, from: Pos, to: Pos, came_from: &[Option<(Pos, Direction)>], visited: usize) -> Path {
+    let mut moves = Vec::new();
+    let mut cost = 0;
+    let mut pos = to;
+    while pos != from {
+      let (prev, dir) = came_from[self.index(pos)].expect("every reached cell knows where it was reached from");
+      moves.push(dir);
+      cost += self.cost(pos).unwrap_or(0);
+      pos = prev;
+    }
+    moves.reverse();
+    Path { start: from, moves, cost, visited }
+  }
+
+  // The map with the path drawn on it as arrows pointing the way to go
+  pub fn render(&self, path: &Path) -> String {
+    let mut grid: Vec<Vec<char>> = (0..self.height)
+      .map(|y| {
+        (0..self.width)
+          .map(|x| match self.cells[y * self.width + x] {
+            Cell::Wall => '#',
+            Cell::Open(1) => '.',
+            Cell::Open(cost) => char::from_digit(cost, 10).unwrap_or('?'),
+          })
+          .collect()
+      })
+      .collect();
+
+    let mut pos = path.start;
+    for dir in &path.moves {
+      grid[pos.y][pos.x] = match dir {
+        Direction::North => '^',
+        Direction::East => '>',
+        Direction::South => 'v',
+        Direction::West => '<',
+      };
+      pos = self.step(pos, *dir).expect("path stays on the map");
+    }
+    for (marker, ch) in [(self.start, 'S'), (self.goal, 'G')] {
+      if let Some(p) = marker {
+        grid[p.y][p.x] = ch;
+      }
+    }
+
+    grid.iter().map(|row| row.iter().collect::<String>() + "\n").collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ALGORITHMS: [Algorithm; 3] = [Algorithm::Bfs, Algorithm::Dijkstra, Algorithm::AStar];
+
+  #[test]
+  fn walled_in_goal_has_no_path() {
+    let maze = Maze::parse("S.#..\n..#.G\n").unwrap();
+    for algorithm in ALGORITHMS {
+      assert_eq!(maze.solve(algorithm), None, "{algorithm:?}");
+    }
+    // surrounded on every side, in the middle of open ground
+    let maze = Maze::parse("S....\n..#..\n.#G#.\n..#..\n").unwrap();
+    for algorithm in ALGORITHMS {
+      assert_eq!(maze.solve(algorithm), None, "{algorithm:?}");
+    }
+  }
+
+  #[test]
+  fn paths_into_walls_or_off_the_map_are_none() {
+    let maze = Maze::parse("S.#\n..G\n").unwrap();
+    for algorithm in ALGORITHMS {
+      assert_eq!(maze.find_path(Pos { x: 0, y: 0 }, Pos { x: 2, y: 0 }, algorithm), None);
+      assert_eq!(maze.find_path(Pos { x: 0, y: 0 }, Pos { x: 3, y: 0 }, algorithm), None);
+      assert_eq!(maze.find_path(Pos { x: 0, y: 5 }, Pos { x: 2, y: 1 }, algorithm), None);
+    }
+  }
+
+  #[test]
+  fn equal_cost_paths_are_picked_the_same_way_every_time() {
+    // every path from S to G with only East and South moves costs the same
+    let maze = Maze::parse("S...\n....\n....\n...G\n").unwrap();
+    for algorithm in ALGORITHMS {
+      let first = maze.solve(algorithm).unwrap();
+      assert_eq!(first.moves.len(), 6);
+      assert_eq!(first.cost, 6);
+      for _ in 0..20 {
+        assert_eq!(maze.solve(algorithm).as_ref(), Some(&first), "{algorithm:?}");
+        assert_eq!(Maze::parse("S...\n....\n....\n...G\n").unwrap().solve(algorithm).as_ref(), Some(&first));
+      }
+    }
+    // East is tried before South, so BFS reaches every cell of the top row first and goes along it
+    use Direction::{East, South};
+    assert_eq!(maze.solve(Algorithm::Bfs).unwrap().moves, vec![East, East, East, South, South, South]);
+  }
+
+  #[test]
+  fn cheapest_path_goes_around_expensive_terrain() {
+    let maze = Maze::parse("S9G\n...\n").unwrap();
+    assert_eq!(maze.solve(Algorithm::Bfs).unwrap().cost, 10);
+    for algorithm in [Algorithm::Dijkstra, Algorithm::AStar] {
+      let path = maze.solve(algorithm).unwrap();
+      assert_eq!((path.moves.len(), path.cost), (4, 4));
+    }
+  }
+
+  #[test]
+  fn trailing_spaces_are_open_ground() {
+    // the only way to G is through the spaces at the end of the first line
+    let maze = Maze::parse("S.  \n### \n  #G").unwrap();
+    assert_eq!(maze.width, 4);
+    for algorithm in ALGORITHMS {
+      assert_eq!(maze.solve(algorithm).map(|path| path.cost), Some(5), "{algorithm:?}");
+    }
+  }
+
+  #[test]
+  fn blank_lines_are_rows_and_short_lines_are_padded() {
+    let maze = Maze::parse("S.\n\n.G\n\n").unwrap();
+    assert_eq!((maze.width, maze.height), (2, 3));
+    assert_eq!(maze.goal, Some(Pos { x: 1, y: 2 }));
+    // the blank row is all walls, so the goal can't be reached
+    assert_eq!(maze.solve(Algorithm::Bfs), None);
+
+    let maze = Maze::parse("S...\n.\n...G").unwrap();
+    assert_eq!(maze.cost(Pos { x: 0, y: 1 }), Some(1));
+    assert_eq!(maze.cost(Pos { x: 1, y: 1 }), None);
+    assert_eq!(maze.solve(Algorithm::Bfs).map(|path| path.moves.len()), Some(5));
+  }
+}
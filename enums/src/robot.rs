@@ -0,0 +1,322 @@
+// A robot driving around a bounded grid, steered by single letter commands:
+//   F - one step forward, B - one step backward (without turning), L - turn left, R - turn right
+// A step into a wall or an obstacle is not taken, the robot stays where it is and the collision is recorded.
+//
+// Programs can be stored in a file, one instruction per line (blank lines and lines starting with # are skipped):
+//   grid 10 8          width and height, cells go from (0, 0) to (width - 1, height - 1)
+//   obstacle 3 4       a blocked cell, as many as needed
+//   start 0 0 N        start cell and heading (N, E, S or W), defaults to 0 0 N
+//   program FFRFFLB    commands, several program lines are run one after the other
+
+use std::collections::HashSet;
+
+use crate::direction::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+  Forward,
+  Backward,
+  Left,
+  Right,
+}
+
+impl Command {
+  pub fn parse(letter: char) -> Option<Command> {
+    match letter.to_ascii_uppercase() {
+      'F' => Some(Command::Forward),
+      'B' => Some(Command::Backward),
+      'L' => Some(Command::Left),
+      'R' => Some(Command::Right),
+      _ => None,
+    }
+  }
+}
+
+// Turns a command string like "FFRFFLB" into Commands, whitespace is ignored
+pub fn parse_commands(program: &str) -> Result<Vec<Command>, String> {
+  program
+    .chars()
+    .enumerate()
+    .filter(|(_, ch)| !ch.is_whitespace())
+    .map(|(idx, ch)| Command::parse(ch).ok_or(format!("unknown command `{ch}` at position {}", idx + 1)))
+    .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+  pub width: i32,
+  pub height: i32,
+  obstacles: HashSet<(i32, i32)>,
+}
+
+impl Grid {
+  pub fn new(width: i32, height: i32) -> Result<Grid, String> {
+    if width <= 0 || height <= 0 {
+      return Err(format!("grid must be at least 1x1, got {width}x{height}"));
+    }
+    Ok(Grid { width, height, obstacles: HashSet::new() })
+  }
+
+  pub fn add_obstacle(&mut self, x: i32, y: i32) -> Result<(), String> {
+    if !self.in_bounds(x, y) {
+      return Err(format!("obstacle ({x}, {y}) is outside the {}x{} grid", self.width, self.height));
+    }
+    self.obstacles.insert((x, y));
+    Ok(())
+  }
+
+  pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+    (0..self.width).contains(&x) && (0..self.height).contains(&y)
+  }
+
+  pub fn is_obstacle(&self, x: i32, y: i32) -> bool {
+    self.obstacles.contains(&(x, y))
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blocked {
+  Wall,
+  Obstacle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collision {
+  // index of the command that could not be carried out (0-based)
+  pub step: usize,
+  // where the robot was and the cell it tried to move into
+  pub at: (i32, i32),
+  pub target: (i32, i32),
+  pub blocked_by: Blocked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Robot {
+  pub x: i32,
+  pub y: i32,
+  pub heading: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+  pub robot: Robot,
+  pub collisions: Vec<Collision>,
+}
+
+impl Robot {
+  pub fn new(x: i32, y: i32, heading: Direction) -> Robot {
+    Robot { x, y, heading }
+  }
+
+  // Carries out one command, returns what blocked the move if the robot could not move
+  pub fn execute(&mut self, command: Command, grid: &Grid) -> Result<(), (Blocked, (i32, i32))> {
+    let moving = match command {
+      Command::Left => {
+        self.heading = self.heading.turn_left();
+        return Ok(());
+      },
+      Command::Right => {
+        self.heading = self.heading.turn_right();
+        return Ok(());
+      },
+      Command::Forward => self.heading,
+      Command::Backward => self.heading.opposite(),
+    };
+    let (dx, dy) = moving.unit_vector();
+    // The fields are public, so the robot may stand anywhere, even at i32::MAX. Cells go up to width - 1 at most,
+    // so a step that saturates always ends outside the grid and is reported as a wall instead of overflowing.
+    let target = (self.x.saturating_add(dx), self.y.saturating_add(dy));
+    if !grid.in_bounds(target.0, target.1) {
+      return Err((Blocked::Wall, target));
+    }
+    if grid.is_obstacle(target.0, target.1) {
+      return Err((Blocked::Obstacle, target));
+    }
+    (self.x, self.y) = target;
+    Ok(())
+  }
+
+  pub fn run(mut self, commands: &[Command], grid: &Grid) -> Report {
+    let mut collisions = Vec::new();
+    for (step, command) in commands.iter().enumerate() {
+      let at = (self.x, self.y);
+      if let Err((blocked_by, target)) = self.execute(*command, grid) {
+        collisions.push(Collision { step, at, target, blocked_by });
+      }
+    }
+    Report { robot: self, collisions }
+  }
+}
+
+// Everything a program file describes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+  pub grid: Grid,
+  pub start: Robot,
+  pub commands: Vec<Command>,
+}
+
+impl Scenario {
+  pub fn run(&self) -> Report {
+    self.start.run(&self.commands, &self.grid)
+  }
+}
+
+fn parse_number(word: Option<&str>, what: &str) -> Result<i32, String> {
+  let word = word.ok_or(format!("missing {what}"))?;
+  word.parse().map_err(|_| format!("{what} must be a whole number, got `{word}`"))
+}
+
+// Parses the file format described at the top of this file. Errors mention the line they were found on.
+pub fn parse_scenario(text: &str) -> Result<Scenario, String> {
+  let mut grid: Option<Grid> = None;
+  let mut obstacles = Vec::new();
+  let mut start = Robot::new(0, 0, Direction::North);
+  let mut commands = Vec::new();
+
+  for (idx, line) in text.lines().enumerate() {
+    let line_no = idx + 1;
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let mut words = line.split_whitespace();
+    let keyword = words.next().unwrap_or_default();
+    let result: Result<(), String> = match keyword {
+      "grid" => parse_number(words.next(), "width").and_then(|width| {
+        let height = parse_number(words.next(), "height")?;
+        grid = Some(Grid::new(width, height)?);
+        Ok(())
+      }),
+      "obstacle" => parse_number(words.next(), "x").and_then(|x| {
+        let y = parse_number(words.next(), "y")?;
+        obstacles.push((line_no, x, y));
+        Ok(())
+      }),
+      "start" => parse_number(words.next(), "x").and_then(|x| {
+        let y = parse_number(words.next(), "y")?;
+        let heading = words.next().ok_or(String::from("missing heading"))?;
+        let mut letters = heading.chars();
+        let heading = match (letters.next().and_then(Direction::from_letter), letters.next()) {
+          (Some(direction), None) => direction,
+          _ => return Err(format!("heading must be one of N, E, S or W, got `{heading}`")),
+        };
+        start = Robot::new(x, y, heading);
+        Ok(())
+      }),
+      "program" => {
+        let program: String = words.by_ref().collect();
+        parse_commands(&program).map(|parsed| commands.extend(parsed))
+      },
+      other => Err(format!("unknown instruction `{other}`")),
+    };
+    result.map_err(|e| format!("line {line_no}: {e}"))?;
+    if let Some(extra) = words.next() {
+      return Err(format!("line {line_no}: unexpected `{extra}`"));
+    }
+  }
+
+  let mut grid = grid.ok_or(String::from("missing `grid` instruction"))?;
+  for (line_no, x, y) in obstacles {
+    grid.add_obstacle(x, y).map_err(|e| format!("line {line_no}: {e}"))?;
+  }
+  if !grid.in_bounds(start.x, start.y) || grid.is_obstacle(start.x, start.y) {
+    return Err(format!("start ({}, {}) is not a free cell of the grid", start.x, start.y));
+  }
+  Ok(Scenario { grid, start, commands })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn run(program: &str, grid: &Grid, start: Robot) -> Report {
+    start.run(&parse_commands(program).unwrap(), grid)
+  }
+
+  #[test]
+  fn commands_are_parsed_from_letters() {
+    use Command::*;
+    assert_eq!(parse_commands("FfB l R"), Ok(vec![Forward, Forward, Backward, Left, Right]));
+    assert_eq!(parse_commands(""), Ok(vec![]));
+    assert_eq!(parse_commands("FF\tR\n"), Ok(vec![Forward, Forward, Right]));
+    // the position counts the whitespace too, so it points at the character in the original text
+    assert_eq!(parse_commands("F F X"), Err(String::from("unknown command `X` at position 5")));
+    assert_eq!(Command::parse('x'), None);
+  }
+
+  #[test]
+  fn turning_does_not_move() {
+    let grid = Grid::new(3, 3).unwrap();
+    let start = Robot::new(1, 1, Direction::North);
+    assert_eq!(run("L", &grid, start).robot.heading, Direction::West);
+    assert_eq!(run("R", &grid, start).robot.heading, Direction::East);
+    assert_eq!(run("LL", &grid, start).robot.heading, Direction::South);
+    assert_eq!(run("LLLL", &grid, start).robot, start);
+    assert_eq!(run("RRRR", &grid, start).robot, start);
+    assert_eq!(run("LR", &grid, start).robot, start);
+  }
+
+  #[test]
+  fn moving_forward_and_backward() {
+    let grid = Grid::new(5, 5).unwrap();
+    let report = run("FFRFF", &grid, Robot::new(0, 0, Direction::North));
+    assert_eq!(report.robot, Robot::new(2, 2, Direction::East));
+    assert!(report.collisions.is_empty());
+    // backward keeps the heading
+    let report = run("BB", &grid, Robot::new(2, 2, Direction::East));
+    assert_eq!(report.robot, Robot::new(0, 2, Direction::East));
+  }
+
+  #[test]
+  fn walls_and_obstacles_stop_the_robot() {
+    let mut grid = Grid::new(3, 3).unwrap();
+    grid.add_obstacle(1, 1).unwrap();
+    let report = run("BFFFFRF", &grid, Robot::new(0, 0, Direction::North));
+    assert_eq!(report.robot, Robot::new(1, 2, Direction::East));
+    assert_eq!(
+      report.collisions,
+      vec![
+        Collision { step: 0, at: (0, 0), target: (0, -1), blocked_by: Blocked::Wall },
+        Collision { step: 3, at: (0, 2), target: (0, 3), blocked_by: Blocked::Wall },
+        Collision { step: 4, at: (0, 2), target: (0, 3), blocked_by: Blocked::Wall },
+      ]
+    );
+    let mut robot = Robot::new(1, 0, Direction::North);
+    assert_eq!(robot.execute(Command::Forward, &grid), Err((Blocked::Obstacle, (1, 1))));
+    assert_eq!(robot, Robot::new(1, 0, Direction::North));
+    assert!(grid.add_obstacle(3, 0).is_err());
+    assert!(Grid::new(0, 4).is_err());
+  }
+
+  #[test]
+  fn steps_at_the_edge_of_i32_do_not_overflow() {
+    let grid = Grid::new(i32::MAX, i32::MAX).unwrap();
+    let mut robot = Robot::new(i32::MAX - 1, i32::MAX - 1, Direction::East);
+    assert_eq!(robot.execute(Command::Forward, &grid), Err((Blocked::Wall, (i32::MAX, i32::MAX - 1))));
+
+    // a robot placed outside the grid by hand
+    let mut robot = Robot::new(i32::MAX, i32::MIN, Direction::North);
+    assert_eq!(robot.execute(Command::Backward, &grid), Err((Blocked::Wall, (i32::MAX, i32::MIN))));
+    robot.heading = Direction::West;
+    assert_eq!(robot.execute(Command::Backward, &grid), Err((Blocked::Wall, (i32::MAX, i32::MIN))));
+    assert_eq!(robot, Robot::new(i32::MAX, i32::MIN, Direction::West));
+  }
+
+  #[test]
+  fn scenario_files() {
+    let text = "# a small room\ngrid 4 3\nobstacle 1 1\nstart 0 0 E\nprogram FF\nprogram LFF\n";
+    let scenario = parse_scenario(text).unwrap();
+    assert_eq!(scenario.commands.len(), 5);
+    let report = scenario.run();
+    assert_eq!(report.robot, Robot::new(2, 2, Direction::North));
+    assert!(report.collisions.is_empty());
+
+    assert_eq!(parse_scenario("program F"), Err(String::from("missing `grid` instruction")));
+    assert_eq!(parse_scenario("grid 4\n"), Err(String::from("line 1: missing height")));
+    assert_eq!(parse_scenario("grid 4 4\nstart 0 0 Q"), Err(String::from("line 2: heading must be one of N, E, S or W, got `Q`")));
+    assert_eq!(parse_scenario("grid 4 4\nobstacle 9 9"), Err(String::from("line 2: obstacle (9, 9) is outside the 4x4 grid")));
+    assert_eq!(parse_scenario("grid 4 4 4"), Err(String::from("line 1: unexpected `4`")));
+    assert_eq!(parse_scenario("grid 2 2\nobstacle 0 0"), Err(String::from("start (0, 0) is not a free cell of the grid")));
+  }
+}
@@ -0,0 +1,92 @@
+// A scene is a plain text list of shapes, one per line. Blank lines and lines starting with # are skipped.
+//   circle 5              radius
+//   square 4.5            side
+//   rect 3 4              length and width (`rectangle` works too)
+//   triangle 3 4 5        the three sides
+//   ellipse 5 3           semi-major and semi-minor axis
+//   regular 6 2           number of sides and side length of a regular polygon
+//   polygon 0 0 4 0 4 3   x y of every vertex
+// `Display` for Shape (in shape.rs) writes a shape back in this format.
+
+use crate::shape::Shape;
+
+// How many numbers each kind of shape takes
+fn expect_count(kind: &str, numbers: &[f64], count: usize) -> Result<(), String> {
+  if numbers.len() == count {
+    Ok(())
+  } else {
+    Err(format!("`{kind}` takes {count} number(s) but got {}", numbers.len()))
+  }
+}
+
+// Parses a single line such as "rect 3 4"
+pub fn parse_shape(line: &str) -> Result<Shape, String> {
+  let mut words = line.split_whitespace();
+  let kind = words.next().ok_or(String::from("empty line"))?;
+  let numbers = words
+    .map(|word| word.parse::<f64>().map_err(|_| format!("`{word}` is not a number")))
+    .collect::<Result<Vec<f64>, String>>()?;
+
+  match kind.to_ascii_lowercase().as_str() {
+    "circle" => expect_count(kind, &numbers, 1).and_then(|_| Shape::circle(numbers[0])),
+    "square" => expect_count(kind, &numbers, 1).and_then(|_| Shape::square(numbers[0])),
+    "rect" | "rectangle" => expect_count(kind, &numbers, 2).and_then(|_| Shape::rectangle(numbers[0], numbers[1])),
+    "triangle" => expect_count(kind, &numbers, 3).and_then(|_| Shape::triangle(numbers[0], numbers[1], numbers[2])),
+    "ellipse" => expect_count(kind, &numbers, 2).and_then(|_| Shape::ellipse(numbers[0], numbers[1])),
+    "regular" => {
+      expect_count(kind, &numbers, 2)?;
+      let sides = numbers[0];
+      if sides.fract() != 0.0 || !(0.0..=u32::MAX as f64).contains(&sides) {
+        return Err(format!("number of sides must be a whole number, got {sides}"));
+      }
+      Shape::regular_polygon(sides as u32, numbers[1])
+    },
+    "polygon" => {
+      if numbers.len() % 2 != 0 {
+        return Err(String::from("`polygon` takes pairs of x y coordinates"));
+      }
+      Shape::polygon(numbers.chunks(2).map(|pair| (pair[0], pair[1])).collect())
+    },
+    _ => Err(format!("unknown shape `{kind}`")),
+  }
+}
+
+// Parses a whole scene. Every bad line is reported, not just the first one, each message starting with its line number.
+pub fn parse_scene(text: &str) -> Result<Vec<Shape>, Vec<String>> {
+  let mut shapes = Vec::new();
+  let mut errors = Vec::new();
+  for (idx, line) in text.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    match parse_shape(line) {
+      Ok(shape) => shapes.push(shape),
+      Err(e) => errors.push(format!("line {}: {e}", idx + 1)),
+    }
+  }
+  if errors.is_empty() {
+    Ok(shapes)
+  } else {
+    Err(errors)
+  }
+}
+
+// A table with the area and perimeter of every shape and the totals underneath
+pub fn report(shapes: &[Shape]) -> String {
+  let names: Vec<String> = shapes.iter().map(|shape| shape.to_string()).collect();
+  let width = names.iter().map(|name| name.len()).chain([5]).max().unwrap_or(5);
+
+  let mut out = format!("{:>3}  {:<width$}  {:>12}  {:>12}\n", "#", "shape", "area", "perimeter");
+  out.push_str(&format!("{}\n", "-".repeat(width + 33)));
+  let (mut total_area, mut total_perimeter) = (0.0, 0.0);
+  for (idx, (shape, name)) in shapes.iter().zip(&names).enumerate() {
+    let (area, perimeter) = (shape.area(), shape.perimeter());
+    total_area += area;
+    total_perimeter += perimeter;
+    out.push_str(&format!("{:>3}  {:<width$}  {:>12.3}  {:>12.3}\n", idx + 1, name, area, perimeter));
+  }
+  out.push_str(&format!("{}\n", "-".repeat(width + 33)));
+  out.push_str(&format!("{:>3}  {:<width$}  {:>12.3}  {:>12.3}\n", "", "total", total_area, total_perimeter));
+  out
+}
@@ -0,0 +1,451 @@
+// Enum with values: each variant of Shape carries the dimensions of that kind of shape.
+// A `match` over a Shape has to handle every variant, so adding a variant makes the compiler point out
+// every function that still needs to learn about it.
+
+use std::{f64::consts::PI, fmt};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+  Circle(f64), // f64 radius of circle
+  Square(f64), // f64 side of square
+  Rectangle(f64, f64), // f64 length and width of the rectangle
+  Triangle(f64, f64, f64), // f64 lengths of the three sides
+  Ellipse(f64, f64), // f64 semi-major and semi-minor axis (half the width and half the height)
+  RegularPolygon(u32, f64), // number of sides and f64 length of each side
+  Polygon(Vec<(f64, f64)>), // (x, y) of each vertex, the last one joins back to the first. The edges must not cross.
+}
+
+// Width and height of the smallest axis aligned box around the shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+  pub width: f64,
+  pub height: f64,
+}
+
+// Lengths must be real numbers bigger than 0, which also rules out NaN and infinity
+fn check_length(name: &str, value: f64) -> Result<(), String> {
+  if value.is_finite() && value > 0.0 {
+    Ok(())
+  } else {
+    Err(format!("{name} must be a positive number, got {value}"))
+  }
+}
+
+// The box around a regular polygon with its bottom edge lying flat, worked out from the circumradius
+// r = s / (2 sin(pi / n)) instead of from the n corners, so a polygon with billions of sides costs nothing.
+// The corners sit at the angles -pi/2 + (2k - 1) pi/n. The bottom edge is the apothem r cos(pi/n) below the
+// centre. The top is a corner (r) for odd n and an edge (r cos(pi/n)) for even n. Sideways the corner closest
+// to the horizontal is 0, pi/(2n) or pi/n away from it depending on n mod 4.
+fn regular_polygon_bounding_box(sides: u32, side: f64) -> BoundingBox {
+  let n = sides as f64;
+  let radius = side / (2.0 * (PI / n).sin());
+  let apothem = radius * (PI / n).cos();
+  let height = apothem + if sides % 2 == 1 { radius } else { apothem };
+  let widest = match sides % 4 {
+    0 => PI / n,
+    2 => 0.0,
+    _ => PI / (2.0 * n),
+  };
+  BoundingBox { width: 2.0 * radius * widest.cos(), height }
+}
+
+fn polygon_edges(vertices: &[(f64, f64)]) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+  vertices.iter().zip(vertices.iter().cycle().skip(1)).map(|(a, b)| (*a, *b))
+}
+
+// Shoelace formula
+fn polygon_area(vertices: &[(f64, f64)]) -> f64 {
+  polygon_edges(vertices).map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1).sum::<f64>().abs() / 2.0
+}
+
+fn polygon_perimeter(vertices: &[(f64, f64)]) -> f64 {
+  polygon_edges(vertices).map(|((x1, y1), (x2, y2))| (x2 - x1).hypot(y2 - y1)).sum()
+}
+
+// Which side of the line through a and b the point c is on: positive to the left, 0 on the line
+fn orientation(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+  (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+// Whether c, known to be on the line through a and b, lies between them
+fn on_segment(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> bool {
+  a.0.min(b.0) <= c.0 && c.0 <= a.0.max(b.0) && a.1.min(b.1) <= c.1 && c.1 <= a.1.max(b.1)
+}
+
+// Whether the segments share a point, touching counts
+fn segments_touch((a, b): ((f64, f64), (f64, f64)), (c, d): ((f64, f64), (f64, f64))) -> bool {
+  let (o1, o2, o3, o4) = (orientation(a, b, c), orientation(a, b, d), orientation(c, d, a), orientation(c, d, b));
+  if o1 * o2 < 0.0 && o3 * o4 < 0.0 {
+    return true;
+  }
+  (o1 == 0.0 && on_segment(a, b, c))
+    || (o2 == 0.0 && on_segment(a, b, d))
+    || (o3 == 0.0 && on_segment(c, d, a))
+    || (o4 == 0.0 && on_segment(c, d, b))
+}
+
+// A simple polygon's edges only meet their two neighbours, and those only at the shared vertex. Every pair
+// of edges is compared, which is fine for the handful of vertices a scene file gives.
+fn polygon_self_intersects(vertices: &[(f64, f64)]) -> bool {
+  let edges: Vec<_> = polygon_edges(vertices).collect();
+  let n = edges.len();
+  for i in 0..n {
+    // the next edge turning straight back over this one
+    let ((a, b), (_, c)) = (edges[i], edges[(i + 1) % n]);
+    if orientation(a, b, c) == 0.0 && (b.0 - a.0) * (c.0 - b.0) + (b.1 - a.1) * (c.1 - b.1) < 0.0 {
+      return true;
+    }
+    // every edge that is not a neighbour, the last edge is a neighbour of the first
+    for j in i + 2..n {
+      if (i, j) != (0, n - 1) && segments_touch(edges[i], edges[j]) {
+        return true;
+      }
+    }
+  }
+  false
+}
+
+fn vertices_bounding_box(vertices: &[(f64, f64)]) -> BoundingBox {
+  let xs = vertices.iter().map(|(x, _)| *x);
+  let ys = vertices.iter().map(|(_, y)| *y);
+  let width = xs.clone().fold(f64::NEG_INFINITY, f64::max) - xs.fold(f64::INFINITY, f64::min);
+  let height = ys.clone().fold(f64::NEG_INFINITY, f64::max) - ys.fold(f64::INFINITY, f64::min);
+  BoundingBox { width, height }
+}
+
+impl Shape {
+  // The constructors check the dimensions before handing out a Shape. The variants can still be built
+  // directly, `validate` tells whether such a value makes sense.
+  pub fn circle(radius: f64) -> Result<Shape, String> {
+    Shape::Circle(radius).validated()
+  }
+
+  pub fn square(side: f64) -> Result<Shape, String> {
+    Shape::Square(side).validated()
+  }
+
+  pub fn rectangle(length: f64, width: f64) -> Result<Shape, String> {
+    Shape::Rectangle(length, width).validated()
+  }
+
+  pub fn triangle(a: f64, b: f64, c: f64) -> Result<Shape, String> {
+    Shape::Triangle(a, b, c).validated()
+  }
+
+  pub fn ellipse(semi_major: f64, semi_minor: f64) -> Result<Shape, String> {
+    Shape::Ellipse(semi_major, semi_minor).validated()
+  }
+
+  pub fn regular_polygon(sides: u32, side: f64) -> Result<Shape, String> {
+    Shape::RegularPolygon(sides, side).validated()
+  }
+
+  pub fn polygon(vertices: Vec<(f64, f64)>) -> Result<Shape, String> {
+    Shape::Polygon(vertices).validated()
+  }
+
+  fn validated(self) -> Result<Shape, String> {
+    self.validate()?;
+    Ok(self)
+  }
+
+  pub fn validate(&self) -> Result<(), String> {
+    match self {
+      Shape::Circle(r) => check_length("radius", *r),
+      Shape::Square(s) => check_length("side", *s),
+      Shape::Rectangle(l, w) => {
+        check_length("length", *l)?;
+        check_length("width", *w)
+      },
+      Shape::Triangle(a, b, c) => {
+        for side in [a, b, c] {
+          check_length("side", *side)?;
+        }
+        // each side has to be shorter than the other two together, otherwise the sides don't meet
+        if a + b <= *c || a + c <= *b || b + c <= *a {
+          return Err(format!("sides {a}, {b} and {c} do not form a triangle"));
+        }
+        Ok(())
+      },
+      Shape::Ellipse(a, b) => {
+        check_length("semi-major axis", *a)?;
+        check_length("semi-minor axis", *b)?;
+        if b > a {
+          return Err(format!("semi-minor axis {b} is longer than the semi-major axis {a}"));
+        }
+        Ok(())
+      },
+      Shape::RegularPolygon(sides, side) => {
+        if *sides < 3 {
+          return Err(format!("a regular polygon needs at least 3 sides, got {sides}"));
+        }
+        check_length("side", *side)
+      },
+      Shape::Polygon(vertices) => {
+        if vertices.len() < 3 {
+          return Err(format!("a polygon needs at least 3 vertices, got {}", vertices.len()));
+        }
+        if let Some((x, y)) = vertices.iter().find(|(x, y)| !x.is_finite() || !y.is_finite()) {
+          return Err(format!("vertex ({x}, {y}) is not a finite point"));
+        }
+        if polygon_area(vertices) == 0.0 {
+          return Err(String::from("polygon vertices all lie on one line"));
+        }
+        // the shoelace area of a crossing outline counts the parts winding the other way negatively
+        if polygon_self_intersects(vertices) {
+          return Err(String::from("polygon edges cross or touch each other"));
+        }
+        Ok(())
+      },
+    }
+  }
+
+  pub fn area(&self) -> f64 {
+    match self {
+      Shape::Circle(r) => PI * r * r,
+      Shape::Square(s) => s * s,
+      Shape::Rectangle(l, w) => l * w,
+      // Heron's formula
+      Shape::Triangle(a, b, c) => {
+        let s = (a + b + c) / 2.0;
+        (s * (s - a) * (s - b) * (s - c)).max(0.0).sqrt()
+      },
+      Shape::Ellipse(a, b) => PI * a * b,
+      Shape::RegularPolygon(n, s) => *n as f64 * s * s / (4.0 * (PI / *n as f64).tan()),
+      Shape::Polygon(vertices) => polygon_area(vertices),
+    }
+  }
+
+  pub fn perimeter(&self) -> f64 {
+    match self {
+      Shape::Circle(r) => 2.0 * PI * r,
+      Shape::Square(s) => 4.0 * s,
+      Shape::Rectangle(l, w) => 2.0 * (l + w),
+      Shape::Triangle(a, b, c) => a + b + c,
+      // An ellipse has no exact closed formula for its perimeter, this is Ramanujan's approximation
+      Shape::Ellipse(a, b) => {
+        let h = ((a - b) / (a + b)).powi(2);
+        PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+      },
+      Shape::RegularPolygon(n, s) => *n as f64 * s,
+      Shape::Polygon(vertices) => polygon_perimeter(vertices),
+    }
+  }
+
+  // Shapes given only by their dimensions are measured lying in their usual position: rectangles and
+  // ellipses along the axes, triangles on their longest side and regular polygons on one of their edges.
+  pub fn bounding_box(&self) -> BoundingBox {
+    match self {
+      Shape::Circle(r) => BoundingBox { width: 2.0 * r, height: 2.0 * r },
+      Shape::Square(s) => BoundingBox { width: *s, height: *s },
+      Shape::Rectangle(l, w) => BoundingBox { width: *l, height: *w },
+      Shape::Triangle(a, b, c) => {
+        let base = a.max(*b).max(*c);
+        BoundingBox { width: base, height: 2.0 * self.area() / base }
+      },
+      Shape::Ellipse(a, b) => BoundingBox { width: 2.0 * a, height: 2.0 * b },
+      Shape::RegularPolygon(n, s) => regular_polygon_bounding_box(*n, *s),
+      Shape::Polygon(vertices) => vertices_bounding_box(vertices),
+    }
+  }
+
+  // The same shape with every length multiplied by `factor`
+  pub fn scaled(&self, factor: f64) -> Result<Shape, String> {
+    check_length("scale factor", factor)?;
+    let shape = match self {
+      Shape::Circle(r) => Shape::Circle(r * factor),
+      Shape::Square(s) => Shape::Square(s * factor),
+      Shape::Rectangle(l, w) => Shape::Rectangle(l * factor, w * factor),
+      Shape::Triangle(a, b, c) => Shape::Triangle(a * factor, b * factor, c * factor),
+      Shape::Ellipse(a, b) => Shape::Ellipse(a * factor, b * factor),
+      Shape::RegularPolygon(n, s) => Shape::RegularPolygon(*n, s * factor),
+      Shape::Polygon(vertices) => Shape::Polygon(vertices.iter().map(|(x, y)| (x * factor, y * factor)).collect()),
+    };
+    // a huge factor can push a dimension to infinity
+    shape.validated()
+  }
+}
+
+// Writes the shape in the line format read by scene.rs, e.g. "rect 3 4"
+impl fmt::Display for Shape {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Shape::Circle(r) => write!(f, "circle {r}"),
+      Shape::Square(s) => write!(f, "square {s}"),
+      Shape::Rectangle(l, w) => write!(f, "rect {l} {w}"),
+      Shape::Triangle(a, b, c) => write!(f, "triangle {a} {b} {c}"),
+      Shape::Ellipse(a, b) => write!(f, "ellipse {a} {b}"),
+      Shape::RegularPolygon(n, s) => write!(f, "regular {n} {s}"),
+      Shape::Polygon(vertices) => {
+        write!(f, "polygon")?;
+        for (x, y) in vertices {
+          write!(f, " {x} {y}")?;
+        }
+        Ok(())
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // The corners of a regular polygon with its bottom edge lying flat, centred on the origin
+  fn regular_polygon_vertices(sides: u32, side: f64) -> Vec<(f64, f64)> {
+    let n = sides as f64;
+    let radius = side / (2.0 * (PI / n).sin());
+    let start = -PI / 2.0 - PI / n;
+    (0..sides)
+      .map(|k| {
+        let angle = start + 2.0 * PI * k as f64 / n;
+        (radius * angle.cos(), radius * angle.sin())
+      })
+      .collect()
+  }
+
+  fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9 * a.abs().max(1.0)
+  }
+
+  #[test]
+  fn every_constructor_rejects_bad_sizes() {
+    for bad in [0.0, -1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+      assert!(Shape::circle(bad).is_err(), "{bad}");
+      assert!(Shape::square(bad).is_err(), "{bad}");
+      assert!(Shape::rectangle(bad, 1.0).is_err(), "{bad}");
+      assert!(Shape::rectangle(1.0, bad).is_err(), "{bad}");
+      assert!(Shape::triangle(bad, 1.0, 1.0).is_err(), "{bad}");
+      assert!(Shape::triangle(1.0, 1.0, bad).is_err(), "{bad}");
+      assert!(Shape::ellipse(bad, 1.0).is_err(), "{bad}");
+      assert!(Shape::ellipse(1.0, bad).is_err(), "{bad}");
+      assert!(Shape::regular_polygon(5, bad).is_err(), "{bad}");
+    }
+    for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+      assert!(Shape::polygon(vec![(0.0, 0.0), (bad, 0.0), (1.0, 1.0)]).is_err(), "{bad}");
+      assert!(Shape::polygon(vec![(0.0, 0.0), (1.0, 0.0), (1.0, bad)]).is_err(), "{bad}");
+    }
+    assert_eq!(Shape::circle(-2.0), Err(String::from("radius must be a positive number, got -2")));
+    assert_eq!(Shape::rectangle(3.0, 0.0), Err(String::from("width must be a positive number, got 0")));
+  }
+
+  #[test]
+  fn constructors_check_how_the_sizes_fit_together() {
+    assert_eq!(Shape::triangle(1.0, 2.0, 3.0), Err(String::from("sides 1, 2 and 3 do not form a triangle")));
+    assert!(Shape::triangle(1.0, 10.0, 2.0).is_err());
+    assert!(Shape::triangle(3.0, 4.0, 5.0).is_ok());
+    assert_eq!(Shape::ellipse(1.0, 2.0), Err(String::from("semi-minor axis 2 is longer than the semi-major axis 1")));
+    assert!(Shape::ellipse(2.0, 2.0).is_ok());
+    assert_eq!(Shape::regular_polygon(2, 1.0), Err(String::from("a regular polygon needs at least 3 sides, got 2")));
+    assert!(Shape::regular_polygon(0, 1.0).is_err());
+    assert_eq!(Shape::polygon(vec![(0.0, 0.0), (1.0, 1.0)]), Err(String::from("a polygon needs at least 3 vertices, got 2")));
+    assert_eq!(
+      Shape::polygon(vec![(0.0, 0.0), (1.0, 1.0), (3.0, 3.0)]),
+      Err(String::from("polygon vertices all lie on one line"))
+    );
+    // a variant built directly skips the constructor, validate still catches it
+    assert!(Shape::Square(-1.0).validate().is_err());
+    assert!(Shape::Square(1.0).validate().is_ok());
+  }
+
+  #[test]
+  fn self_intersecting_polygons_are_rejected() {
+    let crossing = Err(String::from("polygon edges cross or touch each other"));
+    // a bow tie, its two halves cancel out in the shoelace sum so it already fails the area check
+    assert!(Shape::polygon(vec![(0.0, 0.0), (4.0, 4.0), (4.0, 0.0), (0.0, 4.0)]).is_err());
+    // a lopsided bow tie still has an area, only the crossing check finds it
+    assert_eq!(Shape::polygon(vec![(0.0, 0.0), (4.0, 4.0), (4.0, 0.0), (0.0, 1.0)]), crossing);
+    // an edge running back over the one before it
+    assert_eq!(Shape::polygon(vec![(0.0, 0.0), (4.0, 0.0), (2.0, 0.0), (2.0, 3.0)]), crossing);
+    // two corners touching
+    assert_eq!(Shape::polygon(vec![(0.0, 0.0), (4.0, 0.0), (2.0, 2.0), (4.0, 4.0), (0.0, 4.0), (2.0, 2.0)]), crossing);
+    // a pentagram drawn in one stroke
+    let star: Vec<(f64, f64)> = (0..5).map(|k| ((k as f64 * 4.0 * PI / 5.0).cos(), (k as f64 * 4.0 * PI / 5.0).sin())).collect();
+    assert_eq!(Shape::polygon(star), crossing);
+
+    // concave and collinear vertices are fine
+    assert!(Shape::polygon(vec![(0.0, 0.0), (4.0, 2.0), (0.0, 4.0), (2.0, 2.0)]).is_ok());
+    assert!(Shape::polygon(vec![(0.0, 0.0), (2.0, 0.0), (4.0, 0.0), (4.0, 3.0)]).is_ok());
+  }
+
+  #[test]
+  fn area_and_perimeter() {
+    let cases = [
+      (Shape::circle(1.0).unwrap(), PI, 2.0 * PI),
+      (Shape::square(3.0).unwrap(), 9.0, 12.0),
+      (Shape::rectangle(3.0, 4.0).unwrap(), 12.0, 14.0),
+      (Shape::triangle(3.0, 4.0, 5.0).unwrap(), 6.0, 12.0),
+      // a circle is an ellipse with equal axes, where Ramanujan's approximation is exact
+      (Shape::ellipse(2.0, 2.0).unwrap(), 4.0 * PI, 4.0 * PI),
+      (Shape::regular_polygon(4, 2.0).unwrap(), 4.0, 8.0),
+      (Shape::regular_polygon(6, 1.0).unwrap(), 3.0 * 3f64.sqrt() / 2.0, 6.0),
+      (Shape::polygon(vec![(0.0, 0.0), (4.0, 0.0), (4.0, 3.0)]).unwrap(), 6.0, 12.0),
+      // the winding doesn't change the area
+      (Shape::polygon(vec![(0.0, 0.0), (4.0, 3.0), (4.0, 0.0)]).unwrap(), 6.0, 12.0),
+    ];
+    for (shape, area, perimeter) in cases {
+      assert!(close(shape.area(), area), "{shape:?}: area {}", shape.area());
+      assert!(close(shape.perimeter(), perimeter), "{shape:?}: perimeter {}", shape.perimeter());
+    }
+    // a flat ellipse is about 4 times its semi-major axis around
+    let flat = Shape::ellipse(10.0, 1e-9).unwrap();
+    assert!((flat.perimeter() - 40.0).abs() < 0.1);
+  }
+
+  #[test]
+  fn scaling_multiplies_lengths_and_squares_areas() {
+    let shapes = [
+      Shape::circle(1.5).unwrap(),
+      Shape::square(2.0).unwrap(),
+      Shape::rectangle(1.0, 3.0).unwrap(),
+      Shape::triangle(3.0, 4.0, 5.0).unwrap(),
+      Shape::ellipse(3.0, 1.0).unwrap(),
+      Shape::regular_polygon(7, 1.0).unwrap(),
+      Shape::polygon(vec![(0.0, 0.0), (4.0, 2.0), (0.0, 4.0), (2.0, 2.0)]).unwrap(),
+    ];
+    for shape in shapes {
+      let scaled = shape.scaled(2.5).unwrap();
+      assert!(close(scaled.area(), shape.area() * 6.25), "{shape:?}");
+      assert!(close(scaled.perimeter(), shape.perimeter() * 2.5), "{shape:?}");
+      assert!(close(scaled.bounding_box().width, shape.bounding_box().width * 2.5), "{shape:?}");
+      assert_eq!(shape.scaled(1.0).unwrap(), shape);
+      assert!(shape.scaled(0.0).is_err());
+      assert!(shape.scaled(-2.0).is_err());
+      assert!(shape.scaled(f64::NAN).is_err());
+    }
+    // the dimensions overflow to infinity
+    assert!(Shape::circle(1e300).unwrap().scaled(1e300).is_err());
+  }
+
+  #[test]
+  fn regular_polygon_box_matches_its_corners() {
+    for sides in 3..200 {
+      for side in [0.5, 1.0, 7.25] {
+        let expected = vertices_bounding_box(&regular_polygon_vertices(sides, side));
+        let actual = Shape::RegularPolygon(sides, side).bounding_box();
+        assert!((actual.width - expected.width).abs() < 1e-9 * side * sides as f64, "{sides} sides: {actual:?} vs {expected:?}");
+        assert!((actual.height - expected.height).abs() < 1e-9 * side * sides as f64, "{sides} sides: {actual:?} vs {expected:?}");
+      }
+    }
+  }
+
+  #[test]
+  fn regular_polygon_box_of_familiar_shapes() {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+    let square = Shape::RegularPolygon(4, 2.0).bounding_box();
+    assert!(close(square.width, 2.0) && close(square.height, 2.0));
+    let triangle = Shape::RegularPolygon(3, 2.0).bounding_box();
+    assert!(close(triangle.width, 2.0) && close(triangle.height, 3f64.sqrt()));
+    let hexagon = Shape::RegularPolygon(6, 1.0).bounding_box();
+    assert!(close(hexagon.width, 2.0) && close(hexagon.height, 3f64.sqrt()));
+  }
+
+  #[test]
+  fn huge_regular_polygon_is_measured_without_its_corners() {
+    // the circle the corners lie on: circumference n * s = 2 pi r
+    let bounds = Shape::RegularPolygon(u32::MAX, 1.0).bounding_box();
+    let diameter = u32::MAX as f64 / PI;
+    assert!((bounds.width / diameter - 1.0).abs() < 1e-9);
+    assert!((bounds.height / diameter - 1.0).abs() < 1e-9);
+  }
+}
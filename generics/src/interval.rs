@@ -0,0 +1,496 @@
+// Ranges of any ordered type and sets made of them.
+// An Interval has a start and an end bound, each of which includes its value, excludes it, or is missing
+// (std's Bound::Included / Excluded / Unbounded), so [1, 5), (a, z] and [noon, forever) are all intervals.
+// Only Ord is needed from the values, which covers integers, chars, strings, Durations and SystemTimes.
+//
+// An IntervalSet keeps its intervals sorted, non-empty and apart: intervals that overlap or touch, like [1, 3)
+// and [3, 5], are merged into one. Nothing is known about what lies between two values, so [1, 2] and [3, 4]
+// stay two intervals even for integers, where they cover the same numbers as [1, 4].
+
+use std::{
+  cmp::Ordering,
+  fmt,
+  ops::Bound::{self, Excluded, Included, Unbounded},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval<T> {
+  start: Bound<T>,
+  end: Bound<T>,
+}
+
+// Which of two start bounds starts first. A missing start is the earliest, and at the same value an included
+// start comes before an excluded one.
+fn cmp_start<T: Ord>(a: &Bound<T>, b: &Bound<T>) -> Ordering {
+  match (a, b) {
+    (Unbounded, Unbounded) => Ordering::Equal,
+    (Unbounded, _) => Ordering::Less,
+    (_, Unbounded) => Ordering::Greater,
+    (Included(x), Included(y)) | (Excluded(x), Excluded(y)) => x.cmp(y),
+    (Included(x), Excluded(y)) => x.cmp(y).then(Ordering::Less),
+    (Excluded(x), Included(y)) => x.cmp(y).then(Ordering::Greater),
+  }
+}
+
+// The same for end bounds: a missing end is the latest, and an included end reaches further than an excluded one
+fn cmp_end<T: Ord>(a: &Bound<T>, b: &Bound<T>) -> Ordering {
+  match (a, b) {
+    (Unbounded, Unbounded) => Ordering::Equal,
+    (Unbounded, _) => Ordering::Greater,
+    (_, Unbounded) => Ordering::Less,
+    (Included(x), Included(y)) | (Excluded(x), Excluded(y)) => x.cmp(y),
+    (Included(x), Excluded(y)) => x.cmp(y).then(Ordering::Greater),
+    (Excluded(x), Included(y)) => x.cmp(y).then(Ordering::Less),
+  }
+}
+
+// True when nothing lies between an interval ending at `end` and one starting at `start`, so the two can be
+// merged: they overlap, or meet at a value one of them includes
+fn reaches<T: Ord>(end: &Bound<T>, start: &Bound<T>) -> bool {
+  match (end, start) {
+    (Unbounded, _) | (_, Unbounded) => true,
+    (Excluded(e), Excluded(s)) => s < e,
+    (Included(e), Included(s)) | (Included(e), Excluded(s)) | (Excluded(e), Included(s)) => s <= e,
+  }
+}
+
+// The bound on the other side of the same value, used for the gaps between intervals: the gap after an
+// interval ending at 5] starts at (5
+fn flip<T: Clone>(bound: &Bound<T>) -> Bound<T> {
+  match bound {
+    Included(value) => Excluded(value.clone()),
+    Excluded(value) => Included(value.clone()),
+    Unbounded => Unbounded,
+  }
+}
+
+impl<T: Ord + Clone> Interval<T> {
+  pub fn new(start: Bound<T>, end: Bound<T>) -> Interval<T> {
+    Interval { start, end }
+  }
+
+  // [start, end]
+  pub fn closed(start: T, end: T) -> Interval<T> {
+    Interval::new(Included(start), Included(end))
+  }
+
+  // [start, end), like `start..end`
+  pub fn closed_open(start: T, end: T) -> Interval<T> {
+    Interval::new(Included(start), Excluded(end))
+  }
+
+  // (start, end)
+  pub fn open(start: T, end: T) -> Interval<T> {
+    Interval::new(Excluded(start), Excluded(end))
+  }
+
+  // [start, forever)
+  pub fn at_least(start: T) -> Interval<T> {
+    Interval::new(Included(start), Unbounded)
+  }
+
+  // (-forever, end)
+  pub fn less_than(end: T) -> Interval<T> {
+    Interval::new(Unbounded, Excluded(end))
+  }
+
+  pub fn start(&self) -> &Bound<T> {
+    &self.start
+  }
+
+  pub fn end(&self) -> &Bound<T> {
+    &self.end
+  }
+
+  // [3, 2], [3, 3) and (3, 3) contain nothing
+  pub fn is_empty(&self) -> bool {
+    match (&self.start, &self.end) {
+      (Unbounded, _) | (_, Unbounded) => false,
+      (Included(s), Included(e)) => s > e,
+      (Included(s), Excluded(e)) | (Excluded(s), Included(e)) | (Excluded(s), Excluded(e)) => s >= e,
+    }
+  }
+
+  pub fn contains(&self, value: &T) -> bool {
+    let after_start = match &self.start {
+      Included(s) => value >= s,
+      Excluded(s) => value > s,
+      Unbounded => true,
+    };
+    let before_end = match &self.end {
+      Included(e) => value <= e,
+      Excluded(e) => value < e,
+      Unbounded => true,
+    };
+    after_start && before_end
+  }
+
+  // Every value of `other` is also in this interval
+  pub fn contains_interval(&self, other: &Interval<T>) -> bool {
+    other.is_empty()
+      || (cmp_start(&self.start, &other.start) != Ordering::Greater && cmp_end(&self.end, &other.end) != Ordering::Less)
+  }
+
+  // The values in both, None when there are none
+  pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+    let start = if cmp_start(&self.start, &other.start) == Ordering::Greater { &self.start } else { &other.start };
+    let end = if cmp_end(&self.end, &other.end) == Ordering::Less { &self.end } else { &other.end };
+    let result = Interval::new(start.clone(), end.clone());
+    (!result.is_empty()).then_some(result)
+  }
+}
+
+// [1, 5), (-inf, 3], (a, +inf)
+impl<T: fmt::Display> fmt::Display for Interval<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match &self.start {
+      Included(value) => write!(f, "[{value}")?,
+      Excluded(value) => write!(f, "({value}")?,
+      Unbounded => write!(f, "(-inf")?,
+    }
+    match &self.end {
+      Included(value) => write!(f, ", {value}]"),
+      Excluded(value) => write!(f, ", {value})"),
+      Unbounded => write!(f, ", +inf)"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+  // sorted by start, no two of them overlap or touch, none is empty
+  intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+  fn default() -> IntervalSet<T> {
+    IntervalSet { intervals: Vec::new() }
+  }
+}
+
+impl<T: Ord + Clone> IntervalSet<T> {
+  pub fn new() -> IntervalSet<T> {
+    IntervalSet::default()
+  }
+
+  pub fn intervals(&self) -> &[Interval<T>] {
+    &self.intervals
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.intervals.is_empty()
+  }
+
+  // Adds the interval, merging it with every interval it overlaps or touches
+  pub fn insert(&mut self, interval: Interval<T>) {
+    if interval.is_empty() {
+      return;
+    }
+    let mut merged = interval;
+    let mut kept = Vec::with_capacity(self.intervals.len() + 1);
+    for existing in self.intervals.drain(..) {
+      let (first, second) = if cmp_start(&existing.start, &merged.start) == Ordering::Greater {
+        (&merged, &existing)
+      } else {
+        (&existing, &merged)
+      };
+      if reaches(&first.end, &second.start) {
+        let end = if cmp_end(&first.end, &second.end) == Ordering::Less { &second.end } else { &first.end };
+        merged = Interval::new(first.start.clone(), end.clone());
+      } else {
+        kept.push(existing);
+      }
+    }
+    let position = kept.iter().position(|i| cmp_start(&i.start, &merged.start) == Ordering::Greater).unwrap_or(kept.len());
+    kept.insert(position, merged);
+    self.intervals = kept;
+  }
+
+  pub fn contains(&self, value: &T) -> bool {
+    self.intervals.iter().any(|interval| interval.contains(value))
+  }
+
+  // Every value of `interval` is in the set. Since touching intervals are merged, it has to fit in a single one.
+  pub fn contains_interval(&self, interval: &Interval<T>) -> bool {
+    interval.is_empty() || self.intervals.iter().any(|i| i.contains_interval(interval))
+  }
+
+  pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+    let mut result = self.clone();
+    for interval in &other.intervals {
+      result.insert(interval.clone());
+    }
+    result
+  }
+
+  // Walks both sorted lists at once, always moving on from the interval that ends first
+  pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < self.intervals.len() && j < other.intervals.len() {
+      let (a, b) = (&self.intervals[i], &other.intervals[j]);
+      if let Some(common) = a.intersection(b) {
+        result.push(common);
+      }
+      if cmp_end(&a.end, &b.end) == Ordering::Less {
+        i += 1;
+      } else {
+        j += 1;
+      }
+    }
+    // the pieces are sorted and apart already, apart from two pieces of one interval meeting at a point
+    // neither includes, which insert handles
+    let mut set = IntervalSet::new();
+    for interval in result {
+      set.insert(interval);
+    }
+    set
+  }
+
+  // Everything not in the set: the gaps between the intervals, and before the first and after the last one
+  pub fn complement(&self) -> IntervalSet<T> {
+    let mut gaps = IntervalSet::new();
+    let mut start = Unbounded;
+    for interval in &self.intervals {
+      if interval.start != Unbounded {
+        gaps.insert(Interval::new(start, flip(&interval.start)));
+      }
+      start = flip(&interval.end);
+    }
+    if self.intervals.last().is_none_or(|last| last.end != Unbounded) {
+      gaps.insert(Interval::new(start, Unbounded));
+    }
+    gaps
+  }
+
+  // The values in this set but not in `other`
+  pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+    self.intersection(&other.complement())
+  }
+}
+
+impl<T: Ord + Clone> FromIterator<Interval<T>> for IntervalSet<T> {
+  fn from_iter<I: IntoIterator<Item = Interval<T>>>(intervals: I) -> IntervalSet<T> {
+    let mut set = IntervalSet::new();
+    for interval in intervals {
+      set.insert(interval);
+    }
+    set
+  }
+}
+
+// {[1, 3), (5, 8]}
+impl<T: fmt::Display> fmt::Display for IntervalSet<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let parts: Vec<String> = self.intervals.iter().map(|interval| interval.to_string()).collect();
+    write!(f, "{{{}}}", parts.join(", "))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+  use super::*;
+  use crate::XorShift;
+
+  // Values 0 to LIMIT are checked one by one. Bounds are even values up to LIMIT - 2, so there is always an odd
+  // value strictly between two bounds and values past every bound.
+  const LIMIT: u64 = 24;
+
+  // A random interval with a random kind of bound on each side
+  fn random_interval(rng: &mut XorShift) -> Interval<u64> {
+    let mut bound = || {
+      let value = rng.next_u64() % (LIMIT / 2) * 2;
+      match rng.next_u64() % 5 {
+        0 => Unbounded,
+        1 | 2 => Included(value),
+        _ => Excluded(value),
+      }
+    };
+    let start = bound();
+    Interval::new(start, bound())
+  }
+
+  fn random_intervals(rng: &mut XorShift) -> Vec<Interval<u64>> {
+    let count = rng.next_u64() % 4;
+    (0..count).map(|_| random_interval(rng)).collect()
+  }
+
+  // The sets' invariants: intervals sorted, non-empty and apart (an interval never reaches the next one)
+  fn check_invariants(set: &IntervalSet<u64>) {
+    for interval in set.intervals() {
+      assert!(!interval.is_empty(), "{set:?} holds an empty interval");
+    }
+    for pair in set.intervals().windows(2) {
+      assert_eq!(cmp_start(&pair[0].start, &pair[1].start), Ordering::Less, "{set} is not sorted");
+      assert!(!reaches(&pair[0].end, &pair[1].start), "{set}: {} and {} should have been merged", pair[0], pair[1]);
+    }
+  }
+
+  fn set(intervals: impl IntoIterator<Item = Interval<u64>>) -> IntervalSet<u64> {
+    intervals.into_iter().collect()
+  }
+
+  #[test]
+  fn random_sets_match_their_intervals_value_by_value() {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    for _ in 0..500 {
+      let (a, b) = (random_intervals(&mut rng), random_intervals(&mut rng));
+      let (set_a, set_b) = (set(a.clone()), set(b.clone()));
+      let in_a = |value: &u64| a.iter().any(|interval| interval.contains(value));
+      let in_b = |value: &u64| b.iter().any(|interval| interval.contains(value));
+      let (union, intersection, difference) = (set_a.union(&set_b), set_a.intersection(&set_b), set_a.difference(&set_b));
+      let complement = set_a.complement();
+      for set in [&set_a, &union, &intersection, &difference, &complement] {
+        check_invariants(set);
+      }
+      for value in 0..=LIMIT {
+        assert_eq!(set_a.contains(&value), in_a(&value));
+        assert_eq!(union.contains(&value), in_a(&value) || in_b(&value));
+        assert_eq!(intersection.contains(&value), in_a(&value) && in_b(&value));
+        assert_eq!(difference.contains(&value), in_a(&value) && !in_b(&value));
+        assert_eq!(complement.contains(&value), !in_a(&value));
+      }
+      for interval in b.iter().filter(|i| i.start != Unbounded && i.end != Unbounded) {
+        let covered = (0..=LIMIT).filter(|value| interval.contains(value)).all(|value| in_a(&value));
+        assert_eq!(set_a.contains_interval(interval), covered, "{set_a} contains {interval}");
+      }
+    }
+  }
+
+  #[test]
+  fn set_laws() {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    let everything = set([Interval::new(Unbounded, Unbounded)]);
+    for _ in 0..300 {
+      let a = set(random_intervals(&mut rng));
+      let b = set(random_intervals(&mut rng));
+      let c = set(random_intervals(&mut rng));
+
+      // commutative and associative
+      assert_eq!(a.union(&b), b.union(&a));
+      assert_eq!(a.intersection(&b), b.intersection(&a));
+      assert_eq!(a.union(&b).union(&c), a.union(&b.union(&c)));
+      assert_eq!(a.intersection(&b).intersection(&c), a.intersection(&b.intersection(&c)));
+      // distributive, both ways round
+      assert_eq!(a.intersection(&b.union(&c)), a.intersection(&b).union(&a.intersection(&c)));
+      assert_eq!(a.union(&b.intersection(&c)), a.union(&b).intersection(&a.union(&c)));
+      // idempotent, absorption
+      assert_eq!(a.union(&a), a);
+      assert_eq!(a.intersection(&a), a);
+      assert_eq!(a.union(&a.intersection(&b)), a);
+      assert_eq!(a.intersection(&a.union(&b)), a);
+
+      // complement
+      assert_eq!(a.complement().complement(), a);
+      assert_eq!(a.union(&a.complement()), everything);
+      assert!(a.intersection(&a.complement()).is_empty());
+      // De Morgan
+      assert_eq!(a.union(&b).complement(), a.complement().intersection(&b.complement()));
+      assert_eq!(a.intersection(&b).complement(), a.complement().union(&b.complement()));
+
+      // difference
+      assert_eq!(a.difference(&b), a.intersection(&b.complement()));
+      assert!(a.difference(&a).is_empty());
+      assert_eq!(a.difference(&IntervalSet::new()), a);
+      assert!(a.difference(&b).intersection(&b).is_empty());
+      assert_eq!(a.difference(&b).union(&a.intersection(&b)), a);
+      assert_eq!(a.difference(&b.union(&c)), a.difference(&b).intersection(&a.difference(&c)));
+    }
+  }
+
+  #[test]
+  fn touching_bounds_merge_only_when_the_value_between_is_covered() {
+    // 3 is in the second interval, nothing is left out between them
+    assert_eq!(set([Interval::closed_open(1, 3), Interval::closed(3, 5)]).intervals(), [Interval::closed(1, 5)]);
+    assert_eq!(set([Interval::closed(1, 3), Interval::open(3, 5)]).intervals(), [Interval::closed_open(1, 5)]);
+    // the order they are inserted in doesn't matter
+    assert_eq!(set([Interval::closed(3, 5), Interval::closed_open(1, 3)]).intervals(), [Interval::closed(1, 5)]);
+    // 3 is in neither, so the gap [3, 3] stays
+    let apart = set([Interval::open(1, 3), Interval::open(3, 5)]);
+    assert_eq!(apart.intervals(), [Interval::open(1, 3), Interval::open(3, 5)]);
+    assert!(!apart.contains(&3));
+    assert_eq!(apart.complement().to_string(), "{(-inf, 1], [3, 3], [5, +inf)}");
+    // filling the gap joins all three
+    let mut filled = apart.clone();
+    filled.insert(Interval::closed(3, 3));
+    assert_eq!(filled.intervals(), [Interval::open(1, 5)]);
+    // [1, 2] and [3, 4] leave the values between 2 and 3 out, even though there are no integers there
+    assert_eq!(set([Interval::closed(1, 2), Interval::closed(3, 4)]).intervals().len(), 2);
+    // an interval bridging several others swallows them all
+    let bridged = set([Interval::closed(0, 1), Interval::closed(4, 5), Interval::closed(8, 9), Interval::open(1, 8)]);
+    assert_eq!(bridged.intervals(), [Interval::closed(0, 9)]);
+  }
+
+  #[test]
+  fn empty_intervals_are_ignored() {
+    for empty in [Interval::closed(3, 2), Interval::closed_open(3, 3), Interval::open(3, 3)] {
+      assert!(empty.is_empty(), "{empty}");
+      assert!(set([empty]).is_empty());
+    }
+    // (3, 4) holds the values between 3 and 4, just no integers
+    assert!(!Interval::open(3, 4).is_empty());
+    assert_eq!(Interval::open(3, 4).intersection(&Interval::open(4, 5)), None);
+    assert_eq!(Interval::closed(3, 4).intersection(&Interval::closed(4, 5)), Some(Interval::closed(4, 4)));
+  }
+
+  #[test]
+  fn unbounded_ends() {
+    let below = Interval::less_than(3);
+    let above = Interval::at_least(3);
+    assert!(below.contains(&0) && !below.contains(&3));
+    assert!(above.contains(&3) && above.contains(&u64::MAX));
+    assert_eq!(below.to_string(), "(-inf, 3)");
+    assert_eq!(Interval::new(Excluded(7), Unbounded).to_string(), "(7, +inf)");
+
+    // the two halves make up everything, whose complement is empty
+    let everything = set([below.clone(), above.clone()]);
+    assert_eq!(everything.intervals(), [Interval::new(Unbounded, Unbounded)]);
+    assert!(everything.complement().is_empty());
+    assert_eq!(IntervalSet::<u64>::new().complement(), everything);
+    assert!(everything.contains_interval(&Interval::at_least(0)));
+    assert_eq!(everything.to_string(), "{(-inf, +inf)}");
+
+    // the halves only meet at 3, with a gap when neither includes it
+    assert_eq!(below.intersection(&above), None);
+    let split = set([below.clone(), Interval::new(Excluded(3), Unbounded)]);
+    assert_eq!(split.complement().intervals(), [Interval::closed(3, 3)]);
+    assert_eq!(split.complement().complement(), split);
+
+    // bounded pieces taken out of an unbounded interval
+    let rest = set([Interval::at_least(0)]).difference(&set([Interval::closed_open(2, 4), Interval::at_least(10)]));
+    assert_eq!(rest.intervals(), [Interval::closed_open(0, 2), Interval::closed_open(4, 10)]);
+    assert!(set([Interval::at_least(5)]).contains_interval(&Interval::at_least(6)));
+    assert!(!set([Interval::at_least(5)]).contains_interval(&Interval::less_than(6)));
+    assert_eq!(below.intersection(&Interval::at_least(1)), Some(Interval::closed_open(1, 3)));
+  }
+
+  #[test]
+  fn other_ordered_types() {
+    let working_hours: IntervalSet<u32> = [Interval::closed_open(9, 12), Interval::closed_open(13, 17)].into_iter().collect();
+    let meetings: IntervalSet<u32> = [Interval::closed_open(11, 14), Interval::closed_open(16, 18)].into_iter().collect();
+    let free = working_hours.difference(&meetings);
+    assert_eq!(free.to_string(), "{[9, 11), [14, 16)}");
+    assert_eq!(working_hours.union(&meetings).to_string(), "{[9, 18)}");
+    assert_eq!(working_hours.intersection(&meetings).to_string(), "{[11, 12), [13, 14), [16, 17)}");
+    assert!(free.contains_interval(&Interval::closed(14, 15)) && !free.contains(&16));
+
+    let letters: IntervalSet<char> = [Interval::closed('a', 'z')].into_iter().collect();
+    let vowels: IntervalSet<char> = "aeiou".chars().map(|c| Interval::closed(c, c)).collect();
+    let consonants = letters.difference(&vowels);
+    assert!(consonants.contains(&'b') && !consonants.contains(&'e'));
+    assert_eq!(consonants.complement().intersection(&letters), vowels);
+
+    let words: IntervalSet<&str> = [Interval::closed_open("apple", "banana"), Interval::closed("banana", "cherry")].into_iter().collect();
+    assert_eq!(words.intervals(), [Interval::closed("apple", "cherry")]);
+
+    let noon = UNIX_EPOCH + Duration::from_secs(1_700_049_600);
+    let lunch = Interval::closed_open(noon, noon + Duration::from_secs(3600));
+    let later: IntervalSet<SystemTime> = [Interval::at_least(noon + Duration::from_secs(1800))].into_iter().collect();
+    let lunch_set: IntervalSet<SystemTime> = [lunch.clone()].into_iter().collect();
+    assert!(lunch.contains(&(noon + Duration::from_secs(60))));
+    assert_eq!(lunch_set.difference(&later).intervals(), [Interval::closed_open(noon, noon + Duration::from_secs(1800))]);
+    assert!(lunch_set.intersection(&IntervalSet::from_iter([Interval::less_than(noon)])).is_empty());
+  }
+}
@@ -0,0 +1,225 @@
+// `largest` from main.rs, grown from two values to whole collections.
+// Every function takes anything implementing IntoIterator: a Vec, an array, a range, a slice (which yields
+// references, so nothing has to be Copy or even Clone) or an iterator chain.
+//
+// PartialOrd is not a total order: a float NaN is neither smaller nor larger than anything, not even itself.
+// A plain `a > b` loop then gives a different answer depending on where the NaN sits. The PartialOrd based
+// functions here skip values that don't compare equal to themselves, so NaN never wins and never hides the
+// real maximum. When NaN should take part, wrap the floats in Total, which orders them with `total_cmp`.
+//
+// On ties the first of the equal items is returned.
+
+use std::cmp::Ordering;
+
+// false for NaN, true for every value of a type with a proper order
+pub fn comparable<T: PartialOrd>(value: &T) -> bool {
+  value.partial_cmp(value).is_some()
+}
+
+pub fn largest_in<I>(items: I) -> Option<I::Item>
+where
+  I: IntoIterator,
+  I::Item: PartialOrd,
+{
+  items.into_iter().filter(comparable).fold(None, |best, item| match best {
+    Some(best) if item <= best => Some(best),
+    _ => Some(item),
+  })
+}
+
+// The smallest and the largest item in one pass. Items are cloned only when one item is both, which is
+// free for the references a slice gives out.
+pub fn min_max<I>(items: I) -> Option<(I::Item, I::Item)>
+where
+  I: IntoIterator,
+  I::Item: PartialOrd + Clone,
+{
+  let mut result: Option<(I::Item, I::Item)> = None;
+  for item in items.into_iter().filter(comparable) {
+    result = match result {
+      None => Some((item.clone(), item)),
+      Some((min, max)) if item < min => Some((item, max)),
+      Some((min, max)) if item > max => Some((min, item)),
+      unchanged => unchanged,
+    };
+  }
+  result
+}
+
+// The position of the largest item
+pub fn argmax<I>(items: I) -> Option<usize>
+where
+  I: IntoIterator,
+  I::Item: PartialOrd,
+{
+  largest_by(
+    items.into_iter().enumerate().filter(|(_, item)| comparable(item)),
+    |(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal),
+  )
+  .map(|(idx, _)| idx)
+}
+
+// The largest item according to `compare`, which has to be a total order
+pub fn largest_by<I, F>(items: I, mut compare: F) -> Option<I::Item>
+where
+  I: IntoIterator,
+  F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+  items.into_iter().fold(None, |best, item| match best {
+    Some(best) if compare(&item, &best) != Ordering::Greater => Some(best),
+    _ => Some(item),
+  })
+}
+
+// The item with the largest key, e.g. the longest word with `|word| word.len()`
+pub fn largest_by_key<I, K, F>(items: I, mut key: F) -> Option<I::Item>
+where
+  I: IntoIterator,
+  K: PartialOrd,
+  F: FnMut(&I::Item) -> K,
+{
+  let mut best: Option<(K, I::Item)> = None;
+  for item in items {
+    let item_key = key(&item);
+    if !comparable(&item_key) {
+      continue;
+    }
+    best = match best {
+      Some((best_key, best_item)) if item_key <= best_key => Some((best_key, best_item)),
+      _ => Some((item_key, item)),
+    };
+  }
+  best.map(|(_, item)| item)
+}
+
+// Floats have a total order too, it just isn't the one `<` uses: -NaN < -inf < ... < -0.0 < +0.0 < ... < +inf < NaN
+pub trait TotalOrd {
+  fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl TotalOrd for f32 {
+  fn total_cmp(&self, other: &f32) -> Ordering {
+    f32::total_cmp(self, other)
+  }
+}
+
+impl TotalOrd for f64 {
+  fn total_cmp(&self, other: &f64) -> Ordering {
+    f64::total_cmp(self, other)
+  }
+}
+
+// A float that implements Ord, so it works everywhere Ord is required: `max`, `sort`, BTreeMap keys, ...
+#[derive(Debug, Clone, Copy)]
+pub struct Total<T>(pub T);
+
+impl<T: TotalOrd> PartialEq for Total<T> {
+  fn eq(&self, other: &Total<T>) -> bool {
+    self.0.total_cmp(&other.0) == Ordering::Equal
+  }
+}
+
+impl<T: TotalOrd> Eq for Total<T> {}
+
+impl<T: TotalOrd> PartialOrd for Total<T> {
+  fn partial_cmp(&self, other: &Total<T>) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<T: TotalOrd> Ord for Total<T> {
+  fn cmp(&self, other: &Total<T>) -> Ordering {
+    self.0.total_cmp(&other.0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn words() -> Vec<String> {
+    ["apple", "fig", "banana", "cherry"].map(String::from).to_vec()
+  }
+
+  #[test]
+  fn slices_give_references() {
+    let words = words();
+    assert_eq!(largest_in(&words), Some(&words[1]));
+    assert_eq!(largest_by_key(&words, |word| word.len()), Some(&words[2]));
+    assert_eq!(min_max(&words), Some((&words[0], &words[1])));
+    assert_eq!(argmax(&words), Some(1));
+    assert_eq!(largest_by(&words, |a, b| a.len().cmp(&b.len()).then(b.cmp(a))), Some(&words[2]));
+  }
+
+  #[test]
+  fn owned_items_ranges_and_empty_input() {
+    assert_eq!(largest_in(words().iter().map(|word| word.to_uppercase())), Some(String::from("FIG")));
+    assert_eq!(largest_in(1..=10), Some(10));
+    assert_eq!(min_max(1..=10), Some((1, 10)));
+    assert_eq!(largest_in(Vec::<i32>::new()), None);
+    assert_eq!(min_max(Vec::<i32>::new()), None);
+    assert_eq!(argmax(Vec::<i32>::new()), None);
+    assert_eq!(largest_by(Vec::<i32>::new(), |a, b| a.cmp(b)), None);
+    assert_eq!(largest_by_key(Vec::<i32>::new(), |n| *n), None);
+    // a single item is both the smallest and the largest
+    assert_eq!(min_max([7]), Some((7, 7)));
+  }
+
+  #[test]
+  fn ties_keep_the_first_item() {
+    let pairs = [(1, 'a'), (3, 'b'), (2, 'c'), (3, 'd')];
+    assert_eq!(largest_by_key(pairs, |(n, _)| *n), Some((3, 'b')));
+    assert_eq!(largest_by(pairs, |a, b| a.0.cmp(&b.0)), Some((3, 'b')));
+    assert_eq!(argmax([1, 3, 2, 3]), Some(1));
+    let words = ["bb", "aa", "cc"];
+    assert_eq!(largest_by_key(words, |word| word.len()), Some("bb"));
+    assert_eq!(min_max([2, 1, 1, 2]), Some((1, 2)));
+  }
+
+  #[test]
+  fn nan_is_skipped_wherever_it_is() {
+    for readings in [
+      [f64::NAN, 2.5, 7.25, -1.0],
+      [2.5, f64::NAN, 7.25, -1.0],
+      [2.5, 7.25, -1.0, f64::NAN],
+    ] {
+      assert_eq!(largest_in(readings), Some(7.25), "{readings:?}");
+      assert_eq!(min_max(readings), Some((-1.0, 7.25)), "{readings:?}");
+      assert_eq!(readings[argmax(readings).unwrap()], 7.25, "{readings:?}");
+      assert_eq!(largest_by_key(readings, |r| -r), Some(-1.0), "{readings:?}");
+    }
+    assert_eq!(argmax([f64::NAN, 2.5, 7.25]), Some(2));
+    assert_eq!(argmax([2.5, 7.25, f64::NAN]), Some(1));
+    assert!(!comparable(&f32::NAN));
+    assert!(comparable(&f32::INFINITY));
+  }
+
+  #[test]
+  fn only_nan_means_nothing_to_pick() {
+    let all_nan = [f64::NAN, f64::NAN];
+    assert_eq!(largest_in(all_nan), None);
+    assert_eq!(min_max(all_nan), None);
+    assert_eq!(argmax(all_nan), None);
+    assert_eq!(largest_by_key(all_nan, |r| *r), None);
+    assert_eq!(largest_by_key([1, 2], |_| f64::NAN), None);
+  }
+
+  #[test]
+  fn total_order_puts_nan_above_infinity() {
+    let readings = [2.5, f64::NAN, 7.25, f64::INFINITY];
+    assert!(largest_by(readings, |a, b| a.total_cmp(b)).unwrap().is_nan());
+    assert!(largest_in(readings.map(Total)).unwrap().0.is_nan());
+    assert!(largest_in([1.5f32, f32::NAN, 0.5].map(Total)).unwrap().0.is_nan());
+    assert_eq!(largest_in([f64::NAN, -f64::NAN].map(Total)).map(|Total(x)| x.is_sign_positive()), Some(true));
+
+    let mut values: Vec<Total<f64>> = [0.5, -0.0, f64::INFINITY, 0.0, -3.0, -f64::NAN].into_iter().map(Total).collect();
+    values.sort();
+    let sorted: Vec<f64> = values.into_iter().map(|Total(value)| value).collect();
+    assert!(sorted[0].is_nan());
+    assert_eq!(sorted[1..], [-3.0, -0.0, 0.0, 0.5, f64::INFINITY]);
+    // -0.0 and 0.0 are different in the total order
+    assert!(sorted[2].is_sign_negative() && sorted[3].is_sign_positive());
+    assert_ne!(Total(-0.0), Total(0.0));
+    assert_eq!(Total(f64::NAN), Total(f64::NAN));
+  }
+}
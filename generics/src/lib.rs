@@ -0,0 +1,20 @@
+// Everything except the demo lives in the library, so main.rs only shows it off. Matrix has examples that
+// are checked as doctests by `cargo test`, including the ones that must not compile.
+
+pub mod interval;
+pub mod largest;
+pub mod matrix;
+pub mod stats;
+pub mod topk;
+
+// Small xorshift pseudo random generator, good enough for demo and test data and with no crate needed
+pub struct XorShift(pub u64);
+
+impl XorShift {
+  pub fn next_u64(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    self.0
+  }
+}
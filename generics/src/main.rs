@@ -1,3 +1,15 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use generics::{
+  interval::{Interval, IntervalSet},
+  largest::{argmax, largest_by, largest_by_key, largest_in, min_max, Total},
+  matrix::Matrix,
+  stats::{self, RunningStats},
+  topk::{top_k_by_key, PriorityQueue, TopK},
+  XorShift,
+};
+
+// Only compares two values, the largest module does the same for whole collections
 fn largest<T: std::cmp::PartialOrd>(a: T, b: T) -> T {
   if a > b {
     a
@@ -11,4 +23,149 @@ fn main() {
   let big_char = largest('a', 'c');
   println!("{big_i32}");
   println!("{big_char}");
-}
\ No newline at end of file
+  // NaN > 1.0 and 1.0 > NaN are both false, so the answer depends on the order of the arguments
+  println!("largest(NaN, 1.0) = {}, largest(1.0, NaN) = {}", largest(f64::NAN, 1.0), largest(1.0, f64::NAN));
+
+  // Slices give references, so String doesn't have to be copied or cloned
+  let words = vec![String::from("apple"), String::from("fig"), String::from("banana"), String::from("cherry")];
+  println!(
+    "largest word {:?}, longest {:?}, first and last {:?}, largest at {:?}",
+    largest_in(&words),
+    largest_by_key(&words, |word| word.len()),
+    min_max(&words),
+    argmax(&words)
+  );
+
+  // NaN is skipped wherever it is
+  let readings = [2.5, f64::NAN, 7.25, -1.0, f64::NAN];
+  println!("largest reading {:?}, smallest and largest {:?}", largest_in(readings), min_max(readings));
+
+  // ... unless the floats use their total order, where NaN is above infinity
+  println!("largest with the total order: {:?}", largest_by(readings, |a, b| a.total_cmp(b)));
+  let sorted = {
+    let mut values: Vec<Total<f64>> = [0.5, -0.0, f64::INFINITY, 0.0, -3.0].into_iter().map(Total).collect();
+    values.sort();
+    values.into_iter().map(|Total(value)| value).collect::<Vec<f64>>()
+  };
+  println!("sorted with the total order: {sorted:?}");
+
+  // Statistics over any Numeric type
+  let scores: Vec<u8> = vec![200, 180, 250, 180, 90, 255, 180, 120];
+  // the sum is 1455, far more than fits in a u8, but it is added up as i128
+  println!("scores add up to {}, the most common is {:?}", stats::sum(&scores), stats::mode(&scores));
+  println!(
+    "scores: mean {:?}, median {:?}, std dev {:.3}, sample std dev {:.3}, 90th percentile {:?}",
+    stats::mean(&scores).unwrap(),
+    stats::median(&scores).unwrap(),
+    stats::std_dev(&scores).unwrap(),
+    stats::sample_std_dev(&scores).unwrap(),
+    stats::percentile(&scores, 90.0).unwrap()
+  );
+  for bin in stats::histogram(&scores, 4) {
+    println!("  {:>6.1} .. {:>6.1} {}", bin.low, bin.high, "#".repeat(bin.count));
+  }
+
+  // The streaming version sees each value once and agrees with the two pass one
+  let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+  let samples: Vec<i32> = (0..10_000).map(|_| 1_000_000 + (rng.next_u64() % 1000) as i32).collect();
+  let running: RunningStats<i32> = samples.iter().copied().collect();
+  println!(
+    "{} samples between {:?} and {:?}: mean {:.3}, std dev {:.3}, variance {:.3} (two pass {:.3}), sample variance {:.3} (two pass {:.3})",
+    running.count(),
+    running.min().unwrap(),
+    running.max().unwrap(),
+    running.mean().unwrap(),
+    running.std_dev().unwrap(),
+    running.variance().unwrap(),
+    stats::variance(&samples).unwrap(),
+    running.sample_variance().unwrap(),
+    stats::sample_variance(&samples).unwrap()
+  );
+  let mut readings = RunningStats::new();
+  readings.extend([21.5f32, f32::NAN, 19.0, 23.25]);
+  readings.push(f32::NAN);
+  println!("{} temperatures without the NaNs", readings.count());
+
+  // Matrices with their size in the type
+  let a = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+  let b = Matrix::new([[7, 8], [9, 10], [11, 12]]);
+  let product: Matrix<i32, 2, 2> = a * b;
+  assert_eq!(product, Matrix::new([[58, 64], [139, 154]]));
+  assert_eq!(a.transpose().transpose(), a);
+  assert_eq!(a + a - a.scale(2), Matrix::zero());
+  println!("a =\n{a}\na * b =\n{product}\ndet(a * b) = {}", product.determinant());
+
+  let mut m: Matrix<i64, 4, 4> = Matrix::from_fn(|row, col| ((row * 3 + col * 7) % 5) as i64 - 2);
+  m[(0, 0)] = 9;
+  assert_eq!(m * Matrix::identity(), m);
+  assert_eq!(m.determinant(), m.transpose().determinant());
+  println!("m =\n{m}\ndet(m) = {}, trace(m) = {}", m.determinant(), m.trace());
+
+  let rotation = |angle: f64| Matrix::new([[angle.cos(), -angle.sin()], [angle.sin(), angle.cos()]]);
+  let quarter = rotation(std::f64::consts::FRAC_PI_2);
+  println!("a quarter turn:\n{quarter:.3}\nits determinant is {:.3}", quarter.determinant());
+  assert!(((quarter * quarter * quarter * quarter) - Matrix::identity()).trace().abs() < 1e-12);
+
+  // The largest few of a long stream, without keeping the stream around
+  let stream: Vec<u64> = (0..200_000).map(|_| rng.next_u64() % 1_000_000_000).collect();
+  let top = top_k_by_key(stream.iter().copied(), 5, |value| *value);
+  let mut sorted = stream.clone();
+  sorted.sort_unstable_by(|a, b| b.cmp(a));
+  println!("5 largest of {} values: {top:?}, same as sorting: {}", stream.len(), top == sorted[..5]);
+
+  // Items don't need to be Copy or Ord themselves, only the key does
+  let mut longest = TopK::new(3, |word: &String| word.chars().count());
+  longest.extend("the quick brown fox jumps over the lazy dog near a riverbank".split(' ').map(String::from));
+  println!("{} words kept, the shortest is {:?}", longest.len(), longest.threshold());
+  println!("pushing \"to\" gives back {:?}", longest.push(String::from("to")));
+  println!("3 longest words: {:?}", longest.into_sorted_vec());
+  println!("the 0 largest of 1..=10: {:?}", top_k_by_key(1..=10, 0, |n| *n));
+
+  // Dijkstra's shortest paths, lowering the distance of a queued town when a shorter road to it shows up
+  let roads = [("A", "B", 7), ("A", "C", 9), ("A", "F", 14), ("B", "C", 10), ("B", "D", 15), ("C", "D", 11), ("C", "F", 2), ("D", "E", 6), ("E", "F", 9)];
+  let mut distances: Vec<(&str, u32)> = Vec::new();
+  let mut queue = PriorityQueue::new();
+  queue.push("A", 0);
+  while let Some((town, distance)) = queue.pop() {
+    distances.push((town, distance));
+    for (from, to, length) in roads {
+      let neighbour = if from == town { to } else if to == town { from } else { continue };
+      if distances.iter().any(|(done, _)| *done == neighbour) {
+        continue;
+      }
+      let candidate = distance + length;
+      match queue.priority(&neighbour) {
+        None => queue.push(neighbour, candidate),
+        Some(_) => {
+          queue.decrease_key(&neighbour, candidate);
+        },
+      }
+    }
+  }
+  println!("shortest distances from A: {distances:?}");
+
+  // Interval sets of integers, chars and times
+  let working_hours: IntervalSet<u32> = [Interval::closed_open(9, 12), Interval::closed_open(13, 17)].into_iter().collect();
+  let meetings: IntervalSet<u32> = [Interval::closed_open(11, 14), Interval::closed_open(16, 18)].into_iter().collect();
+  let free = working_hours.difference(&meetings);
+  println!("working {working_hours}, meetings {meetings}, free {free}");
+  println!("busy {}, in a meeting during work {}", working_hours.union(&meetings), working_hours.intersection(&meetings));
+  println!("free from 14 to 15: {}", free.contains_interval(&Interval::closed(14, 15)));
+  println!("(3, 4) and (4, 5) share {:?}", Interval::open(3, 4).intersection(&Interval::open(4, 5)));
+
+  let letters: IntervalSet<char> = [Interval::closed('a', 'z')].into_iter().collect();
+  let vowels: IntervalSet<char> = "aeiou".chars().map(|c| Interval::closed(c, c)).collect();
+  let consonants = letters.difference(&vowels);
+  println!("consonants {consonants}, is b one: {}", consonants.contains(&'b'));
+  let first = &consonants.intervals()[0];
+  println!("the first run of consonants goes from {:?} to {:?}", first.start(), first.end());
+
+  let noon = UNIX_EPOCH + Duration::from_secs(1_700_049_600);
+  let minutes = |m: u64| noon + Duration::from_secs(m * 60);
+  let lunch = IntervalSet::from_iter([Interval::closed_open(noon, minutes(60))]);
+  let guests: IntervalSet<SystemTime> = [Interval::at_least(minutes(30))].into_iter().collect();
+  let quiet = lunch.difference(&guests);
+  println!("quiet lunch at 12:20: {}, at 12:40: {}", quiet.contains(&minutes(20)), quiet.contains(&minutes(40)));
+  let morning = IntervalSet::from_iter([Interval::less_than(noon)]);
+  println!("lunch overlaps the morning: {}", !lunch.intersection(&morning).is_empty());
+}
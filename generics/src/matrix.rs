@@ -0,0 +1,287 @@
+//! A matrix whose size is part of its type. `Matrix<f64, 2, 3>` has 2 rows and 3 columns, stored inline as
+//! `[[f64; 3]; 2]` with no heap allocation. Because the sizes are const generic parameters, the compiler checks
+//! them: adding a 2x3 to a 3x2 matrix, or multiplying a 2x3 by a 2x3 matrix, doesn't compile instead of
+//! panicking at runtime.
+//!
+//! ```
+//! use generics::matrix::Matrix;
+//!
+//! let a: Matrix<u32, 2, 3> = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+//! let b: Matrix<u32, 3, 2> = a.transpose();
+//! assert_eq!(a * b, Matrix::new([[14, 32], [32, 77]]));
+//! assert_eq!((a * b).determinant(), 14 * 77 - 32 * 32);
+//! ```
+//!
+//! These are rejected, `cargo test` checks that they really don't compile:
+//!
+//! ```compile_fail,E0308
+//! use generics::matrix::Matrix;
+//!
+//! let a: Matrix<i32, 2, 3> = Matrix::zero();
+//! let b: Matrix<i32, 3, 2> = Matrix::zero();
+//! // expected struct `Matrix<_, 2, 3>` found struct `Matrix<_, 3, 2>`
+//! let sum = a + b;
+//! ```
+//!
+//! ```compile_fail,E0308
+//! use generics::matrix::Matrix;
+//!
+//! let a: Matrix<i32, 2, 3> = Matrix::zero();
+//! let c: Matrix<i32, 2, 3> = Matrix::zero();
+//! // expected struct `Matrix<_, 3, _>` found struct `Matrix<_, 2, 3>`
+//! let product = a * c;
+//! ```
+//!
+//! ```compile_fail,E0599
+//! use generics::matrix::Matrix;
+//!
+//! let a: Matrix<i32, 2, 3> = Matrix::zero();
+//! // no method named `determinant` found for struct `Matrix<i32, 2, 3>`
+//! let d = a.determinant();
+//! ```
+
+use std::{
+  fmt,
+  ops::{Add, Index, IndexMut, Mul, Sub},
+};
+
+// What a matrix entry needs: arithmetic plus a zero and a one to build the zero and identity matrices.
+// SIGNED tells whether the type has negative values, which decides how the determinant adds its terms up.
+pub trait Scalar: Copy + PartialEq + fmt::Display + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+  const ZERO: Self;
+  const ONE: Self;
+  const SIGNED: bool;
+}
+
+macro_rules! scalar {
+  ($($t:ty: $zero:expr, $one:expr, $signed:expr);*) => {
+    $(
+      impl Scalar for $t {
+        const ZERO: $t = $zero;
+        const ONE: $t = $one;
+        const SIGNED: bool = $signed;
+      }
+    )*
+  };
+}
+
+scalar!(
+  i8: 0, 1, true; i16: 0, 1, true; i32: 0, 1, true; i64: 0, 1, true; i128: 0, 1, true; isize: 0, 1, true;
+  u8: 0, 1, false; u16: 0, 1, false; u32: 0, 1, false; u64: 0, 1, false; u128: 0, 1, false; usize: 0, 1, false;
+  f32: 0.0, 1.0, true; f64: 0.0, 1.0, true
+);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix<T, const R: usize, const C: usize> {
+  rows: [[T; C]; R],
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Matrix<T, R, C> {
+  pub fn new(rows: [[T; C]; R]) -> Matrix<T, R, C> {
+    Matrix { rows }
+  }
+
+  pub fn zero() -> Matrix<T, R, C> {
+    Matrix { rows: [[T::ZERO; C]; R] }
+  }
+
+  // Every entry computed from its (row, column)
+  pub fn from_fn(mut entry: impl FnMut(usize, usize) -> T) -> Matrix<T, R, C> {
+    Matrix { rows: std::array::from_fn(|row| std::array::from_fn(|col| entry(row, col))) }
+  }
+
+  pub fn transpose(&self) -> Matrix<T, C, R> {
+    Matrix::from_fn(|row, col| self.rows[col][row])
+  }
+
+  pub fn scale(&self, factor: T) -> Matrix<T, R, C> {
+    Matrix::from_fn(|row, col| self.rows[row][col] * factor)
+  }
+}
+
+impl<T: Scalar, const N: usize> Matrix<T, N, N> {
+  // Ones on the diagonal, zeros everywhere else
+  pub fn identity() -> Matrix<T, N, N> {
+    Matrix::from_fn(|row, col| if row == col { T::ONE } else { T::ZERO })
+  }
+
+  pub fn trace(&self) -> T {
+    (0..N).fold(T::ZERO, |sum, idx| sum + self.rows[idx][idx])
+  }
+
+  // Laplace expansion along the first row. That is n! multiplications, fine for the small matrices this is for.
+  // Signed integers and floats add and subtract every term as it comes, so big terms cancel each other
+  // before a small one is added to them. Unsigned types can't hold the partial results below zero, so for them
+  // the terms added and the terms subtracted are summed separately and only subtracted at the very end. That
+  // works as long as the determinant itself isn't negative. When it is, the subtraction overflows like any
+  // other unsigned subtraction (a panic in debug builds).
+  pub fn determinant(&self) -> T {
+    let rows: Vec<Vec<T>> = self.rows.iter().map(|row| row.to_vec()).collect();
+    if T::SIGNED {
+      signed_determinant(&rows)
+    } else {
+      let (plus, minus) = determinant_parts(&rows);
+      plus - minus
+    }
+  }
+}
+
+// The rows below the first without the given column.
+// The minors get smaller with every step, which the const sizes can't express, so they are Vecs.
+fn minor<T: Scalar>(rows: &[Vec<T>], col: usize) -> Vec<Vec<T>> {
+  rows[1..]
+    .iter()
+    .map(|row| row.iter().enumerate().filter(|(c, _)| *c != col).map(|(_, value)| *value).collect())
+    .collect()
+}
+
+fn signed_determinant<T: Scalar>(rows: &[Vec<T>]) -> T {
+  match rows.len() {
+    0 => T::ONE,
+    1 => rows[0][0],
+    n => (0..n).fold(T::ZERO, |total, col| {
+      let term = rows[0][col] * signed_determinant(&minor(rows, col));
+      if col % 2 == 0 {
+        total + term
+      } else {
+        total - term
+      }
+    }),
+  }
+}
+
+// The determinant as (sum of the positive terms, sum of the negative terms)
+fn determinant_parts<T: Scalar>(rows: &[Vec<T>]) -> (T, T) {
+  match rows.len() {
+    0 => (T::ONE, T::ZERO),
+    1 => (rows[0][0], T::ZERO),
+    n => {
+      let (mut plus, mut minus) = (T::ZERO, T::ZERO);
+      for col in 0..n {
+        // entry * (minor_plus - minor_minus), added for even columns and subtracted for odd ones
+        let (minor_plus, minor_minus) = determinant_parts(&minor(rows, col));
+        let (positive, negative) = (rows[0][col] * minor_plus, rows[0][col] * minor_minus);
+        if col % 2 == 0 {
+          plus = plus + positive;
+          minus = minus + negative;
+        } else {
+          plus = plus + negative;
+          minus = minus + positive;
+        }
+      }
+      (plus, minus)
+    },
+  }
+}
+
+impl<T, const R: usize, const C: usize> Index<(usize, usize)> for Matrix<T, R, C> {
+  type Output = T;
+
+  // m[(row, col)], panics outside the matrix like array indexing does
+  fn index(&self, (row, col): (usize, usize)) -> &T {
+    &self.rows[row][col]
+  }
+}
+
+impl<T, const R: usize, const C: usize> IndexMut<(usize, usize)> for Matrix<T, R, C> {
+  fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+    &mut self.rows[row][col]
+  }
+}
+
+// Only matrices of the same size can be added
+impl<T: Scalar, const R: usize, const C: usize> Add for Matrix<T, R, C> {
+  type Output = Matrix<T, R, C>;
+
+  fn add(self, other: Matrix<T, R, C>) -> Matrix<T, R, C> {
+    Matrix::from_fn(|row, col| self.rows[row][col] + other.rows[row][col])
+  }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Sub for Matrix<T, R, C> {
+  type Output = Matrix<T, R, C>;
+
+  fn sub(self, other: Matrix<T, R, C>) -> Matrix<T, R, C> {
+    Matrix::from_fn(|row, col| self.rows[row][col] - other.rows[row][col])
+  }
+}
+
+// (R x C) * (C x K) = (R x K): the inner sizes have to match, the `C` appearing in both types makes sure of it
+impl<T: Scalar, const R: usize, const C: usize, const K: usize> Mul<Matrix<T, C, K>> for Matrix<T, R, C> {
+  type Output = Matrix<T, R, K>;
+
+  fn mul(self, other: Matrix<T, C, K>) -> Matrix<T, R, K> {
+    Matrix::from_fn(|row, col| (0..C).fold(T::ZERO, |sum, idx| sum + self.rows[row][idx] * other.rows[idx][col]))
+  }
+}
+
+// Columns lined up, one row per line:
+// [ 1  -2 ]
+// [ 3  10 ]
+impl<T: Scalar, const R: usize, const C: usize> fmt::Display for Matrix<T, R, C> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    // the precision is passed on to the entries, so `{:.2}` works for float matrices
+    let cells: Vec<Vec<String>> = self
+      .rows
+      .iter()
+      .map(|row| {
+        row
+          .iter()
+          .map(|value| match f.precision() {
+            Some(precision) => format!("{value:.precision$}"),
+            None => value.to_string(),
+          })
+          .collect()
+      })
+      .collect();
+    let widths: Vec<usize> = (0..C).map(|col| cells.iter().map(|row| row[col].len()).max().unwrap_or(0)).collect();
+    for (idx, row) in cells.iter().enumerate() {
+      if idx > 0 {
+        writeln!(f)?;
+      }
+      write!(f, "[")?;
+      for (cell, width) in row.iter().zip(&widths) {
+        write!(f, " {cell:>width$}")?;
+      }
+      write!(f, " ]")?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn float_terms_cancel_before_small_ones_are_added() {
+    // exactly 2: the two 1e16 sized terms cancel. Summed apart, the positive terms round to a different
+    // multiple of 2 than the negative ones and the difference comes out as 4.
+    let m = Matrix::new([[1.0, 1e16, 1.0], [1.0, 1e16 + 2.0, 0.0], [1.0, 1e16, 2.0]]);
+    assert_eq!(m.determinant(), 2.0);
+    let rows: Vec<Vec<f64>> = m.rows.iter().map(|row| row.to_vec()).collect();
+    let (plus, minus) = determinant_parts(&rows);
+    assert_eq!(plus - minus, 4.0);
+  }
+
+  #[test]
+  fn signed_and_unsigned_agree_when_the_determinant_is_not_negative() {
+    // one of the minors is negative on the way, 1 * 1 - 3 * 1
+    let signed: Matrix<i64, 3, 3> = Matrix::new([[2, 0, 1], [1, 3, 2], [1, 1, 2]]);
+    let unsigned: Matrix<u64, 3, 3> = Matrix::new([[2, 0, 1], [1, 3, 2], [1, 1, 2]]);
+    assert_eq!(signed.determinant(), 6);
+    assert_eq!(unsigned.determinant(), 6);
+    // swapping two rows flips the sign, which only the signed type can hold
+    let swapped: Matrix<i64, 3, 3> = Matrix::new([[1, 3, 2], [2, 0, 1], [1, 1, 2]]);
+    assert_eq!(swapped.determinant(), -6);
+  }
+
+  #[test]
+  fn determinant_of_small_sizes() {
+    assert_eq!(Matrix::<i32, 0, 0>::zero().determinant(), 1);
+    assert_eq!(Matrix::new([[-7i8]]).determinant(), -7);
+    assert_eq!(Matrix::new([[1u8, 2], [3, 7]]).determinant(), 1);
+    assert_eq!(Matrix::<f32, 4, 4>::identity().determinant(), 1.0);
+    assert_eq!(Matrix::<i32, 4, 4>::from_fn(|row, col| (row * 4 + col) as i32).determinant(), 0);
+  }
+}
@@ -0,0 +1,405 @@
+// Descriptive statistics for any number type.
+// The Numeric trait is the one bound all functions share. Besides converting to f64 it picks a wider type to add
+// values up in: adding a few u8 or i64 values in their own type overflows quickly, so integers are summed as
+// i128 and only the final result is turned into an f64. Floats are summed as f64.
+//
+// Like in largest.rs, NaN values are left out. Empty input (or input that was all NaN) gives None.
+
+use std::{cmp::Ordering, ops::Add};
+
+use crate::largest::comparable;
+
+pub trait Numeric: Copy + PartialOrd {
+  type Sum: Copy + Default + Add<Output = Self::Sum>;
+
+  fn widen(self) -> Self::Sum;
+  fn sum_to_f64(sum: Self::Sum) -> f64;
+  fn to_f64(self) -> f64;
+}
+
+// The same impl for every integer type, `$t as i128` can't lose anything for these
+macro_rules! numeric_integer {
+  ($($t:ty),*) => {
+    $(
+      impl Numeric for $t {
+        type Sum = i128;
+
+        fn widen(self) -> i128 {
+          self as i128
+        }
+
+        fn sum_to_f64(sum: i128) -> f64 {
+          sum as f64
+        }
+
+        fn to_f64(self) -> f64 {
+          self as f64
+        }
+      }
+    )*
+  };
+}
+
+numeric_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! numeric_float {
+  ($($t:ty),*) => {
+    $(
+      impl Numeric for $t {
+        type Sum = f64;
+
+        fn widen(self) -> f64 {
+          self as f64
+        }
+
+        fn sum_to_f64(sum: f64) -> f64 {
+          sum
+        }
+
+        fn to_f64(self) -> f64 {
+          self as f64
+        }
+      }
+    )*
+  };
+}
+
+numeric_float!(f32, f64);
+
+// The values without NaN, sorted
+fn sorted<T: Numeric>(values: &[T]) -> Vec<T> {
+  let mut sorted: Vec<T> = values.iter().copied().filter(comparable).collect();
+  sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+  sorted
+}
+
+pub fn sum<T: Numeric>(values: &[T]) -> T::Sum {
+  values.iter().filter(|v| comparable(*v)).fold(T::Sum::default(), |sum, v| sum + v.widen())
+}
+
+pub fn mean<T: Numeric>(values: &[T]) -> Option<f64> {
+  let count = values.iter().filter(|v| comparable(*v)).count();
+  if count == 0 {
+    return None;
+  }
+  Some(T::sum_to_f64(sum(values)) / count as f64)
+}
+
+// The middle value, or the average of the two middle values for an even count
+pub fn median<T: Numeric>(values: &[T]) -> Option<f64> {
+  percentile(values, 50.0)
+}
+
+// The most frequent values, smallest first. Several values are returned when they are equally frequent.
+pub fn mode<T: Numeric>(values: &[T]) -> Vec<T> {
+  let sorted = sorted(values);
+  // equal values are next to each other after sorting, so counting runs is enough
+  let mut runs: Vec<(T, usize)> = Vec::new();
+  for value in sorted {
+    match runs.last_mut() {
+      Some((last, count)) if *last == value => *count += 1,
+      _ => runs.push((value, 1)),
+    }
+  }
+  let most = runs.iter().map(|(_, count)| *count).max().unwrap_or(0);
+  runs.into_iter().filter(|(_, count)| *count == most).map(|(value, _)| value).collect()
+}
+
+// Mean of the squared distances to the mean, dividing by `count - correction`
+fn variance_with<T: Numeric>(values: &[T], correction: usize) -> Option<f64> {
+  let mean = mean(values)?;
+  let finite: Vec<f64> = values.iter().filter(|v| comparable(*v)).map(|v| v.to_f64()).collect();
+  if finite.len() <= correction {
+    return None;
+  }
+  let squares: f64 = finite.iter().map(|v| (v - mean) * (v - mean)).sum();
+  Some(squares / (finite.len() - correction) as f64)
+}
+
+// Population variance, for when the values are all there is
+pub fn variance<T: Numeric>(values: &[T]) -> Option<f64> {
+  variance_with(values, 0)
+}
+
+// Sample variance (Bessel's correction), for when the values are a sample of something bigger. Needs two values.
+pub fn sample_variance<T: Numeric>(values: &[T]) -> Option<f64> {
+  variance_with(values, 1)
+}
+
+pub fn std_dev<T: Numeric>(values: &[T]) -> Option<f64> {
+  variance(values).map(f64::sqrt)
+}
+
+pub fn sample_std_dev<T: Numeric>(values: &[T]) -> Option<f64> {
+  sample_variance(values).map(f64::sqrt)
+}
+
+// The value below which `p` percent of the values fall, interpolating linearly between neighbours.
+// `p` goes from 0 (the minimum) to 100 (the maximum), anything outside that gives None.
+pub fn percentile<T: Numeric>(values: &[T], p: f64) -> Option<f64> {
+  if !(0.0..=100.0).contains(&p) {
+    return None;
+  }
+  let sorted = sorted(values);
+  if sorted.is_empty() {
+    return None;
+  }
+  let rank = p / 100.0 * (sorted.len() - 1) as f64;
+  let below = rank.floor() as usize;
+  let above = rank.ceil() as usize;
+  let low = sorted[below].to_f64();
+  let high = sorted[above].to_f64();
+  Some(low + (high - low) * (rank - below as f64))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bin {
+  pub low: f64,
+  pub high: f64,
+  pub count: usize,
+}
+
+// Splits the range from the smallest to the largest value into `bins` equally wide bins and counts the values in
+// each. Bins include their low end, the last one also includes its high end so the maximum is counted.
+// The range only spans the finite values, an infinite value can't be given a place inside it. -inf is counted in
+// the first bin and +inf in the last, and when there are no finite values there is no range and no bins.
+pub fn histogram<T: Numeric>(values: &[T], bins: usize) -> Vec<Bin> {
+  let sorted = sorted(values);
+  let mut finite = sorted.iter().map(|value| value.to_f64()).filter(|value| value.is_finite());
+  let (min, max) = match (finite.next(), finite.next_back()) {
+    (Some(min), max) if bins > 0 => (min, max.unwrap_or(min)),
+    _ => return Vec::new(),
+  };
+  // halved first so that e.g. f64::MAX - f64::MIN doesn't overflow to infinity
+  let width = (max / 2.0 - min / 2.0) / bins as f64 * 2.0;
+  let mut histogram: Vec<Bin> = (0..bins)
+    .map(|idx| Bin { low: min + width * idx as f64, high: min + width * (idx + 1) as f64, count: 0 })
+    .collect();
+  // the last bin ends exactly at the maximum, whatever rounding did to the sum above
+  histogram[bins - 1].high = max;
+  for value in sorted {
+    let value = value.to_f64();
+    // with all values equal the width is 0 and everything goes into the first bin. The cast saturates, so
+    // -inf ends up at 0 and +inf at the last bin.
+    let idx = if width > 0.0 { ((value - min) / width) as usize } else if value > max { bins - 1 } else { 0 };
+    histogram[idx.min(bins - 1)].count += 1;
+  }
+  histogram
+}
+
+// Statistics that are updated one value at a time, without keeping the values around.
+// The variance uses Welford's method: it tracks the mean and the sum of squared distances to it, which stays
+// accurate where the textbook `sum of squares - square of sum` loses everything to rounding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunningStats<T: Numeric> {
+  count: usize,
+  mean: f64,
+  // sum of squared distances to the current mean
+  m2: f64,
+  min: Option<T>,
+  max: Option<T>,
+}
+
+impl<T: Numeric> Default for RunningStats<T> {
+  fn default() -> RunningStats<T> {
+    RunningStats { count: 0, mean: 0.0, m2: 0.0, min: None, max: None }
+  }
+}
+
+impl<T: Numeric> RunningStats<T> {
+  pub fn new() -> RunningStats<T> {
+    RunningStats::default()
+  }
+
+  pub fn push(&mut self, value: T) {
+    if !comparable(&value) {
+      return;
+    }
+    self.count += 1;
+    let x = value.to_f64();
+    let delta = x - self.mean;
+    self.mean += delta / self.count as f64;
+    self.m2 += delta * (x - self.mean);
+    if self.min.is_none_or(|min| value < min) {
+      self.min = Some(value);
+    }
+    if self.max.is_none_or(|max| value > max) {
+      self.max = Some(value);
+    }
+  }
+
+  pub fn count(&self) -> usize {
+    self.count
+  }
+
+  pub fn mean(&self) -> Option<f64> {
+    (self.count > 0).then_some(self.mean)
+  }
+
+  pub fn variance(&self) -> Option<f64> {
+    (self.count > 0).then(|| self.m2 / self.count as f64)
+  }
+
+  pub fn sample_variance(&self) -> Option<f64> {
+    (self.count > 1).then(|| self.m2 / (self.count - 1) as f64)
+  }
+
+  pub fn std_dev(&self) -> Option<f64> {
+    self.variance().map(f64::sqrt)
+  }
+
+  pub fn min(&self) -> Option<T> {
+    self.min
+  }
+
+  pub fn max(&self) -> Option<T> {
+    self.max
+  }
+}
+
+impl<T: Numeric> Extend<T> for RunningStats<T> {
+  fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+    for value in values {
+      self.push(value);
+    }
+  }
+}
+
+impl<T: Numeric> FromIterator<T> for RunningStats<T> {
+  fn from_iter<I: IntoIterator<Item = T>>(values: I) -> RunningStats<T> {
+    let mut stats = RunningStats::new();
+    stats.extend(values);
+    stats
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::XorShift;
+
+  fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * b.abs().max(1.0)
+  }
+
+  #[test]
+  fn integers_are_summed_without_overflow() {
+    let scores: Vec<u8> = vec![200, 180, 250, 180, 90, 255, 180, 120];
+    assert_eq!(sum(&scores), 1455);
+    assert_eq!(mean(&scores), Some(181.875));
+    let huge = [i64::MAX, i64::MAX, i64::MIN + 1];
+    assert_eq!(sum(&huge), i64::MAX as i128);
+    assert_eq!(mean(&huge), Some(i64::MAX as f64 / 3.0));
+    assert_eq!(sum(&[i64::MAX; 4]), 4 * i64::MAX as i128);
+    assert_eq!(sum(&[u64::MAX, u64::MAX]), 2 * u64::MAX as i128);
+    assert_eq!(sum::<i32>(&[]), 0);
+  }
+
+  #[test]
+  fn median_of_odd_and_even_counts() {
+    assert_eq!(median(&[3, 1, 2]), Some(2.0));
+    assert_eq!(median(&[4, 1, 3, 2]), Some(2.5));
+    assert_eq!(median(&[200u8, 180, 250, 180, 90, 255, 180, 120]), Some(180.0));
+    // NaN doesn't count, leaving an even number of values
+    assert_eq!(median(&[21.5f32, f32::NAN, 19.0, 23.25, 19.0]), Some(20.25));
+    assert_eq!(median(&[7]), Some(7.0));
+    assert_eq!(median::<i32>(&[]), None);
+    assert_eq!(median(&[f64::NAN]), None);
+  }
+
+  #[test]
+  fn percentiles_at_and_past_the_ends() {
+    let values = [10, 40, 20, 30];
+    assert_eq!(percentile(&values, 0.0), Some(10.0));
+    assert_eq!(percentile(&values, 100.0), Some(40.0));
+    // a third of the way from 20 to 30
+    assert!(close(percentile(&values, 50.0).unwrap(), 25.0));
+    assert!(close(percentile(&values, 90.0).unwrap(), 37.0));
+    for outside in [-0.001, 100.001, -50.0, 1e9, f64::NAN, f64::INFINITY] {
+      assert_eq!(percentile(&values, outside), None, "{outside}");
+    }
+    assert_eq!(percentile(&[5], 0.0), Some(5.0));
+    assert_eq!(percentile(&[5], 100.0), Some(5.0));
+  }
+
+  #[test]
+  fn mode_returns_every_most_frequent_value() {
+    assert_eq!(mode(&[200u8, 180, 250, 180, 90, 255, 180, 120]), vec![180]);
+    assert_eq!(mode(&[3, 1, 3, 1, 2]), vec![1, 3]);
+    assert_eq!(mode(&[21.5f32, f32::NAN, 19.0, 23.25, 19.0]), vec![19.0]);
+    assert_eq!(mode::<i32>(&[]), Vec::<i32>::new());
+  }
+
+  #[test]
+  fn variance_needs_enough_values() {
+    assert_eq!(variance(&[2, 4, 4, 4, 5, 5, 7, 9]), Some(4.0));
+    assert_eq!(std_dev(&[2, 4, 4, 4, 5, 5, 7, 9]), Some(2.0));
+    assert!(close(sample_variance(&[2, 4, 4, 4, 5, 5, 7, 9]).unwrap(), 32.0 / 7.0));
+    assert_eq!(variance(&[4.0]), Some(0.0));
+    assert_eq!(sample_variance(&[4.0]), None);
+    assert_eq!(sample_std_dev(&[4.0, f64::NAN]), None);
+    assert_eq!(mean::<f64>(&[]), None);
+    assert_eq!(variance::<f64>(&[]), None);
+  }
+
+  #[test]
+  fn histogram_bins() {
+    let bins = histogram(&[0, 1, 2, 3, 4, 5, 6, 7, 8], 4);
+    assert_eq!(bins.iter().map(|bin| bin.count).collect::<Vec<_>>(), vec![2, 2, 2, 3]);
+    assert_eq!((bins[0].low, bins[0].high, bins[3].low, bins[3].high), (0.0, 2.0, 6.0, 8.0));
+    assert!(histogram(&[1, 2], 0).is_empty());
+    assert!(histogram::<i32>(&[], 3).is_empty());
+  }
+
+  #[test]
+  fn histogram_of_equal_values() {
+    let bins = histogram(&[5, 5, 5], 3);
+    assert_eq!(bins.iter().map(|bin| bin.count).collect::<Vec<_>>(), vec![3, 0, 0]);
+    assert!(bins.iter().all(|bin| bin.low == 5.0 && bin.high == 5.0));
+  }
+
+  #[test]
+  fn histogram_with_infinities() {
+    let values = [f64::NEG_INFINITY, 0.0, 1.0, 2.0, 4.0, f64::INFINITY, f64::NAN];
+    let bins = histogram(&values, 2);
+    assert_eq!(bins, vec![Bin { low: 0.0, high: 2.0, count: 3 }, Bin { low: 2.0, high: 4.0, count: 3 }]);
+
+    // one finite value, the infinities go to either end of the empty range
+    let bins = histogram(&[f64::INFINITY, 3.0, f64::NEG_INFINITY], 2);
+    assert_eq!(bins.iter().map(|bin| bin.count).collect::<Vec<_>>(), vec![2, 1]);
+    assert!(bins.iter().all(|bin| bin.low.is_finite() && bin.high.is_finite()));
+
+    assert!(histogram(&[f64::INFINITY, f64::NEG_INFINITY], 2).is_empty());
+    // the whole f64 range doesn't overflow the bin width
+    let bins = histogram(&[f64::MIN, f64::MAX], 2);
+    assert_eq!(bins.iter().map(|bin| bin.count).collect::<Vec<_>>(), vec![1, 1]);
+    assert_eq!((bins[0].low, bins[0].high, bins[1].high), (f64::MIN, 0.0, f64::MAX));
+  }
+
+  #[test]
+  fn running_stats_match_the_batch_functions() {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let samples: Vec<i32> = (0..10_000).map(|_| 1_000_000 + (rng.next_u64() % 1000) as i32).collect();
+    let running: RunningStats<i32> = samples.iter().copied().collect();
+    let close = |a: f64, b: f64| (a - b).abs() <= 1e-6 * b.abs().max(1.0);
+    assert_eq!(running.count(), samples.len());
+    assert!(close(running.mean().unwrap(), mean(&samples).unwrap()));
+    assert!(close(running.variance().unwrap(), variance(&samples).unwrap()));
+    assert!(close(running.sample_variance().unwrap(), sample_variance(&samples).unwrap()));
+    assert!(close(running.std_dev().unwrap(), std_dev(&samples).unwrap()));
+    assert_eq!(running.min(), samples.iter().copied().min());
+    assert_eq!(running.max(), samples.iter().copied().max());
+  }
+
+  #[test]
+  fn running_stats_skip_nan_and_start_empty() {
+    let mut readings = RunningStats::new();
+    assert_eq!((readings.count(), readings.mean(), readings.variance(), readings.min()), (0, None, None, None));
+    readings.extend([21.5f32, f32::NAN, 19.0, 23.25, 19.0]);
+    readings.push(f32::NAN);
+    assert_eq!((readings.count(), readings.min(), readings.max()), (4, Some(19.0), Some(23.25)));
+    let mut one = RunningStats::new();
+    one.push(3u8);
+    assert_eq!((one.variance(), one.sample_variance()), (Some(0.0), None));
+  }
+}
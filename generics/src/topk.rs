@@ -0,0 +1,345 @@
+// `largest` again, now for the k largest items of a stream too long to sort or even keep in memory.
+//
+// TopK keeps the best k items seen so far in a min-heap: the root is the weakest item kept, so a new item only
+// has to beat the root to get in, and then it replaces it. That is O(log k) per item and O(k) memory however
+// long the stream is. Items are compared by a key function, and the heap is written out by hand on top of a Vec
+// because std's BinaryHeap can only order by the items' own Ord.
+//
+// PriorityQueue is the other half: a min-queue on std's BinaryHeap that can lower the priority of an entry that
+// is already queued (decrease-key), which is what Dijkstra's shortest paths needs.
+
+use std::{
+  cmp::{Ordering, Reverse},
+  collections::{BinaryHeap, HashMap},
+  hash::Hash,
+};
+
+pub struct TopK<T, F> {
+  k: usize,
+  key: F,
+  // a min-heap by key: every item is not larger than its children at 2i + 1 and 2i + 2
+  heap: Vec<T>,
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> TopK<T, F> {
+  pub fn new(k: usize, key: F) -> TopK<T, F> {
+    TopK { k, key, heap: Vec::with_capacity(k) }
+  }
+
+  pub fn len(&self) -> usize {
+    self.heap.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.heap.is_empty()
+  }
+
+  // The smallest item kept, a new item has to have a larger key than this one to get in once k items are kept
+  pub fn threshold(&self) -> Option<&T> {
+    self.heap.first()
+  }
+
+  // Returns the item that didn't make it (the new one or the one it pushed out), if any.
+  // On equal keys the item that came first stays.
+  pub fn push(&mut self, item: T) -> Option<T> {
+    if self.k == 0 {
+      return Some(item);
+    }
+    if self.heap.len() < self.k {
+      self.heap.push(item);
+      self.sift_up(self.heap.len() - 1);
+      return None;
+    }
+    if (self.key)(&item) <= (self.key)(&self.heap[0]) {
+      return Some(item);
+    }
+    let removed = std::mem::replace(&mut self.heap[0], item);
+    self.sift_down(0);
+    Some(removed)
+  }
+
+  // The kept items, largest key first
+  pub fn into_sorted_vec(self) -> Vec<T> {
+    let key = self.key;
+    let mut items = self.heap;
+    items.sort_by_key(|item| Reverse(key(item)));
+    items
+  }
+
+  fn less(&self, a: usize, b: usize) -> bool {
+    (self.key)(&self.heap[a]) < (self.key)(&self.heap[b])
+  }
+
+  fn sift_up(&mut self, mut idx: usize) {
+    while idx > 0 {
+      let parent = (idx - 1) / 2;
+      if !self.less(idx, parent) {
+        break;
+      }
+      self.heap.swap(idx, parent);
+      idx = parent;
+    }
+  }
+
+  fn sift_down(&mut self, mut idx: usize) {
+    loop {
+      let mut smallest = idx;
+      for child in [2 * idx + 1, 2 * idx + 2] {
+        if child < self.heap.len() && self.less(child, smallest) {
+          smallest = child;
+        }
+      }
+      if smallest == idx {
+        break;
+      }
+      self.heap.swap(idx, smallest);
+      idx = smallest;
+    }
+  }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> Extend<T> for TopK<T, F> {
+  fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+    for item in items {
+      self.push(item);
+    }
+  }
+}
+
+// The k items with the largest keys, largest first
+pub fn top_k_by_key<I, K, F>(items: I, k: usize, key: F) -> Vec<I::Item>
+where
+  I: IntoIterator,
+  K: Ord,
+  F: Fn(&I::Item) -> K,
+{
+  let mut top = TopK::new(k, key);
+  top.extend(items);
+  top.into_sorted_vec()
+}
+
+// What is stored in the BinaryHeap. Ordered by priority, then by insertion so equal priorities come out first in
+// first out, and reversed because BinaryHeap pops its largest item and this is a min-queue.
+struct Entry<K, P> {
+  priority: P,
+  sequence: u64,
+  key: K,
+}
+
+impl<K, P: Ord> PartialEq for Entry<K, P> {
+  fn eq(&self, other: &Entry<K, P>) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl<K, P: Ord> Eq for Entry<K, P> {}
+
+impl<K, P: Ord> PartialOrd for Entry<K, P> {
+  fn partial_cmp(&self, other: &Entry<K, P>) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<K, P: Ord> Ord for Entry<K, P> {
+  fn cmp(&self, other: &Entry<K, P>) -> Ordering {
+    (&self.priority, self.sequence).cmp(&(&other.priority, other.sequence))
+  }
+}
+
+// A BinaryHeap can't find and change an entry in place. Changing a priority pushes a new entry instead and
+// remembers which one is current in `current`; outdated entries are thrown away when they reach the top.
+pub struct PriorityQueue<K, P> {
+  heap: BinaryHeap<Reverse<Entry<K, P>>>,
+  // the live priority and sequence number of every queued key
+  current: HashMap<K, (P, u64)>,
+  next_sequence: u64,
+}
+
+impl<K: Hash + Eq + Clone, P: Ord + Clone> Default for PriorityQueue<K, P> {
+  fn default() -> PriorityQueue<K, P> {
+    PriorityQueue { heap: BinaryHeap::new(), current: HashMap::new(), next_sequence: 0 }
+  }
+}
+
+impl<K: Hash + Eq + Clone, P: Ord + Clone> PriorityQueue<K, P> {
+  pub fn new() -> PriorityQueue<K, P> {
+    PriorityQueue::default()
+  }
+
+  pub fn len(&self) -> usize {
+    self.current.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.current.is_empty()
+  }
+
+  pub fn priority(&self, key: &K) -> Option<&P> {
+    self.current.get(key).map(|(priority, _)| priority)
+  }
+
+  // Queues the key, or moves it to the new priority if it is already queued
+  pub fn push(&mut self, key: K, priority: P) {
+    let sequence = self.next_sequence;
+    self.next_sequence += 1;
+    self.current.insert(key.clone(), (priority.clone(), sequence));
+    self.heap.push(Reverse(Entry { priority, sequence, key }));
+  }
+
+  // Lowers the priority of a queued key. Returns false, changing nothing, when the key isn't queued or the
+  // new priority is not lower.
+  pub fn decrease_key(&mut self, key: &K, priority: P) -> bool {
+    match self.current.get(key) {
+      Some((old, _)) if priority < *old => {
+        self.push(key.clone(), priority);
+        true
+      },
+      _ => false,
+    }
+  }
+
+  // The key with the lowest priority
+  pub fn pop(&mut self) -> Option<(K, P)> {
+    while let Some(Reverse(entry)) = self.heap.pop() {
+      let live = matches!(self.current.get(&entry.key), Some((_, sequence)) if *sequence == entry.sequence);
+      if live {
+        self.current.remove(&entry.key);
+        return Some((entry.key, entry.priority));
+      }
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::XorShift;
+
+  #[test]
+  fn top_k_of_a_stream_matches_sorting_it() {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let stream: Vec<u64> = (0..20_000).map(|_| rng.next_u64() % 1000).collect();
+    let mut sorted = stream.clone();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    for k in [1, 5, 100] {
+      assert_eq!(top_k_by_key(stream.iter().copied(), k, |value| *value), sorted[..k]);
+    }
+    // asking for more than there are gives all of them
+    assert_eq!(top_k_by_key([3, 1, 2], 10, |n| *n), vec![3, 2, 1]);
+  }
+
+  #[test]
+  fn k_zero_keeps_nothing() {
+    let mut top = TopK::new(0, |n: &i32| *n);
+    assert_eq!(top.push(7), Some(7));
+    assert!(top.is_empty());
+    assert_eq!(top.threshold(), None);
+    assert!(top_k_by_key(1..=10, 0, |n| *n).is_empty());
+  }
+
+  #[test]
+  fn on_equal_keys_the_item_that_came_first_stays() {
+    let mut longest = TopK::new(2, |word: &&str| word.len());
+    assert_eq!(longest.push("fox"), None);
+    assert_eq!(longest.push("dog"), None);
+    assert_eq!(longest.len(), 2);
+    // as long as the kept ones, but not longer
+    assert_eq!(longest.push("cat"), Some("cat"));
+    assert_eq!(longest.push("owl"), Some("owl"));
+    // longer pushes out one of the 3 letter words and the other one stays
+    assert_eq!(longest.threshold().map(|word| word.len()), Some(3));
+    assert!(matches!(longest.push("horse"), Some("fox" | "dog")));
+    assert_eq!(longest.push("to"), Some("to"));
+    let kept = longest.into_sorted_vec();
+    assert_eq!(kept[0], "horse");
+    assert!(kept[1] == "fox" || kept[1] == "dog");
+  }
+
+  #[test]
+  fn items_only_need_an_ordered_key() {
+    let mut longest = TopK::new(3, |word: &String| word.chars().count());
+    longest.extend("the quick brown fox jumps over the lazy dog near a riverbank".split(' ').map(String::from));
+    assert_eq!(longest.len(), 3);
+    assert_eq!(longest.threshold().map(|word| word.chars().count()), Some(5));
+    assert_eq!(longest.push(String::from("to")), Some(String::from("to")));
+    assert_eq!(longest.into_sorted_vec(), vec!["riverbank", "quick", "brown"]);
+  }
+
+  #[test]
+  fn decrease_key_only_lowers() {
+    let mut queue = PriorityQueue::new();
+    queue.push("a", 5);
+    assert!(!queue.decrease_key(&"a", 5));
+    assert!(!queue.decrease_key(&"a", 9));
+    assert_eq!(queue.priority(&"a"), Some(&5));
+    assert!(!queue.decrease_key(&"missing", 0));
+    assert_eq!(queue.priority(&"missing"), None);
+    assert!(queue.decrease_key(&"a", 2));
+    assert_eq!(queue.priority(&"a"), Some(&2));
+    assert_eq!(queue.len(), 1);
+  }
+
+  #[test]
+  fn outdated_entries_are_skipped() {
+    let mut queue = PriorityQueue::new();
+    queue.push("a", 5);
+    queue.push("b", 4);
+    queue.decrease_key(&"a", 1);
+    // pushing again moves "b" up to 8, its old entry at 4 is still in the heap
+    queue.push("b", 8);
+    queue.push("c", 6);
+    assert_eq!(queue.pop(), Some(("a", 1)));
+    assert_eq!(queue.pop(), Some(("c", 6)));
+    assert_eq!(queue.pop(), Some(("b", 8)));
+    assert_eq!(queue.pop(), None);
+    assert!(queue.is_empty());
+  }
+
+  #[test]
+  fn pushing_the_same_key_again_counts_it_once() {
+    let mut queue = PriorityQueue::new();
+    for priority in [3, 1, 2, 2] {
+      queue.push("a", priority);
+      assert_eq!(queue.len(), 1);
+    }
+    // the last push wins, even though it isn't the lowest
+    assert_eq!(queue.pop(), Some(("a", 2)));
+    assert_eq!(queue.pop(), None);
+    assert_eq!(queue.len(), 0);
+  }
+
+  #[test]
+  fn equal_priorities_come_out_first_in_first_out() {
+    let mut queue = PriorityQueue::new();
+    for key in ["x", "y", "z"] {
+      queue.push(key, 0);
+    }
+    let order: Vec<&str> = std::iter::from_fn(|| queue.pop()).map(|(key, _)| key).collect();
+    assert_eq!(order, vec!["x", "y", "z"]);
+  }
+
+  #[test]
+  fn dijkstra_shortest_distances() {
+    let roads = [("A", "B", 7), ("A", "C", 9), ("A", "F", 14), ("B", "C", 10), ("B", "D", 15), ("C", "D", 11), ("C", "F", 2), ("D", "E", 6), ("E", "F", 9)];
+    let mut distances: Vec<(&str, u32)> = Vec::new();
+    let mut queue = PriorityQueue::new();
+    queue.push("A", 0);
+    while let Some((town, distance)) = queue.pop() {
+      distances.push((town, distance));
+      for (from, to, length) in roads {
+        let neighbour = if from == town { to } else if to == town { from } else { continue };
+        if distances.iter().any(|(done, _)| *done == neighbour) {
+          continue;
+        }
+        if queue.priority(&neighbour).is_none() {
+          queue.push(neighbour, distance + length);
+        } else {
+          queue.decrease_key(&neighbour, distance + length);
+        }
+      }
+    }
+    assert!(queue.is_empty());
+    assert_eq!(distances, [("A", 0), ("B", 7), ("C", 9), ("F", 11), ("D", 20), ("E", 20)]);
+  }
+}
@@ -1,25 +1,149 @@
-#[derive(Debug)]
-struct Rectangle {
-  width: i32,
-  height: i32,
-}
+mod packing;
+mod quadtree;
+mod rectangle;
+mod render;
+
+use std::{env, fs, process};
+
+use packing::{Packing, Strategy};
+use quadtree::QuadTree;
+use rectangle::{HoldOptions, PlacedRectangle, Rectangle};
+use render::Drawn;
+
+fn main() {
+  // `cargo run -- svg <output.svg>` writes the packed panels below as an SVG file instead of running the demo
+  let args: Vec<String> = env::args().skip(1).collect();
+  if !args.is_empty() {
+    match args.as_slice() {
+      [command, output] if command == "svg" => {
+        let (_, drawing) = pack_panels();
+        match fs::write(output, render::to_svg(&drawing, 400, 320)) {
+          Ok(()) => println!("SVG written to {output}"),
+          Err(e) => {
+            eprintln!("error: could not write {output}: {e}");
+            process::exit(1);
+          }
+        }
+      }
+      _ => {
+        eprintln!("usage: methods svg <output.svg>");
+        process::exit(2);
+      }
+    }
+    return;
+  }
+
+  let rect = Rectangle::new(4, 5).expect("sides are not negative");
+
+  println!("rectangle is {}x{}", rect.width(), rect.height());
+  println!("area of rectangle: {}", rect.area());
+  println!("perimeter of rectangle: {}", rect.perimeter());
+  println!("can hold: {}", rect.can_hold(&Rectangle::new(2, 1).unwrap()));
+  println!("can hold a rectangle of the same size: {}", rect.can_hold(&rect));
+  println!("can hold a 4x4 square: {}", rect.can_hold(&Rectangle::square(4).unwrap()));
+
+  // 4x3 only fits into 4x5 once it is turned on its side
+  let wide = Rectangle::new(4, 3).unwrap();
+  let rotation = HoldOptions { allow_rotation: true, ..HoldOptions::default() };
+  println!("can hold {:?}: {}, when rotated: {}", wide, rect.can_hold(&wide), rect.can_hold_with(&wide, rotation));
+  let equal = HoldOptions { allow_equal: true, ..HoldOptions::default() };
+  println!("can hold a rectangle of the same size when equal sides fit: {}", rect.can_hold_with(&rect, equal));
+
+  println!("common size with {:?}: {:?}", wide, rect.common_size(&wide));
+  println!("covering size with {:?}: {:?}", wide, rect.covering_size(&wide));
+  println!("contains (4, 5): {}, contains (5, 4): {}", rect.contains_point(4, 5), rect.contains_point(5, 4));
+
+  // Negative sides are rejected instead of producing a negative area
+  match Rectangle::new(-4, 5) {
+    Ok(r) => println!("created {:?}", r),
+    Err(e) => println!("error: {e}"),
+  }
+
+  // i32::MAX * 2 does not fit into an i32, `area` widens to u64 and `checked_area` reports the overflow
+  let huge = Rectangle::new(i32::MAX, 2).unwrap();
+  println!("area of huge rectangle: {}, checked area: {:?}", huge.area(), huge.checked_area());
+
+  // Packing UI panels into a 10x8 screen, `cargo run -- svg <output.svg>` saves the drawing of it
+  let (packing, drawing) = pack_panels();
+  for p in &packing.placements {
+    println!("panel {} ({}x{}) at ({}, {}), rotated: {}", p.item, p.size.width(), p.size.height(), p.x, p.y, p.rotated);
+  }
+  println!("did not fit: {:?}, wasted area: {}", packing.unplaced, packing.wasted_area());
+  print!("{}", render::to_ascii(&drawing, 60, 20));
 
-impl Rectangle {
-  fn area(&self) -> i32 {
-    return self.height * self.width;
+  // How well each strategy does on random inputs
+  let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+  let container = Rectangle::new(100, 100).unwrap();
+  let mut totals = [0.0; 3];
+  let rounds = 50;
+  for _ in 0..rounds {
+    let items: Vec<Rectangle> = (0..40)
+      .map(|_| Rectangle::new(rng.next_in(5, 30), rng.next_in(5, 30)).unwrap())
+      .collect();
+    for (total, packing) in totals.iter_mut().zip(packing::compare_strategies(&container, &items, true)) {
+      *total += packing.efficiency();
+    }
+  }
+  for (strategy, total) in Strategy::ALL.iter().zip(totals) {
+    println!("{:?}: {:.1}% of the container used on average", strategy, total / rounds as f64 * 100.0);
   }
 
-  fn can_hold(&self, other: &Rectangle) -> bool {
-    self.width > other.width && self.height > other.height
+  // A quadtree answers overlap queries over many rectangles without looking at every one of them
+  let world = PlacedRectangle::new(0, 0, 1000, 1000).unwrap();
+  let mut tree = QuadTree::new(world);
+  let mut ids = Vec::new();
+  for _ in 0..2000 {
+    let rect = PlacedRectangle::new(rng.next_in(-20, 990), rng.next_in(-20, 990), rng.next_in(1, 30), rng.next_in(1, 30)).unwrap();
+    ids.push(tree.insert(rect));
   }
+  // remove every tenth rectangle again
+  for id in ids.iter().step_by(10) {
+    tree.remove(*id);
+  }
+
+  let region = PlacedRectangle::new(200, 300, 150, 100).unwrap();
+  let found = tree.query(&region);
+  println!("{} of {} rectangles overlap {:?}", found.len(), tree.len(), region);
+
+  let nearest = tree.nearest(500, 500);
+  println!("closest rectangle to (500, 500): {:?}", nearest.and_then(|id| tree.get(id)));
+
+  let collisions = tree.collisions();
+  println!("{} pairs of rectangles collide", collisions.len());
+  println!("(1000, 1000) is inside the world: {}", world.contains_point(1000, 1000));
 }
 
-fn main() {
-  let rect = Rectangle {
-    width: 4,
-    height: 5
-  };
+// Packs a few UI panels into a 10x8 screen and returns the packing together with a drawing of it.
+// The screen itself is drawn first so the panels end up on top of it.
+fn pack_panels() -> (Packing, Vec<Drawn>) {
+  let screen = Rectangle::new(10, 8).unwrap();
+  let panels: Vec<Rectangle> = [(6, 4), (4, 4), (3, 5), (7, 2), (2, 2), (5, 3)]
+    .iter()
+    .map(|&(w, h)| Rectangle::new(w, h).unwrap())
+    .collect();
+  let packing = packing::pack(&screen, &panels, Strategy::MaxRects, true);
 
-  println!("area of rectangle: {}", rect.area());
-  println!("can hold: {}", rect.can_hold(&Rectangle { width: 2, height: 1}))
+  let mut drawing = vec![Drawn::new(PlacedRectangle { x: 0, y: 0, size: screen }).with_fill("white").with_stroke("gray", 2.0)];
+  for p in &packing.placements {
+    drawing.push(Drawn::new(p.bounds()).with_label(&format!("panel {}", p.item)));
+  }
+  (packing, drawing)
+}
+
+// A tiny pseudo random number generator so the comparison above (and the quadtree tests) need no extra crate
+// and are repeatable
+struct XorShift(u64);
+
+impl XorShift {
+  fn next(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    self.0
+  }
+
+  // A number between `low` and `high` (both included)
+  fn next_in(&mut self, low: i32, high: i32) -> i32 {
+    low + (self.next() % (high - low + 1) as u64) as i32
+  }
 }
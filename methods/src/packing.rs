@@ -0,0 +1,398 @@
+// Packing many rectangles into one container rectangle (2D bin packing).
+// Finding the best packing is NP-hard, so the strategies below are the usual greedy heuristics. Each of them
+// keeps track of the free space left in the container and uses `Rectangle::can_hold_with` to find where an item fits.
+// Items are placed biggest first, which on average leaves less unusable space behind.
+
+use crate::rectangle::{HoldOptions, PlacedRectangle, Rectangle};
+
+// An item exactly as wide or as high as the free space still fits. Rotations are tried by the caller.
+const FITS: HoldOptions = HoldOptions { allow_rotation: false, allow_equal: true };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+  // Items are put next to each other in rows (shelves), a new shelf starts above the tallest item of the last one
+  Shelf,
+  // Every placement cuts the free rectangle it went into into two smaller free rectangles
+  Guillotine,
+  // Keeps every maximal free rectangle, even overlapping ones, and picks the one the item fits most snugly
+  MaxRects,
+}
+
+impl Strategy {
+  pub const ALL: [Strategy; 3] = [Strategy::Shelf, Strategy::Guillotine, Strategy::MaxRects];
+}
+
+// Where an item ended up. x and y are the bottom left corner of the item inside the container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+  // index of the item in the slice given to `pack`
+  pub item: usize,
+  pub x: i32,
+  pub y: i32,
+  // the item was turned by 90 degrees, `size` is already the turned size
+  pub rotated: bool,
+  pub size: Rectangle,
+}
+
+impl Placement {
+  // The area the item covers inside the container
+  pub fn bounds(&self) -> PlacedRectangle {
+    PlacedRectangle { x: self.x, y: self.y, size: self.size }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packing {
+  pub strategy: Strategy,
+  pub container: Rectangle,
+  pub placements: Vec<Placement>,
+  // indexes of the items that did not fit
+  pub unplaced: Vec<usize>,
+}
+
+impl Packing {
+  pub fn used_area(&self) -> u64 {
+    self.placements.iter().map(|p| p.size.area()).sum()
+  }
+
+  // Container area not covered by any item
+  pub fn wasted_area(&self) -> u64 {
+    self.container.area() - self.used_area()
+  }
+
+  // Share of the container covered by items, between 0 and 1
+  pub fn efficiency(&self) -> f64 {
+    if self.container.area() == 0 {
+      return 0.0;
+    }
+    self.used_area() as f64 / self.container.area() as f64
+  }
+}
+
+// A free area of the container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Slot {
+  x: i32,
+  y: i32,
+  width: i32,
+  height: i32,
+}
+
+impl Slot {
+  fn size(&self) -> Rectangle {
+    Rectangle::new(self.width, self.height).expect("slot sides are never negative")
+  }
+
+  fn right(&self) -> i32 {
+    self.x + self.width
+  }
+
+  fn top(&self) -> i32 {
+    self.y + self.height
+  }
+
+  fn overlaps(&self, other: &Slot) -> bool {
+    self.x < other.right() && other.x < self.right() && self.y < other.top() && other.y < self.top()
+  }
+
+  fn contains(&self, other: &Slot) -> bool {
+    self.x <= other.x && self.y <= other.y && self.right() >= other.right() && self.top() >= other.top()
+  }
+}
+
+pub fn pack(container: &Rectangle, items: &[Rectangle], strategy: Strategy, allow_rotation: bool) -> Packing {
+  let mut order: Vec<usize> = (0..items.len()).collect();
+  order.sort_by_key(|&idx| std::cmp::Reverse((items[idx].area(), items[idx].width().max(items[idx].height()))));
+
+  let mut packer: Box<dyn Packer> = match strategy {
+    Strategy::Shelf => Box::new(ShelfPacker { container: *container, shelves: Vec::new(), next_y: 0 }),
+    Strategy::Guillotine => Box::new(GuillotinePacker { free: vec![full_slot(container)] }),
+    Strategy::MaxRects => Box::new(MaxRectsPacker { free: vec![full_slot(container)] }),
+  };
+
+  let mut placements = Vec::new();
+  let mut unplaced = Vec::new();
+  for idx in order {
+    match packer.place(&orientations(&items[idx], allow_rotation)) {
+      Some((x, y, rotated, size)) => placements.push(Placement { item: idx, x, y, rotated, size }),
+      None => unplaced.push(idx),
+    }
+  }
+  unplaced.sort();
+
+  Packing { strategy, container: *container, placements, unplaced }
+}
+
+// Runs every strategy on the same input
+pub fn compare_strategies(container: &Rectangle, items: &[Rectangle], allow_rotation: bool) -> Vec<Packing> {
+  Strategy::ALL.iter().map(|strategy| pack(container, items, *strategy, allow_rotation)).collect()
+}
+
+fn full_slot(container: &Rectangle) -> Slot {
+  Slot { x: 0, y: 0, width: container.width(), height: container.height() }
+}
+
+// The ways an item can be put down, paired with whether it is rotated
+fn orientations(item: &Rectangle, allow_rotation: bool) -> Vec<(bool, Rectangle)> {
+  let mut result = vec![(false, *item)];
+  if allow_rotation && item.width() != item.height() {
+    result.push((true, item.rotated()));
+  }
+  result
+}
+
+// Each strategy only differs in how it picks a spot and updates its free space
+trait Packer {
+  // Returns where the item went as (x, y, rotated, size), None if it fits nowhere
+  fn place(&mut self, orientations: &[(bool, Rectangle)]) -> Option<(i32, i32, bool, Rectangle)>;
+}
+
+struct Shelf {
+  y: i32,
+  height: i32,
+  used_width: i32,
+}
+
+struct ShelfPacker {
+  container: Rectangle,
+  shelves: Vec<Shelf>,
+  // where the next shelf would start
+  next_y: i32,
+}
+
+impl Packer for ShelfPacker {
+  fn place(&mut self, orientations: &[(bool, Rectangle)]) -> Option<(i32, i32, bool, Rectangle)> {
+    // First try to fit into the room left at the end of an existing shelf
+    for shelf in self.shelves.iter_mut() {
+      let room = Rectangle::new(self.container.width() - shelf.used_width, shelf.height).unwrap();
+      if let Some((rotated, size)) = orientations.iter().find(|(_, size)| room.can_hold_with(size, FITS)) {
+        let x = shelf.used_width;
+        shelf.used_width += size.width();
+        return Some((x, shelf.y, *rotated, *size));
+      }
+    }
+
+    // Otherwise open a new shelf, as low as possible so the space above stays usable
+    let room = Rectangle::new(self.container.width(), self.container.height() - self.next_y).unwrap();
+    let (rotated, size) = orientations
+      .iter()
+      .filter(|(_, size)| room.can_hold_with(size, FITS))
+      .min_by_key(|(_, size)| size.height())?;
+    let y = self.next_y;
+    self.shelves.push(Shelf { y, height: size.height(), used_width: size.width() });
+    self.next_y += size.height();
+    Some((0, y, *rotated, *size))
+  }
+}
+
+struct GuillotinePacker {
+  free: Vec<Slot>,
+}
+
+impl Packer for GuillotinePacker {
+  fn place(&mut self, orientations: &[(bool, Rectangle)]) -> Option<(i32, i32, bool, Rectangle)> {
+    // Best area fit: the free slot with the least area left over once the item is in it
+    let mut best: Option<(u64, usize, bool, Rectangle)> = None;
+    for (idx, slot) in self.free.iter().enumerate() {
+      for (rotated, size) in orientations {
+        if slot.size().can_hold_with(size, FITS) {
+          let leftover = slot.size().area() - size.area();
+          if best.is_none_or(|(best_leftover, ..)| leftover < best_leftover) {
+            best = Some((leftover, idx, *rotated, *size));
+          }
+        }
+      }
+    }
+    let (_, idx, rotated, size) = best?;
+    let slot = self.free.swap_remove(idx);
+
+    // Cut the rest of the slot into the part right of the item and the part above it. The cut runs
+    // along the shorter leftover side, which keeps the bigger of the two parts as large as possible.
+    let (w, h) = (size.width(), size.height());
+    let (right_w, top_h) = (slot.width - w, slot.height - h);
+    let (right, top) = if right_w < top_h {
+      (
+        Slot { x: slot.x + w, y: slot.y, width: right_w, height: h },
+        Slot { x: slot.x, y: slot.y + h, width: slot.width, height: top_h },
+      )
+    } else {
+      (
+        Slot { x: slot.x + w, y: slot.y, width: right_w, height: slot.height },
+        Slot { x: slot.x, y: slot.y + h, width: w, height: top_h },
+      )
+    };
+    self.free.extend([right, top].into_iter().filter(|s| !s.size().is_empty()));
+
+    Some((slot.x, slot.y, rotated, size))
+  }
+}
+
+struct MaxRectsPacker {
+  free: Vec<Slot>,
+}
+
+impl Packer for MaxRectsPacker {
+  fn place(&mut self, orientations: &[(bool, Rectangle)]) -> Option<(i32, i32, bool, Rectangle)> {
+    // Best short side fit: the slot where the smaller gap left next to the item is smallest
+    let mut best: Option<((i32, i32), Slot, bool, Rectangle)> = None;
+    for slot in &self.free {
+      for (rotated, size) in orientations {
+        if slot.size().can_hold_with(size, FITS) {
+          let (gap_w, gap_h) = (slot.width - size.width(), slot.height - size.height());
+          let score = (gap_w.min(gap_h), gap_w.max(gap_h));
+          if best.is_none_or(|(best_score, ..)| score < best_score) {
+            best = Some((score, *slot, *rotated, *size));
+          }
+        }
+      }
+    }
+    let (_, slot, rotated, size) = best?;
+    let used = Slot { x: slot.x, y: slot.y, width: size.width(), height: size.height() };
+
+    // Every free slot overlapping the item is replaced by the (up to four) parts of it around the item
+    let mut free = Vec::with_capacity(self.free.len() + 4);
+    for slot in &self.free {
+      if !slot.overlaps(&used) {
+        free.push(*slot);
+        continue;
+      }
+      if used.x > slot.x {
+        free.push(Slot { width: used.x - slot.x, ..*slot });
+      }
+      if used.right() < slot.right() {
+        free.push(Slot { x: used.right(), width: slot.right() - used.right(), ..*slot });
+      }
+      if used.y > slot.y {
+        free.push(Slot { height: used.y - slot.y, ..*slot });
+      }
+      if used.top() < slot.top() {
+        free.push(Slot { y: used.top(), height: slot.top() - used.top(), ..*slot });
+      }
+    }
+
+    // Drop slots lying completely inside another one, they add nothing but work
+    let mut idx = 0;
+    while idx < free.len() {
+      // of two equal slots only the one further ahead survives
+      let contained = (0..free.len()).any(|other| {
+        other != idx && free[other].contains(&free[idx]) && (free[other] != free[idx] || other < idx)
+      });
+      if contained {
+        free.swap_remove(idx);
+      } else {
+        idx += 1;
+      }
+    }
+    self.free = free;
+
+    Some((used.x, used.y, rotated, size))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::XorShift;
+
+  fn rects(sizes: &[(i32, i32)]) -> Vec<Rectangle> {
+    sizes.iter().map(|&(w, h)| Rectangle::new(w, h).unwrap()).collect()
+  }
+
+  // Everything that has to hold for any packing, whatever the strategy
+  fn check(packing: &Packing, items: &[Rectangle], allow_rotation: bool) {
+    let container = PlacedRectangle { x: 0, y: 0, size: packing.container };
+    for p in &packing.placements {
+      assert!(container.contains(&p.bounds()), "{:?} is outside the container with {:?}", p, packing.strategy);
+      let item = items[p.item];
+      let expected = if p.rotated { item.rotated() } else { item };
+      assert_eq!(p.size, expected, "{:?}", p);
+      assert!(allow_rotation || !p.rotated);
+    }
+    for (i, a) in packing.placements.iter().enumerate() {
+      for b in &packing.placements[i + 1..] {
+        assert!(!a.bounds().overlaps(&b.bounds()), "{:?} and {:?} overlap with {:?}", a, b, packing.strategy);
+      }
+    }
+
+    // every item is either placed or unplaced, exactly once
+    let mut seen: Vec<usize> = packing.placements.iter().map(|p| p.item).chain(packing.unplaced.iter().copied()).collect();
+    seen.sort();
+    assert_eq!(seen, (0..items.len()).collect::<Vec<_>>(), "{:?}", packing.strategy);
+
+    let used: u64 = packing.placements.iter().map(|p| items[p.item].area()).sum();
+    assert_eq!(packing.used_area(), used);
+    assert_eq!(packing.wasted_area(), packing.container.area() - used);
+    assert!((0.0..=1.0).contains(&packing.efficiency()));
+  }
+
+  #[test]
+  fn exact_fit_leaves_nothing_wasted() {
+    let container = Rectangle::new(10, 10).unwrap();
+    let items = rects(&[(5, 5), (5, 5), (5, 5), (5, 5)]);
+    for packing in compare_strategies(&container, &items, false) {
+      check(&packing, &items, false);
+      assert!(packing.unplaced.is_empty(), "{:?}", packing.strategy);
+      assert_eq!(packing.wasted_area(), 0);
+      assert_eq!(packing.efficiency(), 1.0);
+    }
+  }
+
+  #[test]
+  fn rotation_is_only_used_when_allowed() {
+    let container = Rectangle::new(10, 4).unwrap();
+    let items = rects(&[(4, 10)]);
+    for strategy in Strategy::ALL {
+      let fixed = pack(&container, &items, strategy, false);
+      check(&fixed, &items, false);
+      assert_eq!(fixed.unplaced, vec![0]);
+      assert_eq!(fixed.wasted_area(), 40);
+
+      let turned = pack(&container, &items, strategy, true);
+      check(&turned, &items, true);
+      assert!(turned.placements[0].rotated);
+      assert_eq!(turned.wasted_area(), 0);
+    }
+  }
+
+  #[test]
+  fn items_that_do_not_fit_are_reported() {
+    let container = Rectangle::new(10, 8).unwrap();
+    let items = rects(&[(6, 4), (4, 4), (3, 5), (7, 2), (2, 2), (5, 3), (11, 1), (0, 3)]);
+    for packing in compare_strategies(&container, &items, true) {
+      check(&packing, &items, true);
+      assert!(packing.unplaced.contains(&6), "{:?}", packing.strategy);
+    }
+  }
+
+  #[test]
+  fn empty_input_and_empty_container() {
+    let container = Rectangle::new(10, 10).unwrap();
+    for packing in compare_strategies(&container, &[], true) {
+      assert!(packing.placements.is_empty() && packing.unplaced.is_empty());
+      assert_eq!(packing.wasted_area(), 100);
+    }
+    let empty = Rectangle::new(0, 0).unwrap();
+    let items = rects(&[(1, 1)]);
+    for packing in compare_strategies(&empty, &items, true) {
+      check(&packing, &items, true);
+      assert_eq!(packing.unplaced, vec![0]);
+      assert_eq!(packing.efficiency(), 0.0);
+    }
+  }
+
+  #[test]
+  fn random_inputs_stay_valid_for_every_strategy() {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    for round in 0..100 {
+      let container = Rectangle::new(rng.next_in(1, 120), rng.next_in(1, 120)).unwrap();
+      let items: Vec<Rectangle> =
+        (0..rng.next_in(0, 40)).map(|_| Rectangle::new(rng.next_in(1, 40), rng.next_in(1, 40)).unwrap()).collect();
+      let allow_rotation = round % 2 == 0;
+      let packings = compare_strategies(&container, &items, allow_rotation);
+      assert_eq!(packings.iter().map(|p| p.strategy).collect::<Vec<_>>(), Strategy::ALL);
+      for packing in &packings {
+        check(packing, &items, allow_rotation);
+      }
+    }
+  }
+}
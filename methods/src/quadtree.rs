@@ -0,0 +1,343 @@
+// A quadtree spatial index over PlacedRectangles.
+// Each node covers a region of the plane. Once a node holds too many rectangles it is split into four
+// quadrants and every rectangle that fits completely into one quadrant moves down into it. Rectangles
+// crossing a quadrant border stay in the node. Queries then only have to visit the nodes near the region
+// they ask about instead of looking at every rectangle.
+
+use std::collections::HashMap;
+
+use crate::rectangle::PlacedRectangle;
+
+// Rectangles are identified by the id `insert` hands out
+pub type ItemId = usize;
+
+const NODE_CAPACITY: usize = 8;
+const MAX_DEPTH: usize = 10;
+
+struct Node {
+  bounds: PlacedRectangle,
+  depth: usize,
+  items: Vec<(ItemId, PlacedRectangle)>,
+  children: Option<Box<[Node; 4]>>,
+}
+
+pub struct QuadTree {
+  root: Node,
+  // every item in the tree, so `remove` and `get` can find an item by its id
+  items: HashMap<ItemId, PlacedRectangle>,
+  next_id: ItemId,
+}
+
+impl Node {
+  fn new(bounds: PlacedRectangle, depth: usize) -> Node {
+    Node { bounds, depth, items: Vec::new(), children: None }
+  }
+
+  // The quadrant `rect` fits into completely, if any
+  fn child_for(&mut self, rect: &PlacedRectangle) -> Option<&mut Node> {
+    self.children.as_mut()?.iter_mut().find(|child| child.bounds.contains(rect))
+  }
+
+  fn insert(&mut self, id: ItemId, rect: PlacedRectangle) {
+    if let Some(child) = self.child_for(&rect) {
+      return child.insert(id, rect);
+    }
+    self.items.push((id, rect));
+    if self.children.is_none() && self.items.len() > NODE_CAPACITY && self.depth < MAX_DEPTH {
+      self.split();
+    }
+  }
+
+  fn split(&mut self) {
+    let (x, y) = (self.bounds.x, self.bounds.y);
+    let (w, h) = (self.bounds.size.width(), self.bounds.size.height());
+    // a node too small to be halved any further stays a leaf
+    if w < 2 || h < 2 {
+      return;
+    }
+    let (half_w, half_h) = (w / 2, h / 2);
+    let quadrant = |qx: i32, qy: i32, qw: i32, qh: i32| {
+      Node::new(PlacedRectangle::new(qx, qy, qw, qh).expect("quadrant sides are positive"), self.depth + 1)
+    };
+    self.children = Some(Box::new([
+      quadrant(x, y, half_w, half_h),
+      quadrant(x + half_w, y, w - half_w, half_h),
+      quadrant(x, y + half_h, half_w, h - half_h),
+      quadrant(x + half_w, y + half_h, w - half_w, h - half_h),
+    ]));
+
+    // move down everything that now fits into a quadrant
+    for (id, rect) in std::mem::take(&mut self.items) {
+      self.insert(id, rect);
+    }
+  }
+
+  // Follows the same path as `insert` did to find the node holding the item
+  fn remove(&mut self, id: ItemId, rect: &PlacedRectangle) -> bool {
+    if let Some(child) = self.child_for(rect) {
+      return child.remove(id, rect);
+    }
+    let before = self.items.len();
+    self.items.retain(|(item_id, _)| *item_id != id);
+    self.items.len() != before
+  }
+
+  fn children(&self) -> &[Node] {
+    match &self.children {
+      Some(children) => children.as_slice(),
+      None => &[],
+    }
+  }
+
+  // Items outside the root bounds are kept in the root, so the root is always visited
+  fn query(&self, region: &PlacedRectangle, is_root: bool, found: &mut Vec<ItemId>) {
+    if !is_root && !self.bounds.overlaps(region) {
+      return;
+    }
+    found.extend(self.items.iter().filter(|(_, rect)| rect.overlaps(region)).map(|(id, _)| *id));
+    for child in self.children() {
+      child.query(region, false, found);
+    }
+  }
+
+  // Branch and bound: visit the closest quadrants first and skip any quadrant further away than the best item so far
+  fn nearest(&self, x: i32, y: i32, best: &mut Option<(u128, ItemId)>) {
+    for (id, rect) in &self.items {
+      let candidate = (rect.distance_squared_to(x, y), *id);
+      if best.is_none_or(|b| candidate < b) {
+        *best = Some(candidate);
+      }
+    }
+    let mut children: Vec<&Node> = self.children().iter().collect();
+    children.sort_by_key(|child| child.bounds.distance_squared_to(x, y));
+    for child in children {
+      let distance = child.bounds.distance_squared_to(x, y);
+      if best.is_none_or(|(best_distance, _)| distance <= best_distance) {
+        child.nearest(x, y, best);
+      }
+    }
+  }
+
+  // Everything stored in this node and below it
+  fn collect(&self, out: &mut Vec<(ItemId, PlacedRectangle)>) {
+    out.extend(self.items.iter().copied());
+    for child in self.children() {
+      child.collect(out);
+    }
+  }
+
+  // An item can only collide with items in the same node, in nodes above it or in nodes below it.
+  // So each node checks its own items against each other and against everything below it.
+  fn collisions(&self, pairs: &mut Vec<(ItemId, ItemId)>) {
+    let mut below = Vec::new();
+    for child in self.children() {
+      child.collect(&mut below);
+    }
+    for (i, (id_a, a)) in self.items.iter().enumerate() {
+      let others = self.items[i + 1..].iter().chain(below.iter());
+      for (id_b, b) in others {
+        if a.overlaps(b) {
+          pairs.push((*id_a.min(id_b), *id_a.max(id_b)));
+        }
+      }
+    }
+    for child in self.children() {
+      child.collisions(pairs);
+    }
+  }
+}
+
+impl QuadTree {
+  // `bounds` should cover the area the rectangles live in. Rectangles outside of it still work, but
+  // they all end up in the root node where they are checked on every query.
+  pub fn new(bounds: PlacedRectangle) -> QuadTree {
+    QuadTree { root: Node::new(bounds, 0), items: HashMap::new(), next_id: 0 }
+  }
+
+  pub fn len(&self) -> usize {
+    self.items.len()
+  }
+
+  pub fn get(&self, id: ItemId) -> Option<&PlacedRectangle> {
+    self.items.get(&id)
+  }
+
+  pub fn insert(&mut self, rect: PlacedRectangle) -> ItemId {
+    let id = self.next_id;
+    self.next_id += 1;
+    self.items.insert(id, rect);
+    self.root.insert(id, rect);
+    id
+  }
+
+  pub fn remove(&mut self, id: ItemId) -> Option<PlacedRectangle> {
+    let rect = self.items.remove(&id)?;
+    let removed = self.root.remove(id, &rect);
+    debug_assert!(removed, "item {id} was not found where it was inserted");
+    Some(rect)
+  }
+
+  // Ids of all rectangles sharing some area with `region`, in increasing order
+  pub fn query(&self, region: &PlacedRectangle) -> Vec<ItemId> {
+    let mut found = Vec::new();
+    self.root.query(region, true, &mut found);
+    found.sort();
+    found
+  }
+
+  // The rectangle closest to the point (a rectangle containing it has distance 0).
+  // When several are equally close the one with the smallest id wins.
+  pub fn nearest(&self, x: i32, y: i32) -> Option<ItemId> {
+    let mut best = None;
+    self.root.nearest(x, y, &mut best);
+    best.map(|(_, id)| id)
+  }
+
+  // Every pair of overlapping rectangles as (smaller id, bigger id), sorted
+  pub fn collisions(&self) -> Vec<(ItemId, ItemId)> {
+    let mut pairs = Vec::new();
+    self.root.collisions(&mut pairs);
+    pairs.sort();
+    pairs
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::XorShift;
+
+  fn rect(x: i32, y: i32, width: i32, height: i32) -> PlacedRectangle {
+    PlacedRectangle::new(x, y, width, height).unwrap()
+  }
+
+  // Compares every answer of the tree with a plain scan over `all`, the rectangles that should be in it
+  fn check_against_scan(tree: &QuadTree, all: &[(ItemId, PlacedRectangle)], rng: &mut XorShift) {
+    assert_eq!(tree.len(), all.len());
+    for &(id, r) in all {
+      assert_eq!(tree.get(id), Some(&r));
+    }
+
+    for _ in 0..50 {
+      let region = rect(rng.next_in(-100, 1000), rng.next_in(-100, 1000), rng.next_in(0, 300), rng.next_in(0, 300));
+      let expected: Vec<ItemId> = all.iter().filter(|(_, r)| r.overlaps(&region)).map(|(id, _)| *id).collect();
+      assert_eq!(tree.query(&region), expected, "query {region:?}");
+    }
+
+    for _ in 0..50 {
+      let (x, y) = (rng.next_in(-200, 1200), rng.next_in(-200, 1200));
+      let expected = all.iter().map(|(id, r)| (r.distance_squared_to(x, y), *id)).min().map(|(_, id)| id);
+      assert_eq!(tree.nearest(x, y), expected, "nearest to ({x}, {y})");
+    }
+
+    let mut expected = Vec::new();
+    for (i, (id_a, a)) in all.iter().enumerate() {
+      for (id_b, b) in &all[i + 1..] {
+        if a.overlaps(b) {
+          expected.push((*id_a.min(id_b), *id_a.max(id_b)));
+        }
+      }
+    }
+    expected.sort();
+    assert_eq!(tree.collisions(), expected);
+  }
+
+  #[test]
+  fn matches_a_plain_scan() {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let mut tree = QuadTree::new(rect(0, 0, 1000, 1000));
+    let mut all = Vec::new();
+    for _ in 0..2000 {
+      let r = rect(rng.next_in(-20, 990), rng.next_in(-20, 990), rng.next_in(1, 30), rng.next_in(1, 30));
+      all.push((tree.insert(r), r));
+    }
+    check_against_scan(&tree, &all, &mut rng);
+  }
+
+  #[test]
+  fn remove_takes_items_out_of_every_answer() {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    let mut tree = QuadTree::new(rect(0, 0, 1000, 1000));
+    let mut all = Vec::new();
+    for _ in 0..1000 {
+      let r = rect(rng.next_in(0, 980), rng.next_in(0, 980), rng.next_in(1, 20), rng.next_in(1, 20));
+      all.push((tree.insert(r), r));
+    }
+    for (id, r) in all.iter().step_by(3) {
+      assert_eq!(tree.remove(*id), Some(*r));
+      assert_eq!(tree.remove(*id), None);
+      assert_eq!(tree.get(*id), None);
+    }
+    all = all.into_iter().enumerate().filter(|(idx, _)| idx % 3 != 0).map(|(_, item)| item).collect();
+    check_against_scan(&tree, &all, &mut rng);
+
+    // ids are not handed out again after a remove
+    let r = rect(1, 1, 1, 1);
+    let id = tree.insert(r);
+    assert!(all.iter().all(|(other, _)| *other != id));
+    all.push((id, r));
+    check_against_scan(&tree, &all, &mut rng);
+
+    for (id, _) in all.drain(..) {
+      assert!(tree.remove(id).is_some());
+    }
+    check_against_scan(&tree, &all, &mut rng);
+    assert_eq!(tree.nearest(0, 0), None);
+  }
+
+  #[test]
+  fn items_outside_the_root_bounds() {
+    let mut rng = XorShift(0x1234_5678_9abc_def1);
+    let mut tree = QuadTree::new(rect(0, 0, 100, 100));
+    let mut all = Vec::new();
+    // enough items inside for the root to split, plus some far outside and some crossing the border
+    for _ in 0..200 {
+      let r = rect(rng.next_in(0, 95), rng.next_in(0, 95), rng.next_in(1, 5), rng.next_in(1, 5));
+      all.push((tree.insert(r), r));
+    }
+    for r in [rect(-500, -500, 10, 10), rect(900, 900, 50, 50), rect(-10, 40, 20, 20), rect(95, 95, 100, 100), rect(-1000, 200, 3000, 5)] {
+      all.push((tree.insert(r), r));
+    }
+    check_against_scan(&tree, &all, &mut rng);
+
+    assert_eq!(tree.query(&rect(-510, -510, 5, 5)), Vec::<ItemId>::new());
+    assert!(tree.query(&rect(-505, -505, 10, 10)).contains(&200));
+    assert_eq!(tree.nearest(1000, 1000), Some(201));
+    assert_eq!(tree.remove(200), Some(rect(-500, -500, 10, 10)));
+    all.retain(|(id, _)| *id != 200);
+    check_against_scan(&tree, &all, &mut rng);
+  }
+
+  #[test]
+  fn items_on_a_split_line() {
+    // the root splits at x = 50 and y = 50
+    let mut tree = QuadTree::new(rect(0, 0, 100, 100));
+    let mut all = Vec::new();
+    let mut add = |tree: &mut QuadTree, r: PlacedRectangle| all.push((tree.insert(r), r));
+    for i in 0..10 {
+      add(&mut tree, rect(i * 4, i * 4, 2, 2));
+    }
+    // ending exactly at the split line fits the lower quadrant, starting on it fits the upper one
+    add(&mut tree, rect(40, 40, 10, 10));
+    add(&mut tree, rect(50, 50, 10, 10));
+    // crossing the vertical, the horizontal and both lines
+    add(&mut tree, rect(45, 10, 10, 10));
+    add(&mut tree, rect(10, 45, 10, 10));
+    add(&mut tree, rect(49, 49, 2, 2));
+    // no width: a segment lying on the split line, it still crosses the rectangles that straddle the line
+    add(&mut tree, rect(50, 0, 0, 100));
+
+    let mut rng = XorShift(0xdead_beef_cafe_f00d);
+    check_against_scan(&tree, &all, &mut rng);
+
+    // touching at the split line is not overlapping
+    assert_eq!(tree.query(&rect(50, 40, 10, 10)), vec![14]);
+    assert_eq!(tree.collisions(), vec![(10, 14), (11, 14), (12, 15), (14, 15)]);
+    assert_eq!(tree.nearest(50, 50), Some(10));
+    for id in [10, 11, 12, 13, 14, 15] {
+      assert!(tree.remove(id).is_some());
+    }
+    all.truncate(10);
+    check_against_scan(&tree, &all, &mut rng);
+  }
+}
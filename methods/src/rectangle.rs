@@ -0,0 +1,244 @@
+// Methods are functions defined inside an `impl` block, their first parameter is always `self`
+// (or `&self` / `&mut self`) which is the instance the method is called on.
+// Functions inside `impl` without `self` are associated functions, like `Rectangle::new`.
+
+// A Rectangle only has a size, no position. Where a method needs coordinates (contains_point) the rectangle
+// is taken to have its bottom left corner at the origin (0, 0). PlacedRectangle below has a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rectangle {
+  width: i32,
+  height: i32,
+}
+
+// Options for `Rectangle::can_hold_with`. The default (both false) is the strict check `can_hold` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HoldOptions {
+  // the other rectangle may be turned by 90 degrees (width and height swapped) to fit
+  pub allow_rotation: bool,
+  // a side exactly as long as ours still fits
+  pub allow_equal: bool,
+}
+
+impl Rectangle {
+  // The fields are private so every Rectangle has to go through here, which rules out negative sides
+  pub fn new(width: i32, height: i32) -> Result<Rectangle, String> {
+    if width < 0 || height < 0 {
+      return Err(format!("rectangle sides must not be negative, got {width}x{height}"));
+    }
+    Ok(Rectangle { width, height })
+  }
+
+  pub fn square(side: i32) -> Result<Rectangle, String> {
+    Rectangle::new(side, side)
+  }
+
+  // Getters, methods can have the same name as a field
+  pub fn width(&self) -> i32 {
+    self.width
+  }
+
+  pub fn height(&self) -> i32 {
+    self.height
+  }
+
+  // Both sides are at most i32::MAX so their product always fits into a u64
+  pub fn area(&self) -> u64 {
+    self.width as u64 * self.height as u64
+  }
+
+  // The area as an i32, None if it would overflow
+  pub fn checked_area(&self) -> Option<i32> {
+    self.width.checked_mul(self.height)
+  }
+
+  pub fn perimeter(&self) -> u64 {
+    2 * (self.width as u64 + self.height as u64)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.width == 0 || self.height == 0
+  }
+
+  // Same rectangle turned by 90 degrees
+  pub fn rotated(&self) -> Rectangle {
+    Rectangle { width: self.height, height: self.width }
+  }
+
+  // Edges count as inside
+  pub fn contains_point(&self, x: i32, y: i32) -> bool {
+    (0..=self.width).contains(&x) && (0..=self.height).contains(&y)
+  }
+
+  // The largest size that fits into both rectangles (min(width) x min(height)), None if it has no area.
+  // This only compares sizes, see PlacedRectangle::overlaps for rectangles with a position.
+  pub fn common_size(&self, other: &Rectangle) -> Option<Rectangle> {
+    let overlap = Rectangle {
+      width: self.width.min(other.width),
+      height: self.height.min(other.height),
+    };
+    if overlap.is_empty() {
+      None
+    } else {
+      Some(overlap)
+    }
+  }
+
+  // The smallest size that either rectangle fits into, max(width) x max(height)
+  pub fn covering_size(&self, other: &Rectangle) -> Rectangle {
+    Rectangle {
+      width: self.width.max(other.width),
+      height: self.height.max(other.height),
+    }
+  }
+
+  // Whether `other` fits strictly inside this rectangle, a side of the same length doesn't fit
+  pub fn can_hold(&self, other: &Rectangle) -> bool {
+    self.can_hold_with(other, HoldOptions::default())
+  }
+
+  pub fn can_hold_with(&self, other: &Rectangle, options: HoldOptions) -> bool {
+    let fits = |outer: i32, inner: i32| if options.allow_equal { outer >= inner } else { outer > inner };
+    let fits_in = |inner: Rectangle| fits(self.width, inner.width) && fits(self.height, inner.height);
+    fits_in(*other) || (options.allow_rotation && fits_in(other.rotated()))
+  }
+}
+
+// A Rectangle together with the position of its bottom left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlacedRectangle {
+  pub x: i32,
+  pub y: i32,
+  pub size: Rectangle,
+}
+
+impl PlacedRectangle {
+  pub fn new(x: i32, y: i32, width: i32, height: i32) -> Result<PlacedRectangle, String> {
+    Ok(PlacedRectangle { x, y, size: Rectangle::new(width, height)? })
+  }
+
+  // The edges are returned as i64 since x + width does not always fit into an i32
+  pub fn left(&self) -> i64 {
+    self.x as i64
+  }
+
+  pub fn right(&self) -> i64 {
+    self.x as i64 + self.size.width() as i64
+  }
+
+  pub fn bottom(&self) -> i64 {
+    self.y as i64
+  }
+
+  pub fn top(&self) -> i64 {
+    self.y as i64 + self.size.height() as i64
+  }
+
+  // Whether the two rectangles share some area, rectangles only touching at an edge do not overlap
+  pub fn overlaps(&self, other: &PlacedRectangle) -> bool {
+    self.left() < other.right() && other.left() < self.right() && self.bottom() < other.top() && other.bottom() < self.top()
+  }
+
+  // Whether `other` lies completely inside this rectangle
+  pub fn contains(&self, other: &PlacedRectangle) -> bool {
+    self.left() <= other.left() && other.right() <= self.right() && self.bottom() <= other.bottom() && other.top() <= self.top()
+  }
+
+  // Edges count as inside
+  pub fn contains_point(&self, x: i32, y: i32) -> bool {
+    (self.left()..=self.right()).contains(&(x as i64)) && (self.bottom()..=self.top()).contains(&(y as i64))
+  }
+
+  // Squared distance from the point to the closest point of the rectangle, 0 if the point is inside.
+  // Squared so it stays an exact integer, which is all we need to compare distances.
+  pub fn distance_squared_to(&self, x: i32, y: i32) -> u128 {
+    let gap = |value: i64, low: i64, high: i64| (low - value).max(value - high).max(0) as u128;
+    let (dx, dy) = (gap(x as i64, self.left(), self.right()), gap(y as i64, self.bottom(), self.top()));
+    dx * dx + dy * dy
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rect(width: i32, height: i32) -> Rectangle {
+    Rectangle::new(width, height).unwrap()
+  }
+
+  #[test]
+  fn negative_sides_are_rejected() {
+    assert!(Rectangle::new(-1, 5).is_err());
+    assert!(Rectangle::new(5, -1).is_err());
+    assert!(Rectangle::square(-3).is_err());
+    assert!(PlacedRectangle::new(-10, -10, -1, 1).is_err());
+    assert_eq!(Rectangle::square(4).unwrap(), rect(4, 4));
+    assert!(rect(0, 7).is_empty());
+  }
+
+  #[test]
+  fn area_and_perimeter_do_not_overflow() {
+    assert_eq!(rect(3, 4).area(), 12);
+    assert_eq!(rect(3, 4).perimeter(), 14);
+    assert_eq!(rect(3, 4).checked_area(), Some(12));
+    let huge = rect(i32::MAX, i32::MAX);
+    assert_eq!(huge.area(), i32::MAX as u64 * i32::MAX as u64);
+    assert_eq!(huge.perimeter(), 4 * i32::MAX as u64);
+    assert_eq!(huge.checked_area(), None);
+  }
+
+  #[test]
+  fn common_and_covering_size() {
+    let (tall, wide) = (rect(2, 8), rect(6, 3));
+    assert_eq!(tall.common_size(&wide), Some(rect(2, 3)));
+    assert_eq!(tall.covering_size(&wide), rect(6, 8));
+    assert_eq!(wide.common_size(&tall), tall.common_size(&wide));
+    assert_eq!(tall.common_size(&rect(0, 5)), None);
+    assert!(tall.covering_size(&wide).can_hold_with(&tall, HoldOptions { allow_rotation: false, allow_equal: true }));
+  }
+
+  #[test]
+  fn can_hold_options() {
+    let outer = rect(10, 5);
+    assert!(outer.can_hold(&rect(9, 4)));
+    assert!(!outer.can_hold(&rect(10, 4)));
+    assert!(!outer.can_hold(&rect(4, 9)));
+    let equal = HoldOptions { allow_equal: true, ..HoldOptions::default() };
+    assert!(outer.can_hold_with(&rect(10, 5), equal));
+    assert!(!outer.can_hold_with(&rect(5, 10), equal));
+    let rotate = HoldOptions { allow_rotation: true, ..HoldOptions::default() };
+    assert!(outer.can_hold_with(&rect(4, 9), rotate));
+    assert!(!outer.can_hold_with(&rect(5, 10), rotate));
+    assert!(outer.can_hold_with(&rect(5, 10), HoldOptions { allow_rotation: true, allow_equal: true }));
+  }
+
+  #[test]
+  fn points_on_the_edges_are_contained() {
+    let r = rect(4, 3);
+    assert!(r.contains_point(0, 0));
+    assert!(r.contains_point(4, 3));
+    assert!(r.contains_point(2, 3));
+    assert!(!r.contains_point(5, 1));
+    assert!(!r.contains_point(-1, 1));
+    assert_eq!(r.rotated(), rect(3, 4));
+  }
+
+  #[test]
+  fn placed_rectangles() {
+    let a = PlacedRectangle::new(0, 0, 4, 4).unwrap();
+    let b = PlacedRectangle::new(2, 2, 4, 4).unwrap();
+    let touching = PlacedRectangle::new(4, 0, 2, 2).unwrap();
+    let inner = PlacedRectangle::new(1, 1, 2, 2).unwrap();
+    assert!(a.overlaps(&b) && b.overlaps(&a));
+    assert!(!a.overlaps(&touching));
+    assert!(a.contains(&inner) && !inner.contains(&a));
+    assert!(!a.contains(&b));
+    assert!(a.contains_point(4, 4) && !a.contains_point(5, 4));
+    assert_eq!(a.distance_squared_to(2, 2), 0);
+    assert_eq!(a.distance_squared_to(7, 8), 9 + 16);
+    // the far edge is past i32::MAX
+    let far = PlacedRectangle::new(i32::MAX, i32::MAX, i32::MAX, 1).unwrap();
+    assert_eq!(far.right(), 2 * i32::MAX as i64);
+    assert!(far.contains_point(i32::MAX, i32::MAX));
+    assert_eq!(far.distance_squared_to(i32::MIN, i32::MAX), (u32::MAX as u128) * (u32::MAX as u128));
+  }
+}
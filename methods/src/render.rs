@@ -0,0 +1,219 @@
+// Drawing rectangles so a layout can be looked at instead of read as numbers.
+// `to_svg` produces an SVG document (open it in a browser), `to_ascii` draws into a grid of characters for
+// the terminal. Both scale the drawing so that all rectangles fit the requested size.
+// Our y axis points up like in maths, while SVG and the terminal count rows downwards, so y is flipped.
+
+use crate::rectangle::PlacedRectangle;
+
+// Fill colours used for rectangles that don't set their own
+const PALETTE: [&str; 6] = ["#8ecae6", "#ffb703", "#90be6d", "#f28482", "#cdb4db", "#f6bd60"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drawn {
+  pub rect: PlacedRectangle,
+  pub label: Option<String>,
+  // any SVG colour, e.g. "steelblue" or "#ff0000"
+  pub fill: Option<String>,
+  pub stroke: String,
+  pub stroke_width: f64,
+}
+
+impl Drawn {
+  pub fn new(rect: PlacedRectangle) -> Drawn {
+    Drawn { rect, label: None, fill: None, stroke: String::from("black"), stroke_width: 1.0 }
+  }
+
+  // The with_* methods take `self` by value and give it back, so they can be chained:
+  // Drawn::new(rect).with_label("menu").with_fill("gold")
+  pub fn with_label(self, label: &str) -> Drawn {
+    Drawn { label: Some(String::from(label)), ..self }
+  }
+
+  pub fn with_fill(self, fill: &str) -> Drawn {
+    Drawn { fill: Some(String::from(fill)), ..self }
+  }
+
+  pub fn with_stroke(self, stroke: &str, stroke_width: f64) -> Drawn {
+    Drawn { stroke: String::from(stroke), stroke_width, ..self }
+  }
+}
+
+// The area covered by all the rectangles as (left, bottom, right, top)
+fn bounds(items: &[Drawn]) -> Option<(i64, i64, i64, i64)> {
+  let first = items.first()?.rect;
+  let init = (first.left(), first.bottom(), first.right(), first.top());
+  Some(items.iter().fold(init, |(l, b, r, t), item| {
+    (l.min(item.rect.left()), b.min(item.rect.bottom()), r.max(item.rect.right()), t.max(item.rect.top()))
+  }))
+}
+
+fn escape_xml(text: &str) -> String {
+  text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// An SVG document of `width` x `height` pixels showing all items
+pub fn to_svg(items: &[Drawn], width: u32, height: u32) -> String {
+  let mut svg = format!(
+    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n"
+  );
+  svg.push_str(&format!("  <rect width=\"{width}\" height=\"{height}\" fill=\"white\"/>\n"));
+
+  if let Some((left, bottom, right, top)) = bounds(items) {
+    let margin = 10.0;
+    let (drawable_w, drawable_h) = ((width as f64 - 2.0 * margin).max(1.0), (height as f64 - 2.0 * margin).max(1.0));
+    // the same scale on both axes so squares stay square, an empty layout gets scale 1
+    let span_w = (right - left).max(1) as f64;
+    let span_h = (top - bottom).max(1) as f64;
+    let scale = (drawable_w / span_w).min(drawable_h / span_h);
+
+    for (idx, item) in items.iter().enumerate() {
+      let x = margin + (item.rect.left() - left) as f64 * scale;
+      let y = margin + (top - item.rect.top()) as f64 * scale;
+      let w = item.rect.size.width() as f64 * scale;
+      let h = item.rect.size.height() as f64 * scale;
+      let fill = item.fill.clone().unwrap_or_else(|| String::from(PALETTE[idx % PALETTE.len()]));
+      svg.push_str(&format!(
+        "  <rect x=\"{x:.2}\" y=\"{y:.2}\" width=\"{w:.2}\" height=\"{h:.2}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+        escape_xml(&fill),
+        escape_xml(&item.stroke),
+        item.stroke_width
+      ));
+      if let Some(label) = &item.label {
+        let font_size = (h / 3.0).clamp(6.0, 16.0);
+        svg.push_str(&format!(
+          "  <text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{font_size:.1}\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"middle\">{}</text>\n",
+          x + w / 2.0,
+          y + h / 2.0,
+          escape_xml(label)
+        ));
+      }
+    }
+  }
+
+  svg.push_str("</svg>\n");
+  svg
+}
+
+// Draws the outlines of all items into at most `columns` x `rows` characters. Later items are drawn over
+// earlier ones. A character cell is about twice as tall as it is wide, so one row covers twice the distance
+// of one column, which keeps the proportions of the rectangles close to the real ones.
+pub fn to_ascii(items: &[Drawn], columns: usize, rows: usize) -> String {
+  let Some((left, bottom, right, top)) = bounds(items) else {
+    return String::new();
+  };
+  if columns < 2 || rows < 2 {
+    return String::new();
+  }
+
+  // world units per column, rows cover twice as much
+  let unit = ((right - left) as f64 / (columns - 1) as f64).max((top - bottom) as f64 / (2 * (rows - 1)) as f64);
+  let unit = if unit > 0.0 { unit } else { 1.0 };
+  let to_col = |x: i64| ((x - left) as f64 / unit).round() as usize;
+  let to_row = |y: i64| ((top - y) as f64 / (2.0 * unit)).round() as usize;
+
+  let used_columns = to_col(right) + 1;
+  let used_rows = to_row(bottom) + 1;
+  let mut grid = vec![vec![' '; used_columns]; used_rows];
+
+  for item in items {
+    let (c0, c1) = (to_col(item.rect.left()), to_col(item.rect.right()));
+    let (r0, r1) = (to_row(item.rect.top()), to_row(item.rect.bottom()));
+    for (r, row) in grid.iter_mut().enumerate().take(r1 + 1).skip(r0) {
+      for (c, cell) in row.iter_mut().enumerate().take(c1 + 1).skip(c0) {
+        let on_vertical_edge = c == c0 || c == c1;
+        let on_horizontal_edge = r == r0 || r == r1;
+        *cell = match (on_horizontal_edge, on_vertical_edge) {
+          (true, true) => '+',
+          (true, false) => '-',
+          (false, true) => '|',
+          (false, false) => ' ',
+        };
+      }
+    }
+    // the label goes on the first line inside the box, cut off if the box is too narrow
+    if let Some(label) = &item.label {
+      if r1 > r0 + 1 && c1 > c0 + 1 {
+        let room = c1 - c0 - 1;
+        for (offset, ch) in label.chars().take(room).enumerate() {
+          grid[r0 + 1][c0 + 1 + offset] = ch;
+        }
+      }
+    }
+  }
+
+  let lines: Vec<String> = grid.iter().map(|row| row.iter().collect::<String>().trim_end().to_string()).collect();
+  lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::rectangle::Rectangle;
+
+  fn placed(x: i32, y: i32, width: i32, height: i32) -> Drawn {
+    Drawn::new(PlacedRectangle::new(x, y, width, height).unwrap())
+  }
+
+  // The value of `name="..."` in an SVG element
+  fn attribute<'a>(element: &'a str, name: &str) -> &'a str {
+    let start = element.find(&format!(" {name}=\"")).unwrap_or_else(|| panic!("no {name} in {element}")) + name.len() + 3;
+    let end = start + element[start..].find('"').unwrap();
+    &element[start..end]
+  }
+
+  fn rects(svg: &str) -> Vec<&str> {
+    svg.lines().map(str::trim).filter(|line| line.starts_with("<rect")).collect()
+  }
+
+  #[test]
+  fn svg_has_one_rect_per_item_after_the_background() {
+    let items = vec![placed(0, 0, 10, 8), placed(0, 0, 6, 4), placed(6, 0, 4, 4).with_label("b"), placed(0, 4, 10, 4)];
+    let svg = to_svg(&items, 400, 320);
+    assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+    let found = rects(&svg);
+    // the first one is the white background
+    assert_eq!(found.len(), items.len() + 1);
+    assert_eq!(attribute(found[0], "width"), "400");
+    assert_eq!(attribute(found[0], "height"), "320");
+    assert_eq!(svg.matches("<text").count(), 1);
+
+    assert_eq!(rects(&to_svg(&[], 400, 320)).len(), 1);
+  }
+
+  #[test]
+  fn view_box_matches_the_requested_size_and_the_container_fills_it() {
+    let container = PlacedRectangle { x: 0, y: 0, size: Rectangle::new(10, 8).unwrap() };
+    let items = vec![Drawn::new(container), placed(0, 4, 6, 4)];
+    let svg = to_svg(&items, 400, 320);
+    let header = svg.lines().next().unwrap();
+    assert_eq!(attribute(header, "viewBox"), "0 0 400 320");
+    assert_eq!(attribute(header, "width"), "400");
+    assert_eq!(attribute(header, "height"), "320");
+
+    // 10 pixel margin, 380x300 left over, the height is what limits the scale: 300 / 8 = 37.5
+    let found = rects(&svg);
+    let outer = found[1];
+    assert_eq!((attribute(outer, "x"), attribute(outer, "y")), ("10.00", "10.00"));
+    assert_eq!((attribute(outer, "width"), attribute(outer, "height")), ("375.00", "300.00"));
+    // y is flipped, the panel at the top left of the container is drawn at the top of the image
+    let panel = found[2];
+    assert_eq!((attribute(panel, "x"), attribute(panel, "y")), ("10.00", "10.00"));
+    assert_eq!((attribute(panel, "width"), attribute(panel, "height")), ("225.00", "150.00"));
+  }
+
+  #[test]
+  fn svg_escapes_labels_and_colours() {
+    let svg = to_svg(&[placed(0, 0, 2, 2).with_label("<a & \"b\">").with_fill("\"red")], 100, 100);
+    assert!(svg.contains("&lt;a &amp; &quot;b&quot;&gt;"));
+    assert!(svg.contains("fill=\"&quot;red\""));
+    assert!(!svg.contains("<a "));
+  }
+
+  #[test]
+  fn ascii_draws_outlines_and_labels() {
+    let drawing = to_ascii(&[placed(0, 0, 10, 4).with_label("box")], 11, 3);
+    assert_eq!(drawing, "+---------+\n|box      |\n+---------+\n");
+    assert_eq!(to_ascii(&[], 10, 10), "");
+    assert_eq!(to_ascii(&[placed(0, 0, 1, 1)], 1, 10), "");
+  }
+}
@@ -0,0 +1,149 @@
+// A minimal CSV reader / writer for `Record`s.
+// The first row is a header naming the fields, every following row is one record:
+//   name,email,age
+//   Joey,joey@tribbiani.com,27
+// Cells containing commas, quotes or line breaks are wrapped in double quotes, a quote inside is written twice.
+
+use crate::record::{ErrorKind, FieldKind, Record, SchemaError, Value};
+
+pub fn to_csv<R: Record>(records: &[R]) -> String {
+  let header: Vec<String> = R::FIELDS.iter().map(|(name, _)| quote(name)).collect();
+  let mut out = header.join(",");
+  out.push('\n');
+  for record in records {
+    let cells: Vec<String> = record
+      .to_values()
+      .into_iter()
+      .map(|value| match value {
+        Value::Text(s) => quote(&s),
+        Value::Integer(n) => n.to_string(),
+      })
+      .collect();
+    out.push_str(&cells.join(","));
+    out.push('\n');
+  }
+  out
+}
+
+fn quote(cell: &str) -> String {
+  let needs_quotes = cell.contains([',', '"', '\n', '\r']) || cell.trim() != cell;
+  if needs_quotes {
+    format!("\"{}\"", cell.replace('"', "\"\""))
+  } else {
+    String::from(cell)
+  }
+}
+
+pub fn from_csv<R: Record>(text: &str) -> Result<Vec<R>, SchemaError> {
+  let mut rows = split_rows(text)?.into_iter();
+  let Some((header_line, header)) = rows.next() else {
+    return Err(SchemaError { line: 1, kind: ErrorKind::Syntax(String::from("missing header row")) });
+  };
+
+  // For every field of the record, the column it is stored in
+  let mut columns = Vec::new();
+  for (field, _) in R::FIELDS {
+    let column = header.iter().position(|name| name.trim() == *field);
+    let column = column.ok_or(SchemaError { line: header_line, kind: ErrorKind::MissingField(field) })?;
+    if header[column + 1..].iter().any(|name| name.trim() == *field) {
+      return Err(SchemaError { line: header_line, kind: ErrorKind::DuplicateField(field) });
+    }
+    columns.push(column);
+  }
+  if let Some(name) = header.iter().find(|name| !R::FIELDS.iter().any(|(field, _)| name.trim() == *field)) {
+    return Err(SchemaError { line: header_line, kind: ErrorKind::UnknownField(name.clone()) });
+  }
+
+  let mut records = Vec::new();
+  for (line, row) in rows {
+    if row.len() != header.len() {
+      let message = format!("expected {} cells but found {}", header.len(), row.len());
+      return Err(SchemaError { line, kind: ErrorKind::Syntax(message) });
+    }
+    let record = record_from_row::<R>(&row, &columns).map_err(|kind| SchemaError { line, kind })?;
+    records.push(record);
+  }
+  Ok(records)
+}
+
+// CSV cells carry no type, so integer fields are parsed here and reported as mistyped if they are not numbers
+fn record_from_row<R: Record>(row: &[String], columns: &[usize]) -> Result<R, ErrorKind> {
+  let mut values = Vec::new();
+  for ((field, kind), column) in R::FIELDS.iter().zip(columns) {
+    let cell = &row[*column];
+    let value = match kind {
+      FieldKind::Text => Value::Text(cell.clone()),
+      FieldKind::Integer => {
+        let trimmed = cell.trim();
+        if trimmed.is_empty() || !trimmed.trim_start_matches(['-', '+']).chars().all(|c| c.is_ascii_digit()) {
+          return Err(ErrorKind::MistypedField { field, expected: *kind, found: format!("{cell:?}") });
+        }
+        let parsed = trimmed.parse().map_err(|_| ErrorKind::OutOfRange { field, value: String::from(trimmed) })?;
+        Value::Integer(parsed)
+      },
+    };
+    values.push(value);
+  }
+  R::from_values(values)
+}
+
+// Splits the text into rows of cells, each row paired with the line it starts on. Blank lines are skipped.
+fn split_rows(text: &str) -> Result<Vec<(usize, Vec<String>)>, SchemaError> {
+  let mut rows = Vec::new();
+  let mut row = Vec::new();
+  let mut cell = String::new();
+  let mut line = 1;
+  let mut row_line = 1;
+  let mut in_quotes = false;
+  let mut chars = text.chars().peekable();
+
+  while let Some(ch) = chars.next() {
+    if in_quotes {
+      match ch {
+        '"' if chars.peek() == Some(&'"') => {
+          chars.next();
+          cell.push('"');
+        },
+        '"' => in_quotes = false,
+        '\n' => {
+          line += 1;
+          cell.push(ch);
+        },
+        _ => cell.push(ch),
+      }
+      continue;
+    }
+    match ch {
+      '"' if cell.trim().is_empty() => {
+        cell.clear();
+        in_quotes = true;
+      },
+      '"' => {
+        let message = String::from("unexpected `\"` inside an unquoted cell");
+        return Err(SchemaError { line, kind: ErrorKind::Syntax(message) });
+      },
+      ',' => row.push(std::mem::take(&mut cell)),
+      '\r' if chars.peek() == Some(&'\n') => {},
+      '\n' => {
+        end_row(&mut rows, &mut row, &mut cell, row_line);
+        line += 1;
+        row_line = line;
+      },
+      _ => cell.push(ch),
+    }
+  }
+  if in_quotes {
+    return Err(SchemaError { line, kind: ErrorKind::Syntax(String::from("unterminated quoted cell")) });
+  }
+  end_row(&mut rows, &mut row, &mut cell, row_line);
+  Ok(rows)
+}
+
+fn end_row(rows: &mut Vec<(usize, Vec<String>)>, row: &mut Vec<String>, cell: &mut String, line: usize) {
+  if row.is_empty() && cell.trim().is_empty() {
+    cell.clear();
+    return;
+  }
+  row.push(std::mem::take(cell));
+  rows.push((line, std::mem::take(row)));
+}
@@ -0,0 +1,318 @@
+// A minimal JSON reader / writer for `Record`s.
+// A file holds an array of objects, one object per record: [{"name": "Joey", "email": "...", "age": 27}]
+
+use crate::record::{ErrorKind, FieldKind, Record, SchemaError, Value};
+
+pub fn to_json<R: Record>(records: &[R]) -> String {
+  let mut out = String::from("[\n");
+  for (idx, record) in records.iter().enumerate() {
+    let fields: Vec<String> = R::FIELDS
+      .iter()
+      .zip(record.to_values())
+      .map(|((name, _), value)| {
+        let value = match value {
+          Value::Text(s) => quote(&s),
+          Value::Integer(n) => n.to_string(),
+        };
+        format!("{}: {}", quote(name), value)
+      })
+      .collect();
+    out.push_str(&format!("  {{{}}}", fields.join(", ")));
+    if idx + 1 < records.len() {
+      out.push(',');
+    }
+    out.push('\n');
+  }
+  out.push_str("]\n");
+  out
+}
+
+fn quote(s: &str) -> String {
+  let mut out = String::from("\"");
+  for ch in s.chars() {
+    match ch {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+pub fn from_json<R: Record>(text: &str) -> Result<Vec<R>, SchemaError> {
+  let mut parser = Parser { chars: text.chars().collect(), pos: 0, line: 1 };
+  let mut records = Vec::new();
+
+  parser.expect('[')?;
+  if !parser.eat(']') {
+    loop {
+      parser.skip_whitespace();
+      let line = parser.line;
+      let object = match parser.parse_value()? {
+        Json::Object(fields) => fields,
+        other => return Err(parser.error(format!("expected an object but found {}", other.describe()))),
+      };
+      let record = record_from_object::<R>(object).map_err(|kind| SchemaError { line, kind })?;
+      records.push(record);
+      if parser.eat(']') {
+        break;
+      }
+      parser.expect(',')?;
+    }
+  }
+  parser.skip_whitespace();
+  if parser.pos < parser.chars.len() {
+    return Err(parser.error(String::from("unexpected characters after the end of the array")));
+  }
+  Ok(records)
+}
+
+// Checks the object against R::FIELDS and builds the record out of it
+fn record_from_object<R: Record>(mut object: Vec<(String, Json)>) -> Result<R, ErrorKind> {
+  let mut values = Vec::new();
+  for (field, kind) in R::FIELDS {
+    let idx = object.iter().position(|(key, _)| key == field).ok_or(ErrorKind::MissingField(field))?;
+    let (_, json) = object.remove(idx);
+    if object.iter().any(|(key, _)| key == field) {
+      return Err(ErrorKind::DuplicateField(field));
+    }
+    let value = match (kind, json) {
+      (FieldKind::Text, Json::String(s)) => Value::Text(s),
+      (FieldKind::Integer, Json::Number(n)) if is_integer(&n) => {
+        let parsed = n.parse().map_err(|_| ErrorKind::OutOfRange { field, value: n.clone() })?;
+        Value::Integer(parsed)
+      },
+      (expected, json) => {
+        return Err(ErrorKind::MistypedField { field, expected: *expected, found: json.describe() })
+      },
+    };
+    values.push(value);
+  }
+  // Whatever is left over was not declared by the record
+  if let Some((key, _)) = object.into_iter().next() {
+    return Err(ErrorKind::UnknownField(key));
+  }
+  R::from_values(values)
+}
+
+fn is_integer(number: &str) -> bool {
+  !number.contains(['.', 'e', 'E'])
+}
+
+enum Json {
+  Null,
+  Bool(bool),
+  // Numbers are kept as written so integers bigger than f64 can represent exactly are not rounded
+  Number(String),
+  String(String),
+  Array(Vec<Json>),
+  Object(Vec<(String, Json)>),
+}
+
+impl Json {
+  // Used in error messages, e.g. "field `age` should be an integer but found \"23\""
+  fn describe(&self) -> String {
+    match self {
+      Json::Null => String::from("null"),
+      Json::Bool(b) => b.to_string(),
+      Json::Number(n) => n.clone(),
+      Json::String(s) => quote(s),
+      Json::Array(items) => format!("an array of {} values", items.len()),
+      Json::Object(_) => String::from("an object"),
+    }
+  }
+}
+
+struct Parser {
+  chars: Vec<char>,
+  pos: usize,
+  line: usize,
+}
+
+impl Parser {
+  fn error(&self, message: String) -> SchemaError {
+    SchemaError { line: self.line, kind: ErrorKind::Syntax(message) }
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.pos).copied()
+  }
+
+  fn bump(&mut self) -> Option<char> {
+    let ch = self.peek()?;
+    self.pos += 1;
+    if ch == '\n' {
+      self.line += 1;
+    }
+    Some(ch)
+  }
+
+  fn skip_whitespace(&mut self) {
+    while matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+      self.bump();
+    }
+  }
+
+  // Skips whitespace and consumes `ch` if it comes next
+  fn eat(&mut self, ch: char) -> bool {
+    self.skip_whitespace();
+    if self.peek() == Some(ch) {
+      self.bump();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn expect(&mut self, ch: char) -> Result<(), SchemaError> {
+    if self.eat(ch) {
+      Ok(())
+    } else {
+      Err(self.unexpected(&format!("`{ch}`")))
+    }
+  }
+
+  fn unexpected(&self, expected: &str) -> SchemaError {
+    match self.peek() {
+      Some(found) => self.error(format!("expected {expected} but found `{found}`")),
+      None => self.error(format!("expected {expected} but reached the end of the input")),
+    }
+  }
+
+  fn parse_value(&mut self) -> Result<Json, SchemaError> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some('{') => self.parse_object(),
+      Some('[') => self.parse_array(),
+      Some('"') => Ok(Json::String(self.parse_string()?)),
+      Some('-' | '0'..='9') => Ok(Json::Number(self.parse_number()?)),
+      Some('t') => self.parse_keyword("true", Json::Bool(true)),
+      Some('f') => self.parse_keyword("false", Json::Bool(false)),
+      Some('n') => self.parse_keyword("null", Json::Null),
+      _ => Err(self.unexpected("a value")),
+    }
+  }
+
+  fn parse_keyword(&mut self, keyword: &str, value: Json) -> Result<Json, SchemaError> {
+    for expected in keyword.chars() {
+      if self.peek() != Some(expected) {
+        return Err(self.unexpected(&format!("`{keyword}`")));
+      }
+      self.bump();
+    }
+    Ok(value)
+  }
+
+  fn parse_number(&mut self) -> Result<String, SchemaError> {
+    let mut number = String::new();
+    while let Some(ch @ ('-' | '+' | '.' | 'e' | 'E' | '0'..='9')) = self.peek() {
+      number.push(ch);
+      self.bump();
+    }
+    // Every valid JSON number is also a valid f64 literal, which catches things like `1-2` or `1.2.3`
+    match number.parse::<f64>() {
+      Ok(_) => Ok(number),
+      Err(_) => Err(self.error(format!("invalid number `{number}`"))),
+    }
+  }
+
+  fn parse_string(&mut self) -> Result<String, SchemaError> {
+    self.expect('"')?;
+    let mut s = String::new();
+    loop {
+      match self.bump() {
+        Some('"') => return Ok(s),
+        Some('\\') => {
+          let escaped = match self.bump() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => self.parse_unicode_escape()?,
+            _ => return Err(self.error(String::from("invalid escape sequence in string"))),
+          };
+          s.push(escaped);
+        },
+        Some(ch) => s.push(ch),
+        None => return Err(self.error(String::from("unterminated string"))),
+      }
+    }
+  }
+
+  // The four hex digits after `\u`. Characters outside the Basic Multilingual Plane are written as a
+  // surrogate pair of two such escapes, which we combine back into one char.
+  fn parse_unicode_escape(&mut self) -> Result<char, SchemaError> {
+    let high = self.parse_hex4()?;
+    let code = if (0xD800..0xDC00).contains(&high) {
+      if self.bump() != Some('\\') || self.bump() != Some('u') {
+        return Err(self.error(String::from("unpaired surrogate in unicode escape")));
+      }
+      let low = self.parse_hex4()?;
+      if !(0xDC00..0xE000).contains(&low) {
+        return Err(self.error(String::from("unpaired surrogate in unicode escape")));
+      }
+      0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+    } else {
+      high
+    };
+    char::from_u32(code).ok_or_else(|| self.error(String::from("invalid unicode escape")))
+  }
+
+  fn parse_hex4(&mut self) -> Result<u32, SchemaError> {
+    let mut code = 0;
+    for _ in 0..4 {
+      let digit = self.bump().and_then(|ch| ch.to_digit(16));
+      match digit {
+        Some(d) => code = code * 16 + d,
+        None => return Err(self.error(String::from("invalid unicode escape"))),
+      }
+    }
+    Ok(code)
+  }
+
+  fn parse_array(&mut self) -> Result<Json, SchemaError> {
+    self.expect('[')?;
+    let mut items = Vec::new();
+    if self.eat(']') {
+      return Ok(Json::Array(items));
+    }
+    loop {
+      items.push(self.parse_value()?);
+      if self.eat(']') {
+        return Ok(Json::Array(items));
+      }
+      self.expect(',')?;
+    }
+  }
+
+  fn parse_object(&mut self) -> Result<Json, SchemaError> {
+    self.expect('{')?;
+    let mut fields = Vec::new();
+    if self.eat('}') {
+      return Ok(Json::Object(fields));
+    }
+    loop {
+      self.skip_whitespace();
+      if self.peek() != Some('"') {
+        return Err(self.unexpected("a field name"));
+      }
+      let key = self.parse_string()?;
+      self.expect(':')?;
+      let value = self.parse_value()?;
+      fields.push((key, value));
+      if self.eat('}') {
+        return Ok(Json::Object(fields));
+      }
+      self.expect(',')?;
+    }
+  }
+}
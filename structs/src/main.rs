@@ -1,3 +1,15 @@
+mod csv;
+mod json;
+mod point;
+mod polygon;
+mod record;
+
+use std::{env, process};
+
+use point::{Point, PointF};
+use polygon::{Polygon, Polyline};
+use record::Format;
+
 struct User {
   name: String,
   email: String,
@@ -10,9 +22,6 @@ struct Color(u16, u16, u16);
 // Unit like struct
 struct AlwaysEqual;
 
-#[derive(Debug)] // this specifies a default implementation for Debug trait for this struct
-struct Point { x: i32, y: i32 }
-
 fn build_user(name: String, email: String) -> User {
   // field init shorthand where the key, value need not be specified explicitly.
   User {
@@ -23,6 +32,26 @@ fn build_user(name: String, email: String) -> User {
 }
 
 fn main() {
+  // `cargo run -- convert <user|color|point> <input> <output>` converts a file of records between
+  // JSON and CSV, the formats are picked from the file extensions.
+  let args: Vec<String> = env::args().skip(1).collect();
+  if !args.is_empty() {
+    match args.as_slice() {
+      [command, record, input, output] if command == "convert" => match record::convert_file(record, input, output) {
+        Ok(count) => println!("converted {count} {record} record(s) from {input} to {output}"),
+        Err(e) => {
+          eprintln!("error: {e}");
+          process::exit(1);
+        }
+      },
+      _ => {
+        eprintln!("usage: structs convert <user|color|point> <input.json|csv> <output.json|csv>");
+        process::exit(2);
+      }
+    }
+    return;
+  }
+
   let user = User {
     name: String::from("Sanchet"),
     email: String::from("sanchet@email.com"),
@@ -58,9 +87,9 @@ fn main() {
   // user then in that situation, `age` value would have been copied since it has the `Copy`
   // trait.
 
-  let red_color = Color(255, 0, 0); // tuple struct declaration
+  let _red_color = Color(255, 0, 0); // tuple struct declaration
   
-  let always_equal = AlwaysEqual;
+  let _always_equal = AlwaysEqual;
 
   // Borrowing fields of a struct would make `p` and `p.x` temporarily lose its permissions
   let mut p = Point { x: 0, y: 0 };
@@ -73,4 +102,78 @@ fn main() {
   println!("point is: {p:#?}"); // debug mode output
 
   dbg!(&p);
+
+  // Point is `Copy` and overloads the arithmetic operators, so it can be used like a 2D vector
+  let a = Point::new(3, 4);
+  let b = Point::new(-1, 2);
+  println!("a + b = {:?}, a - b = {:?}, a * 2 = {:?}, -a = {:?}", a + b, a - b, a * 2, -a);
+  println!("dot: {}, cross: {}", a.dot(b), a.cross(b));
+  println!("manhattan: {}, euclidean: {}", a.manhattan_distance(b), a.euclidean_distance(b));
+  println!("a rotated by 90 degrees: {:?}", a.rotate_quarter_turns(1));
+  println!("a rotated by 45 degrees: {:?}", a.rotate(std::f64::consts::FRAC_PI_4));
+
+  // `+` would panic in debug builds (and wrap in release builds) here, the checked version returns None instead
+  let far = Point::new(i32::MAX, 0);
+  println!("checked add on overflow: {:?}", far.checked_add(Point::new(1, 0)));
+
+  let unit = PointF::new(3.0, 4.0).normalize();
+  println!("unit vector of (3, 4): {:?}", unit);
+
+  // Writing structs out as JSON / CSV and reading them back
+  let users = vec![mutable_user, build_user(String::from("Chandler"), String::from("chandler@bing.com"))];
+  let users_json = Format::Json.write(&users);
+  let users_csv = Format::Csv.write(&users);
+  println!("{users_json}{users_csv}");
+  let read_back: Vec<User> = Format::Csv.read(&users_csv).expect("CSV written above is valid");
+  println!("read back {} users, first is {}", read_back.len(), read_back[0].name);
+
+  let colors_json = Format::Json.write(&[_red_color, Color(0, 128, 255)]);
+  let points_csv = Format::Csv.write(&[p, a, b]);
+  println!("{colors_json}{points_csv}");
+
+  // Input that does not match the struct is reported with the line it was found on
+  let bad_users = "[\n  {\"name\": \"Ross\", \"age\": 29}\n]";
+  if let Err(e) = Format::Json.read::<User>(bad_users) {
+    println!("error: {e}");
+  }
+  let bad_colors = "red,green,blue\n255,0,0\n12,abc,0\n";
+  if let Err(e) = Format::Csv.read::<Color>(bad_colors) {
+    println!("error: {e}");
+  }
+  let bad_points = "[{\"x\": 1, \"y\": 99999999999}]";
+  if let Err(e) = Format::Json.read::<Point>(bad_points) {
+    println!("error: {e}");
+  }
+
+  // Polylines and polygons are built out of many points
+  let path = Polyline::new(vec![Point::new(0, 0), Point::new(4, 0), Point::new(4, 3)]).expect("path has enough points");
+  println!("path length: {}, crosses itself: {}", path.length(), path.self_intersects());
+
+  println!("path goes through {} points", path.points().len());
+  let triangle = path.close().expect("path has enough points for a triangle");
+  println!("closed path area: {}, counter-clockwise: {}", triangle.area(), triangle.is_counter_clockwise());
+
+  let square = Polygon::new(vec![Point::new(0, 0), Point::new(4, 0), Point::new(4, 4), Point::new(0, 4)])
+    .expect("square has enough vertices");
+  println!(
+    "square area: {}, perimeter: {}, centroid: {:?}",
+    square.area(),
+    square.perimeter(),
+    square.centroid()
+  );
+  println!(
+    "square contains (2, 2): {}, (4, 1): {}, (5, 5): {}",
+    square.contains_point(Point::new(2, 2)),
+    square.contains_point(Point::new(4, 1)),
+    square.contains_point(Point::new(5, 5))
+  );
+
+  // A bow tie whose edges cross in the middle
+  let bow_tie = Polygon::new(vec![Point::new(0, 0), Point::new(4, 4), Point::new(4, 0), Point::new(0, 4)]).unwrap();
+  println!("bow tie self intersects: {}, square self intersects: {}", bow_tie.self_intersects(), square.self_intersects());
+
+  let scattered = [Point::new(0, 0), Point::new(2, 1), Point::new(5, 0), Point::new(3, 3), Point::new(5, 5), Point::new(0, 5)];
+  let hull = polygon::convex_hull(&scattered).expect("points are not all on one line");
+  println!("convex hull: {:?}, convex: {}", hull.vertices(), hull.is_convex());
+  println!("convex hull of the bow tie: {:?}", bow_tie.convex_hull().map(|h| h.area()));
 }
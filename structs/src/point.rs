@@ -0,0 +1,392 @@
+// A 2D point / vector on the integer grid, plus a floating point variant `PointF`.
+// Implementing the traits from `std::ops` lets us use the usual operators (+, -, *, unary -)
+// directly on our own structs, this is called operator overloading.
+
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+// Point is made of two i32 values which are all `Copy`, therefore Point itself can derive `Copy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)] // derive generates a default implementation of each listed trait for this struct
+pub struct Point { pub x: i32, pub y: i32 }
+
+impl Point {
+  pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+  pub fn new(x: i32, y: i32) -> Point {
+    Point { x, y }
+  }
+
+  // Computed in i128: each product fits into an i64, but the sum of two of them can reach 2^63,
+  // e.g. for (i32::MIN, i32::MIN) dotted with itself
+  pub fn dot(self, other: Point) -> i128 {
+    self.x as i128 * other.x as i128 + self.y as i128 * other.y as i128
+  }
+
+  // The z component of the 3D cross product. Positive when `other` is counter-clockwise from `self`.
+  pub fn cross(self, other: Point) -> i64 {
+    self.x as i64 * other.y as i64 - self.y as i64 * other.x as i64
+  }
+
+  // Distance when only horizontal and vertical moves are allowed (|dx| + |dy|)
+  pub fn manhattan_distance(self, other: Point) -> u64 {
+    let dx = (self.x as i64 - other.x as i64).unsigned_abs();
+    let dy = (self.y as i64 - other.y as i64).unsigned_abs();
+    dx + dy
+  }
+
+  // Straight line distance, computed in f64 since it is usually not a whole number
+  pub fn euclidean_distance(self, other: Point) -> f64 {
+    PointF::from(self).distance(PointF::from(other))
+  }
+
+  // Rotating by multiples of 90 degrees keeps the point on the integer grid.
+  // Positive `quarter_turns` rotate counter-clockwise about the origin.
+  // Panics (in debug) like `-i32::MIN` would if a coordinate is i32::MIN, use `checked_rotate_quarter_turns` to avoid that.
+  pub fn rotate_quarter_turns(self, quarter_turns: i32) -> Point {
+    match quarter_turns.rem_euclid(4) {
+      0 => self,
+      1 => Point { x: -self.y, y: self.x },
+      2 => -self,
+      _ => Point { x: self.y, y: -self.x },
+    }
+  }
+
+  // Rotation by an arbitrary angle (in radians) leaves the grid, so we get a PointF back
+  pub fn rotate(self, radians: f64) -> PointF {
+    PointF::from(self).rotate(radians)
+  }
+
+  // The checked_* methods return None instead of panicking / wrapping when an i32 would overflow
+  pub fn checked_add(self, other: Point) -> Option<Point> {
+    Some(Point { x: self.x.checked_add(other.x)?, y: self.y.checked_add(other.y)? })
+  }
+
+  pub fn checked_sub(self, other: Point) -> Option<Point> {
+    Some(Point { x: self.x.checked_sub(other.x)?, y: self.y.checked_sub(other.y)? })
+  }
+
+  pub fn checked_mul(self, scalar: i32) -> Option<Point> {
+    Some(Point { x: self.x.checked_mul(scalar)?, y: self.y.checked_mul(scalar)? })
+  }
+
+  pub fn checked_neg(self) -> Option<Point> {
+    Some(Point { x: self.x.checked_neg()?, y: self.y.checked_neg()? })
+  }
+
+  pub fn checked_rotate_quarter_turns(self, quarter_turns: i32) -> Option<Point> {
+    match quarter_turns.rem_euclid(4) {
+      0 => Some(self),
+      1 => Some(Point { x: self.y.checked_neg()?, y: self.x }),
+      2 => self.checked_neg(),
+      _ => Some(Point { x: self.y, y: self.x.checked_neg()? }),
+    }
+  }
+}
+
+// `Output` is an associated type, it tells the compiler what type `a + b` evaluates to
+impl Add for Point {
+  type Output = Point;
+
+  fn add(self, other: Point) -> Point {
+    Point { x: self.x + other.x, y: self.y + other.y }
+  }
+}
+
+impl Sub for Point {
+  type Output = Point;
+
+  fn sub(self, other: Point) -> Point {
+    Point { x: self.x - other.x, y: self.y - other.y }
+  }
+}
+
+// Scaling by a number: `point * 3`
+impl Mul<i32> for Point {
+  type Output = Point;
+
+  fn mul(self, scalar: i32) -> Point {
+    Point { x: self.x * scalar, y: self.y * scalar }
+  }
+}
+
+// Scaling the other way around: `3 * point`. Here we implement a std trait for the foreign type i32,
+// which is allowed since our own type Point appears as the generic parameter.
+impl Mul<Point> for i32 {
+  type Output = Point;
+
+  fn mul(self, point: Point) -> Point {
+    point * self
+  }
+}
+
+impl Neg for Point {
+  type Output = Point;
+
+  fn neg(self) -> Point {
+    Point { x: -self.x, y: -self.y }
+  }
+}
+
+impl AddAssign for Point {
+  fn add_assign(&mut self, other: Point) {
+    *self = *self + other;
+  }
+}
+
+impl SubAssign for Point {
+  fn sub_assign(&mut self, other: Point) {
+    *self = *self - other;
+  }
+}
+
+// Floating point variant. f64 does not implement `Eq` or `Hash` (NaN != NaN), so we can only derive `PartialEq`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PointF { pub x: f64, pub y: f64 }
+
+impl PointF {
+  pub const ORIGIN: PointF = PointF { x: 0.0, y: 0.0 };
+
+  pub fn new(x: f64, y: f64) -> PointF {
+    PointF { x, y }
+  }
+
+  pub fn dot(self, other: PointF) -> f64 {
+    self.x * other.x + self.y * other.y
+  }
+
+  pub fn cross(self, other: PointF) -> f64 {
+    self.x * other.y - self.y * other.x
+  }
+
+  // Length of the vector from the origin to this point
+  pub fn length(self) -> f64 {
+    self.x.hypot(self.y)
+  }
+
+  pub fn manhattan_distance(self, other: PointF) -> f64 {
+    (self.x - other.x).abs() + (self.y - other.y).abs()
+  }
+
+  pub fn distance(self, other: PointF) -> f64 {
+    (self - other).length()
+  }
+
+  // Unit vector in the same direction, None for the zero vector since it has no direction
+  pub fn normalize(self) -> Option<PointF> {
+    let len = self.length();
+    if len == 0.0 || !len.is_finite() {
+      None
+    } else {
+      Some(PointF { x: self.x / len, y: self.y / len })
+    }
+  }
+
+  // Counter-clockwise rotation about the origin
+  pub fn rotate(self, radians: f64) -> PointF {
+    let (sin, cos) = radians.sin_cos();
+    PointF {
+      x: self.x * cos - self.y * sin,
+      y: self.x * sin + self.y * cos,
+    }
+  }
+
+  pub fn rotate_around(self, center: PointF, radians: f64) -> PointF {
+    (self - center).rotate(radians) + center
+  }
+
+  // Rounds back onto the integer grid, None if the result does not fit into an i32 (or is NaN)
+  pub fn round(self) -> Option<Point> {
+    let (x, y) = (self.x.round(), self.y.round());
+    let range = i32::MIN as f64..=i32::MAX as f64;
+    if range.contains(&x) && range.contains(&y) {
+      Some(Point { x: x as i32, y: y as i32 })
+    } else {
+      None
+    }
+  }
+}
+
+// Every i32 fits into an f64 exactly, so this conversion can never fail
+impl From<Point> for PointF {
+  fn from(p: Point) -> PointF {
+    PointF { x: p.x as f64, y: p.y as f64 }
+  }
+}
+
+impl Add for PointF {
+  type Output = PointF;
+
+  fn add(self, other: PointF) -> PointF {
+    PointF { x: self.x + other.x, y: self.y + other.y }
+  }
+}
+
+impl Sub for PointF {
+  type Output = PointF;
+
+  fn sub(self, other: PointF) -> PointF {
+    PointF { x: self.x - other.x, y: self.y - other.y }
+  }
+}
+
+impl Mul<f64> for PointF {
+  type Output = PointF;
+
+  fn mul(self, scalar: f64) -> PointF {
+    PointF { x: self.x * scalar, y: self.y * scalar }
+  }
+}
+
+impl Mul<PointF> for f64 {
+  type Output = PointF;
+
+  fn mul(self, point: PointF) -> PointF {
+    point * self
+  }
+}
+
+impl Neg for PointF {
+  type Output = PointF;
+
+  fn neg(self) -> PointF {
+    PointF { x: -self.x, y: -self.y }
+  }
+}
+
+impl AddAssign for PointF {
+  fn add_assign(&mut self, other: PointF) {
+    *self = *self + other;
+  }
+}
+
+impl SubAssign for PointF {
+  fn sub_assign(&mut self, other: PointF) {
+    *self = *self - other;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // A fixed pseudo random sequence of points with coordinates in -limit..=limit, plus the corners of that range
+  fn points(limit: i32, count: usize) -> Vec<Point> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = move || {
+      state ^= state << 13;
+      state ^= state >> 7;
+      state ^= state << 17;
+      ((state % (2 * limit as u64 + 1)) as i64 - limit as i64) as i32
+    };
+    let mut points: Vec<Point> = (0..count).map(|_| Point::new(next(), next())).collect();
+    points.extend([Point::ORIGIN, Point::new(limit, limit), Point::new(-limit, limit), Point::new(-limit, -limit)]);
+    points
+  }
+
+  #[test]
+  fn add_is_commutative_and_sub_adds_the_negation() {
+    let points = points(1_000_000, 50);
+    for &a in &points {
+      for &b in &points {
+        assert_eq!(a + b, b + a);
+        assert_eq!(a - b, a + -b);
+        assert_eq!(a.checked_add(b), Some(a + b));
+      }
+    }
+  }
+
+  #[test]
+  fn scalar_multiplication_distributes_over_add() {
+    let points = points(10_000, 50);
+    for &a in &points {
+      for &b in &points {
+        for k in [-7, -1, 0, 1, 3, 100] {
+          assert_eq!((a + b) * k, a * k + b * k);
+          assert_eq!(k * a, a * k);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn cross_product_is_antisymmetric() {
+    // the i64 products can't overflow even at the i32 extremes
+    let mut points = points(i32::MAX, 50);
+    points.push(Point::new(i32::MIN, i32::MIN));
+    for &a in &points {
+      assert_eq!(a.cross(a), 0);
+      for &b in &points {
+        assert_eq!(a.cross(b), -b.cross(a));
+        assert_eq!(a.dot(b), b.dot(a));
+      }
+    }
+  }
+
+  #[test]
+  fn dot_product_does_not_overflow_at_the_extremes() {
+    let min = Point::new(i32::MIN, i32::MIN);
+    assert_eq!(min.dot(min), 1i128 << 63);
+    assert_eq!(min.dot(Point::new(i32::MAX, i32::MAX)), -2 * (1i128 << 31) * i32::MAX as i128);
+    assert_eq!(Point::new(3, 4).dot(Point::new(-1, 2)), 5);
+    assert_eq!(Point::new(3, 4).dot(Point::new(-4, 3)), 0);
+  }
+
+  #[test]
+  fn four_quarter_turns_are_the_identity() {
+    for p in points(i32::MAX, 200) {
+      assert_eq!(p.rotate_quarter_turns(4), p);
+      assert_eq!(p.rotate_quarter_turns(1).rotate_quarter_turns(1).rotate_quarter_turns(1).rotate_quarter_turns(1), p);
+      assert_eq!(p.rotate_quarter_turns(-1), p.rotate_quarter_turns(3));
+      assert_eq!(p.checked_rotate_quarter_turns(1), Some(p.rotate_quarter_turns(1)));
+    }
+  }
+
+  #[test]
+  fn four_rotations_by_a_right_angle_come_back_close() {
+    for p in points(1_000_000, 200) {
+      let mut q = PointF::from(p);
+      for _ in 0..4 {
+        q = q.rotate(std::f64::consts::FRAC_PI_2);
+      }
+      assert_eq!(q.round(), Some(p));
+      assert!(q.distance(PointF::from(p)) < 1e-6);
+      assert_eq!(p.rotate(std::f64::consts::FRAC_PI_2).round(), Some(p.rotate_quarter_turns(1)));
+    }
+  }
+
+  #[test]
+  fn checked_operations_return_none_on_overflow() {
+    let max = Point::new(i32::MAX, i32::MAX);
+    let min = Point::new(i32::MIN, i32::MIN);
+    let one = Point::new(1, 1);
+
+    assert_eq!(max.checked_add(one), None);
+    assert_eq!(Point::new(0, i32::MAX).checked_add(Point::new(0, 1)), None);
+    assert_eq!(max.checked_add(Point::ORIGIN), Some(max));
+    assert_eq!(min.checked_sub(one), None);
+    assert_eq!(max.checked_sub(-one), None);
+    assert_eq!(min.checked_sub(Point::ORIGIN), Some(min));
+    assert_eq!(max.checked_mul(2), None);
+    assert_eq!(min.checked_mul(-1), None);
+    assert_eq!(max.checked_mul(-1), Some(Point::new(-i32::MAX, -i32::MAX)));
+    assert_eq!(min.checked_neg(), None);
+    assert_eq!(Point::new(0, i32::MIN).checked_neg(), None);
+    assert_eq!(max.checked_neg(), Some(Point::new(-i32::MAX, -i32::MAX)));
+    assert_eq!(Point::new(i32::MIN, 0).checked_rotate_quarter_turns(1), Some(Point::new(0, i32::MIN)));
+    assert_eq!(Point::new(0, i32::MIN).checked_rotate_quarter_turns(1), None);
+    assert_eq!(Point::new(i32::MIN, 0).checked_rotate_quarter_turns(-1), None);
+    assert_eq!(min.checked_rotate_quarter_turns(2), None);
+    assert_eq!(min.checked_rotate_quarter_turns(4), Some(min));
+
+    // everything the checked versions accept gives the same result as the operators
+    for a in points(i32::MAX, 100) {
+      for b in points(i32::MAX / 2, 20) {
+        if let Some(sum) = a.checked_add(b) {
+          assert_eq!(sum, a + b);
+        }
+        if let Some(difference) = a.checked_sub(b) {
+          assert_eq!(difference, a - b);
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,336 @@
+// Shapes made out of many Points: an open path (Polyline) and a closed shape (Polygon).
+// Both structs own their points in a Vec, the last vertex of a Polygon is implicitly joined back to the first.
+// All orientation tests are done with exact integer arithmetic (i128) so they can't be fooled by rounding.
+
+use crate::point::{Point, PointF};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+  points: Vec<Point>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+  vertices: Vec<Point>,
+}
+
+// Twice the signed area of the triangle a, b, c.
+// Positive when the points turn counter-clockwise, negative when clockwise and zero when they are collinear.
+fn orientation(a: Point, b: Point, c: Point) -> i128 {
+  let (abx, aby) = (b.x as i128 - a.x as i128, b.y as i128 - a.y as i128);
+  let (acx, acy) = (c.x as i128 - a.x as i128, c.y as i128 - a.y as i128);
+  abx * acy - aby * acx
+}
+
+// Whether p lies on the segment a-b (end points included)
+fn on_segment(p: Point, a: Point, b: Point) -> bool {
+  orientation(a, b, p) == 0
+    && p.x >= a.x.min(b.x)
+    && p.x <= a.x.max(b.x)
+    && p.y >= a.y.min(b.y)
+    && p.y <= a.y.max(b.y)
+}
+
+// Whether the segments a-b and c-d have at least one point in common
+fn segments_intersect(a: Point, b: Point, c: Point, d: Point) -> bool {
+  let (o1, o2) = (orientation(a, b, c).signum(), orientation(a, b, d).signum());
+  let (o3, o4) = (orientation(c, d, a).signum(), orientation(c, d, b).signum());
+  if o1 != o2 && o3 != o4 {
+    return true;
+  }
+  on_segment(c, a, b) || on_segment(d, a, b) || on_segment(a, c, d) || on_segment(b, c, d)
+}
+
+// Checks every pair of segments. Neighbouring segments always share an end point, so for them we only
+// report an intersection when they fold back onto each other.
+fn any_segments_intersect(segments: &[(Point, Point)], closed: bool) -> bool {
+  let n = segments.len();
+  for i in 0..n {
+    for j in (i + 1)..n {
+      let (a, b) = segments[i];
+      let (c, d) = segments[j];
+      let adjacent = j == i + 1 || (closed && i == 0 && j == n - 1);
+      if !adjacent {
+        if segments_intersect(a, b, c, d) {
+          return true;
+        }
+      } else if j == i + 1 {
+        // shared point is b == c, they overlap if the far ends fall back onto the other segment
+        if (on_segment(d, a, b) && d != b) || (on_segment(a, c, d) && a != c) {
+          return true;
+        }
+      } else if (on_segment(b, c, d) && b != a) || (on_segment(c, a, b) && c != d) {
+        // the closing edge (c, d) meets the first edge (a, b) at d == a
+        return true;
+      }
+    }
+  }
+  false
+}
+
+fn length_of(segments: &[(Point, Point)]) -> f64 {
+  segments.iter().map(|(a, b)| a.euclidean_distance(*b)).sum()
+}
+
+impl Polyline {
+  pub fn new(points: Vec<Point>) -> Result<Polyline, String> {
+    if points.len() < 2 {
+      return Err(format!("a polyline needs at least 2 points, got {}", points.len()));
+    }
+    Ok(Polyline { points })
+  }
+
+  pub fn points(&self) -> &[Point] {
+    &self.points
+  }
+
+  // Each pair of consecutive points
+  pub fn segments(&self) -> Vec<(Point, Point)> {
+    self.points.windows(2).map(|w| (w[0], w[1])).collect()
+  }
+
+  pub fn length(&self) -> f64 {
+    length_of(&self.segments())
+  }
+
+  pub fn self_intersects(&self) -> bool {
+    any_segments_intersect(&self.segments(), false)
+  }
+
+  // Joins the last point back to the first one. Consumes the polyline since the points move into the polygon.
+  pub fn close(self) -> Result<Polygon, String> {
+    Polygon::new(self.points)
+  }
+}
+
+impl Polygon {
+  pub fn new(vertices: Vec<Point>) -> Result<Polygon, String> {
+    if vertices.len() < 3 {
+      return Err(format!("a polygon needs at least 3 vertices, got {}", vertices.len()));
+    }
+    Ok(Polygon { vertices })
+  }
+
+  pub fn vertices(&self) -> &[Point] {
+    &self.vertices
+  }
+
+  // Each edge, including the closing one from the last vertex back to the first
+  pub fn edges(&self) -> Vec<(Point, Point)> {
+    let n = self.vertices.len();
+    (0..n).map(|i| (self.vertices[i], self.vertices[(i + 1) % n])).collect()
+  }
+
+  // Shoelace formula, twice the signed area as an exact integer.
+  // Positive when the vertices go counter-clockwise, negative when they go clockwise.
+  pub fn signed_double_area(&self) -> i128 {
+    self.edges().iter().map(|(a, b)| a.x as i128 * b.y as i128 - b.x as i128 * a.y as i128).sum()
+  }
+
+  pub fn area(&self) -> f64 {
+    self.signed_double_area().abs() as f64 / 2.0
+  }
+
+  pub fn is_counter_clockwise(&self) -> bool {
+    self.signed_double_area() > 0
+  }
+
+  pub fn perimeter(&self) -> f64 {
+    length_of(&self.edges())
+  }
+
+  // Centre of mass of the enclosed area, None when the polygon has no area (all vertices on one line)
+  pub fn centroid(&self) -> Option<PointF> {
+    let double_area = self.signed_double_area();
+    if double_area == 0 {
+      return None;
+    }
+    let (mut cx, mut cy) = (0.0, 0.0);
+    for (a, b) in self.edges() {
+      let cross = a.x as f64 * b.y as f64 - b.x as f64 * a.y as f64;
+      cx += (a.x as f64 + b.x as f64) * cross;
+      cy += (a.y as f64 + b.y as f64) * cross;
+    }
+    let factor = 3.0 * double_area as f64;
+    Some(PointF::new(cx / factor, cy / factor))
+  }
+
+  // Crossing number test: count how many edges a ray going right from p crosses.
+  // Points on the boundary count as contained.
+  pub fn contains_point(&self, p: Point) -> bool {
+    let mut inside = false;
+    for (a, b) in self.edges() {
+      if on_segment(p, a, b) {
+        return true;
+      }
+      if (a.y > p.y) != (b.y > p.y) {
+        let side = orientation(a, b, p);
+        // an upward edge crosses the ray when p is on its left, a downward edge when p is on its right
+        if (b.y > a.y && side > 0) || (b.y < a.y && side < 0) {
+          inside = !inside;
+        }
+      }
+    }
+    inside
+  }
+
+  // A simple polygon is one whose edges only touch at shared vertices
+  pub fn self_intersects(&self) -> bool {
+    any_segments_intersect(&self.edges(), true)
+  }
+
+  pub fn is_convex(&self) -> bool {
+    let n = self.vertices.len();
+    let turns: Vec<i128> = (0..n)
+      .map(|i| orientation(self.vertices[i], self.vertices[(i + 1) % n], self.vertices[(i + 2) % n]))
+      .filter(|turn| *turn != 0)
+      .collect();
+    let all_same_way = turns.iter().all(|t| *t > 0) || turns.iter().all(|t| *t < 0);
+    all_same_way && !turns.is_empty() && !self.self_intersects()
+  }
+
+  pub fn convex_hull(&self) -> Option<Polygon> {
+    convex_hull(&self.vertices)
+  }
+}
+
+// Andrew's monotone chain: sort the points, then build the lower and upper halves of the hull keeping only
+// left turns. The result is counter-clockwise without collinear points. None when all points are on one line.
+pub fn convex_hull(points: &[Point]) -> Option<Polygon> {
+  let mut sorted = points.to_vec();
+  sorted.sort_by_key(|p| (p.x, p.y));
+  sorted.dedup();
+
+  let mut hull: Vec<Point> = Vec::with_capacity(sorted.len() * 2);
+  for pass in 0..2 {
+    let start = hull.len();
+    for &p in &sorted {
+      while hull.len() >= start + 2 && orientation(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0 {
+        hull.pop();
+      }
+      hull.push(p);
+    }
+    // the last point of each half is the first point of the other one
+    hull.pop();
+    if pass == 0 {
+      sorted.reverse();
+    }
+  }
+
+  Polygon::new(hull).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn points(coordinates: &[(i32, i32)]) -> Vec<Point> {
+    coordinates.iter().map(|&(x, y)| Point::new(x, y)).collect()
+  }
+
+  fn polygon(coordinates: &[(i32, i32)]) -> Polygon {
+    Polygon::new(points(coordinates)).unwrap()
+  }
+
+  #[test]
+  fn unit_square_area_perimeter_and_centroid() {
+    let square = polygon(&[(0, 0), (1, 0), (1, 1), (0, 1)]);
+    assert_eq!(square.signed_double_area(), 2);
+    assert_eq!(square.area(), 1.0);
+    assert_eq!(square.perimeter(), 4.0);
+    assert_eq!(square.centroid(), Some(PointF::new(0.5, 0.5)));
+    assert!(square.is_convex());
+    assert!(!square.self_intersects());
+
+    // moving it moves the centroid but not the area
+    let moved = polygon(&[(10, -3), (11, -3), (11, -2), (10, -2)]);
+    assert_eq!(moved.area(), 1.0);
+    assert_eq!(moved.centroid(), Some(PointF::new(10.5, -2.5)));
+  }
+
+  #[test]
+  fn winding_only_changes_the_sign() {
+    let counter_clockwise = polygon(&[(0, 0), (4, 0), (4, 3)]);
+    let clockwise = polygon(&[(0, 0), (4, 3), (4, 0)]);
+    assert!(counter_clockwise.is_counter_clockwise());
+    assert!(!clockwise.is_counter_clockwise());
+    assert_eq!(counter_clockwise.signed_double_area(), 12);
+    assert_eq!(clockwise.signed_double_area(), -12);
+    assert_eq!(counter_clockwise.area(), clockwise.area());
+    assert_eq!(counter_clockwise.centroid(), clockwise.centroid());
+    assert_eq!(counter_clockwise.perimeter(), 12.0);
+    for p in [(3, 1), (4, 0), (2, 0), (1, 1), (5, 1), (0, 1)] {
+      let p = Point::new(p.0, p.1);
+      assert_eq!(counter_clockwise.contains_point(p), clockwise.contains_point(p), "{p:?}");
+    }
+  }
+
+  #[test]
+  fn points_on_the_boundary_are_contained() {
+    let square = polygon(&[(0, 0), (4, 0), (4, 4), (0, 4)]);
+    // vertices
+    for (x, y) in [(0, 0), (4, 0), (4, 4), (0, 4)] {
+      assert!(square.contains_point(Point::new(x, y)), "vertex ({x}, {y})");
+    }
+    // on an edge, including the closing edge
+    for (x, y) in [(2, 0), (4, 1), (3, 4), (0, 2)] {
+      assert!(square.contains_point(Point::new(x, y)), "edge ({x}, {y})");
+    }
+    assert!(square.contains_point(Point::new(2, 2)));
+    // outside, also level with a vertex where the ray passes through it
+    for (x, y) in [(5, 5), (-1, 0), (5, 0), (5, 4), (2, -1), (2, 5)] {
+      assert!(!square.contains_point(Point::new(x, y)), "outside ({x}, {y})");
+    }
+
+    // a concave polygon with a vertex pointing into the notch
+    let arrow = polygon(&[(0, 0), (4, 2), (0, 4), (2, 2)]);
+    assert!(arrow.contains_point(Point::new(3, 2)));
+    assert!(arrow.contains_point(Point::new(2, 2)));
+    assert!(!arrow.contains_point(Point::new(1, 2)));
+    assert!(!arrow.is_convex());
+  }
+
+  #[test]
+  fn convex_hull_drops_collinear_and_inner_points() {
+    // corners of a square, points in the middle of its sides and one inside
+    let hull = convex_hull(&points(&[(0, 0), (2, 0), (4, 0), (4, 2), (4, 4), (2, 4), (0, 4), (0, 2), (1, 1), (0, 0)])).unwrap();
+    assert_eq!(hull.vertices(), points(&[(0, 0), (4, 0), (4, 4), (0, 4)]));
+    assert!(hull.is_counter_clockwise());
+    assert!(hull.is_convex());
+
+    // all on one line: no hull
+    assert_eq!(convex_hull(&points(&[(0, 0), (1, 1), (2, 2), (5, 5)])), None);
+    assert_eq!(convex_hull(&points(&[(3, 3), (3, 3), (3, 3)])), None);
+    assert_eq!(convex_hull(&points(&[(0, 0), (2, 0), (1, 3)])).unwrap().vertices(), points(&[(0, 0), (2, 0), (1, 3)]));
+  }
+
+  #[test]
+  fn bow_tie_intersects_itself() {
+    let bow_tie = polygon(&[(0, 0), (4, 4), (4, 0), (0, 4)]);
+    assert!(bow_tie.self_intersects());
+    assert!(!bow_tie.is_convex());
+    // the shoelace sum of a bow tie cancels out, its two halves wind opposite ways
+    assert_eq!(bow_tie.signed_double_area(), 0);
+    assert_eq!(bow_tie.centroid(), None);
+    assert_eq!(bow_tie.convex_hull().map(|hull| hull.area()), Some(16.0));
+
+    // an edge folding back onto the previous one
+    assert!(polygon(&[(0, 0), (4, 0), (2, 0), (2, 3)]).self_intersects());
+    // touching at a vertex that isn't shared by neighbouring edges
+    assert!(polygon(&[(0, 0), (4, 0), (2, 2), (4, 4), (0, 4), (2, 2)]).self_intersects());
+  }
+
+  #[test]
+  fn polylines() {
+    assert!(Polyline::new(points(&[(0, 0)])).is_err());
+    assert!(Polygon::new(points(&[(0, 0), (1, 1)])).is_err());
+    let path = Polyline::new(points(&[(0, 0), (3, 0), (3, 4)])).unwrap();
+    assert_eq!(path.length(), 7.0);
+    assert!(!path.self_intersects());
+    assert!(Polyline::new(points(&[(0, 0), (4, 0), (4, 4), (2, -2)])).unwrap().self_intersects());
+    // open, so the ends meeting don't count as crossing until it is closed
+    let triangle = path.close().unwrap();
+    assert_eq!(triangle.perimeter(), 12.0);
+    assert_eq!(triangle.area(), 6.0);
+  }
+}
@@ -0,0 +1,318 @@
+// Persisting the structs from main.rs as JSON or CSV.
+// Every struct describes its fields once through the `Record` trait. The json and csv modules use that
+// description both to write the values out and to check the input (missing / mistyped fields) when reading.
+
+use std::{fmt, fs, path::Path};
+
+use crate::point::Point;
+use crate::{csv, json, Color, User};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+  Text,
+  Integer,
+}
+
+impl fmt::Display for FieldKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      FieldKind::Text => write!(f, "a string"),
+      FieldKind::Integer => write!(f, "an integer"),
+    }
+  }
+}
+
+// A single field value, already checked against the FieldKind declared for it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Text(String),
+  Integer(i64),
+}
+
+pub trait Record: Sized {
+  // Field names and kinds, in the order they are written out (this is also the CSV header)
+  const FIELDS: &'static [(&'static str, FieldKind)];
+
+  // One value per entry of FIELDS, in the same order
+  fn to_values(&self) -> Vec<Value>;
+
+  // `values` has one value per entry of FIELDS and each value has the declared kind,
+  // so implementations only have to check that integers fit into the field's type.
+  fn from_values(values: Vec<Value>) -> Result<Self, ErrorKind>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+  Syntax(String),
+  MissingField(&'static str),
+  UnknownField(String),
+  DuplicateField(&'static str),
+  MistypedField { field: &'static str, expected: FieldKind, found: String },
+  OutOfRange { field: &'static str, value: String },
+}
+
+// `line` is the line of the input file the error was found on (1-based)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaError {
+  pub line: usize,
+  pub kind: ErrorKind,
+}
+
+impl fmt::Display for SchemaError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "line {}: ", self.line)?;
+    match &self.kind {
+      ErrorKind::Syntax(message) => write!(f, "{message}"),
+      ErrorKind::MissingField(field) => write!(f, "missing field `{field}`"),
+      ErrorKind::UnknownField(field) => write!(f, "unknown field `{field}`"),
+      ErrorKind::DuplicateField(field) => write!(f, "field `{field}` appears more than once"),
+      ErrorKind::MistypedField { field, expected, found } => {
+        write!(f, "field `{field}` should be {expected} but found {found}")
+      },
+      ErrorKind::OutOfRange { field, value } => write!(f, "value {value} is out of range for field `{field}`"),
+    }
+  }
+}
+
+// Takes the next value out of the iterator, `from_values` can rely on it being there with the right kind
+fn next_text(values: &mut impl Iterator<Item = Value>) -> String {
+  match values.next() {
+    Some(Value::Text(s)) => s,
+    other => panic!("expected a text value, got {other:?}"),
+  }
+}
+
+// Integers are read as i64 and then narrowed to the type of the struct field (u8, u16, i32, ...)
+fn next_integer<T: TryFrom<i64>>(values: &mut impl Iterator<Item = Value>, field: &'static str) -> Result<T, ErrorKind> {
+  match values.next() {
+    Some(Value::Integer(n)) => T::try_from(n).map_err(|_| ErrorKind::OutOfRange { field, value: n.to_string() }),
+    other => panic!("expected an integer value, got {other:?}"),
+  }
+}
+
+impl Record for User {
+  const FIELDS: &'static [(&'static str, FieldKind)] = &[
+    ("name", FieldKind::Text),
+    ("email", FieldKind::Text),
+    ("age", FieldKind::Integer),
+  ];
+
+  fn to_values(&self) -> Vec<Value> {
+    vec![
+      Value::Text(self.name.clone()),
+      Value::Text(self.email.clone()),
+      Value::Integer(self.age as i64),
+    ]
+  }
+
+  fn from_values(values: Vec<Value>) -> Result<User, ErrorKind> {
+    let mut values = values.into_iter();
+    Ok(User {
+      name: next_text(&mut values),
+      email: next_text(&mut values),
+      age: next_integer(&mut values, "age")?,
+    })
+  }
+}
+
+// Color is a tuple struct so its fields have no names, we give them names for the file formats
+impl Record for Color {
+  const FIELDS: &'static [(&'static str, FieldKind)] = &[
+    ("red", FieldKind::Integer),
+    ("green", FieldKind::Integer),
+    ("blue", FieldKind::Integer),
+  ];
+
+  fn to_values(&self) -> Vec<Value> {
+    vec![Value::Integer(self.0 as i64), Value::Integer(self.1 as i64), Value::Integer(self.2 as i64)]
+  }
+
+  fn from_values(values: Vec<Value>) -> Result<Color, ErrorKind> {
+    let mut values = values.into_iter();
+    Ok(Color(
+      next_integer(&mut values, "red")?,
+      next_integer(&mut values, "green")?,
+      next_integer(&mut values, "blue")?,
+    ))
+  }
+}
+
+impl Record for Point {
+  const FIELDS: &'static [(&'static str, FieldKind)] = &[("x", FieldKind::Integer), ("y", FieldKind::Integer)];
+
+  fn to_values(&self) -> Vec<Value> {
+    vec![Value::Integer(self.x as i64), Value::Integer(self.y as i64)]
+  }
+
+  fn from_values(values: Vec<Value>) -> Result<Point, ErrorKind> {
+    let mut values = values.into_iter();
+    Ok(Point {
+      x: next_integer(&mut values, "x")?,
+      y: next_integer(&mut values, "y")?,
+    })
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+  Json,
+  Csv,
+}
+
+impl Format {
+  // The format of a file is picked from its extension
+  pub fn from_path(path: &str) -> Result<Format, String> {
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+      Some(ext) if ext.eq_ignore_ascii_case("json") => Ok(Format::Json),
+      Some(ext) if ext.eq_ignore_ascii_case("csv") => Ok(Format::Csv),
+      _ => Err(format!("cannot tell the format of `{path}`, expected a .json or .csv file")),
+    }
+  }
+
+  pub fn write<R: Record>(self, records: &[R]) -> String {
+    match self {
+      Format::Json => json::to_json(records),
+      Format::Csv => csv::to_csv(records),
+    }
+  }
+
+  pub fn read<R: Record>(self, text: &str) -> Result<Vec<R>, SchemaError> {
+    match self {
+      Format::Json => json::from_json(text),
+      Format::Csv => csv::from_csv(text),
+    }
+  }
+}
+
+// Reads records of type R from `input` and writes them to `output`, returns how many were converted
+fn convert<R: Record>(input: &str, output: &str) -> Result<usize, String> {
+  let text = fs::read_to_string(input).map_err(|e| format!("could not read `{input}`: {e}"))?;
+  let records: Vec<R> = Format::from_path(input)?.read(&text).map_err(|e| format!("{input}: {e}"))?;
+  let out = Format::from_path(output)?.write(&records);
+  fs::write(output, out).map_err(|e| format!("could not write `{output}`: {e}"))?;
+  Ok(records.len())
+}
+
+// `record` selects the struct stored in the files: user, color or point
+pub fn convert_file(record: &str, input: &str, output: &str) -> Result<usize, String> {
+  match record {
+    "user" => convert::<User>(input, output),
+    "color" => convert::<Color>(input, output),
+    "point" => convert::<Point>(input, output),
+    _ => Err(format!("unknown record type `{record}`, expected one of: user, color, point")),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn user(name: &str, email: &str, age: u8) -> User {
+    User { name: String::from(name), email: String::from(email), age }
+  }
+
+  fn error_kind<R: Record>(format: Format, text: &str) -> ErrorKind {
+    match format.read::<R>(text) {
+      Ok(records) => panic!("expected an error, read {} records", records.len()),
+      Err(error) => error.kind,
+    }
+  }
+
+  #[test]
+  fn json_to_csv_to_json_keeps_every_character() {
+    let users = [
+      user("Joey \"The Duke\" Tribbiani", "joey@tribbiani.com", 27),
+      user("Chandler, Muriel Bing", "chandler@bing.com", 29),
+      user("Phoebe\nBuffay", " phoebe@buffay.com ", 31),
+      user("Ross\r\nGeller\t\\", "ross@geller.com", 0),
+      user("Zoë Müller 山田 🦀", "zoë@example.com", 255),
+      user("", "", 1),
+    ];
+    let json = Format::Json.write(&users);
+    let from_json: Vec<User> = Format::Json.read(&json).unwrap();
+    let csv = Format::Csv.write(&from_json);
+    let from_csv: Vec<User> = Format::Csv.read(&csv).unwrap();
+    let json_again = Format::Json.write(&from_csv);
+    assert_eq!(json_again, json);
+    for (read, written) in from_csv.iter().zip(&users) {
+      assert_eq!(read.name, written.name);
+      assert_eq!(read.email, written.email);
+      assert_eq!(read.age, written.age);
+    }
+  }
+
+  #[test]
+  fn escapes_are_decoded_before_the_round_trip() {
+    // a surrogate pair for U+1F600, a BMP escape for é, and the short escapes
+    let json = r#"[{"name": "smile \ud83d\ude00 caf\u00e9", "email": "a\/b\"c\\d\te", "age": 5}]"#;
+    let users: Vec<User> = Format::Json.read(json).unwrap();
+    assert_eq!(users[0].name, "smile \u{1F600} café");
+    assert_eq!(users[0].email, "a/b\"c\\d\te");
+    let back: Vec<User> = Format::Csv.read(&Format::Csv.write(&users)).unwrap();
+    assert_eq!(back[0].name, users[0].name);
+    assert_eq!(back[0].email, users[0].email);
+    assert!(Format::Json.write(&back).contains("smile \u{1F600} café"));
+  }
+
+  #[test]
+  fn syntax_errors() {
+    assert!(matches!(error_kind::<Point>(Format::Json, r#"[{"x": 1, "y": 2}"#), ErrorKind::Syntax(_)));
+    assert!(matches!(error_kind::<Point>(Format::Json, r#"[{"x": "\ud83d", "y": 2}]"#), ErrorKind::Syntax(_)));
+    assert!(matches!(error_kind::<Point>(Format::Json, r#"[1]"#), ErrorKind::Syntax(_)));
+    assert!(matches!(error_kind::<Point>(Format::Csv, ""), ErrorKind::Syntax(_)));
+    assert!(matches!(error_kind::<Point>(Format::Csv, "x,y\n1\n"), ErrorKind::Syntax(_)));
+    assert!(matches!(error_kind::<Point>(Format::Csv, "x,y\n\"1,2\n"), ErrorKind::Syntax(_)));
+  }
+
+  #[test]
+  fn missing_fields() {
+    assert_eq!(error_kind::<Point>(Format::Json, r#"[{"x": 1}]"#), ErrorKind::MissingField("y"));
+    assert_eq!(error_kind::<Point>(Format::Csv, "x\n1\n"), ErrorKind::MissingField("y"));
+  }
+
+  #[test]
+  fn unknown_fields() {
+    let json = r#"[{"x": 1, "y": 2, "z": 3}]"#;
+    assert_eq!(error_kind::<Point>(Format::Json, json), ErrorKind::UnknownField(String::from("z")));
+    assert_eq!(error_kind::<Point>(Format::Csv, "x,y,z\n1,2,3\n"), ErrorKind::UnknownField(String::from("z")));
+  }
+
+  #[test]
+  fn duplicate_fields() {
+    let json = r#"[{"x": 1, "y": 2, "x": 3}]"#;
+    assert_eq!(error_kind::<Point>(Format::Json, json), ErrorKind::DuplicateField("x"));
+    assert_eq!(error_kind::<Point>(Format::Csv, "x,y,x\n1,2,3\n"), ErrorKind::DuplicateField("x"));
+    assert_eq!(error_kind::<Point>(Format::Csv, "y, x ,x\n1,2,3\n"), ErrorKind::DuplicateField("x"));
+  }
+
+  #[test]
+  fn mistyped_fields() {
+    let expected = ErrorKind::MistypedField { field: "age", expected: FieldKind::Integer, found: String::from("\"23\"") };
+    let json = r#"[{"name": "Joey", "email": "joey@tribbiani.com", "age": "23"}]"#;
+    assert_eq!(error_kind::<User>(Format::Json, json), expected);
+    let json = r#"[{"name": "Joey", "email": "joey@tribbiani.com", "age": 2.5}]"#;
+    assert!(matches!(error_kind::<User>(Format::Json, json), ErrorKind::MistypedField { field: "age", .. }));
+    let json = r#"[{"name": 7, "email": "joey@tribbiani.com", "age": 23}]"#;
+    assert!(matches!(error_kind::<User>(Format::Json, json), ErrorKind::MistypedField { field: "name", .. }));
+    let csv = "name,email,age\nJoey,joey@tribbiani.com,old\n";
+    assert!(matches!(error_kind::<User>(Format::Csv, csv), ErrorKind::MistypedField { field: "age", .. }));
+  }
+
+  #[test]
+  fn out_of_range_fields() {
+    let expected = ErrorKind::OutOfRange { field: "age", value: String::from("256") };
+    let json = r#"[{"name": "Joey", "email": "joey@tribbiani.com", "age": 256}]"#;
+    assert_eq!(error_kind::<User>(Format::Json, json), expected);
+    assert_eq!(error_kind::<User>(Format::Csv, "name,email,age\nJoey,j,256\n"), expected);
+    let expected = ErrorKind::OutOfRange { field: "x", value: String::from("99999999999999999999") };
+    assert_eq!(error_kind::<Point>(Format::Csv, "x,y\n99999999999999999999,0\n"), expected);
+  }
+
+  #[test]
+  fn errors_report_the_line() {
+    let json = "[\n  {\"x\": 1, \"y\": 2},\n  {\"x\": 1}\n]";
+    assert_eq!(Format::Json.read::<Point>(json).err().map(|e| e.line), Some(3));
+    let csv = "x,y\n1,2\n3,4\n5\n";
+    assert_eq!(Format::Csv.read::<Point>(csv).err().map(|e| e.line), Some(4));
+  }
+}
@@ -0,0 +1,34 @@
+// Escaping text for the formats the crate writes out. Shared by the templates, the renderers, the formatter
+// registry and the webhook sink, which all put user supplied text into HTML or JSON.
+
+// "text" as a JSON string literal: quotes, backslashes and control characters are escaped
+pub fn json_string(text: &str) -> String {
+  let mut out = String::from("\"");
+  for ch in text.chars() {
+    match ch {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+// Text safe to put into HTML, as element content or inside a quoted attribute
+pub fn escape_html(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  for ch in text.chars() {
+    match ch {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&#39;"),
+      c => out.push(c),
+    }
+  }
+  out
+}
@@ -0,0 +1,228 @@
+// More types implementing Summary, and a Feed that keeps all of them in one list.
+// A Vec can only hold values of one type, so the feed stores `Box<dyn Summary>` trait objects instead: each
+// box points to some value of a type implementing Summary, and calls on it are looked up at runtime
+// (dynamic dispatch) instead of being picked at compile time like with `impl Summary` / `T: Summary`.
+
+use std::cmp::Ordering;
+
+use crate::{
+  template::{Fields, Value},
+  Summary,
+};
+
+// 2020-01-01 00:00:00 UTC in seconds since the unix epoch, items from then on count as recent
+pub const SINCE_2020: u64 = 1577836800;
+
+pub struct Tweet {
+  pub username: String,
+  pub content: String,
+  pub retweets: u32,
+  // seconds since the unix epoch
+  pub timestamp: u64,
+}
+
+impl Summary for Tweet {
+  fn summarize(&self) -> String {
+    format!("@{}: {} ({} retweets)", self.username, self.content, self.retweets)
+  }
+
+  // The bare username like every other author, the `@` is only part of how a tweet is shown
+  fn summarize_author(&self) -> Option<String> {
+    Some(self.username.clone())
+  }
+
+  fn timestamp(&self) -> u64 {
+    self.timestamp
+  }
+}
+
+pub struct Article {
+  pub headline: String,
+  pub author: String,
+  pub location: String,
+  pub timestamp: u64,
+}
+
+impl Summary for Article {
+  fn summarize(&self) -> String {
+    format!("{}, by {} ({})", self.headline, self.author, self.location)
+  }
+
+  fn summarize_author(&self) -> Option<String> {
+    Some(self.author.clone())
+  }
+
+  fn timestamp(&self) -> u64 {
+    self.timestamp
+  }
+}
+
+pub struct Commit {
+  pub hash: String,
+  pub author: String,
+  pub message: String,
+  pub timestamp: u64,
+}
+
+impl Summary for Commit {
+  fn summarize(&self) -> String {
+    // only the first line of the commit message and the short hash, like `git log --oneline`
+    let subject = self.message.lines().next().unwrap_or_default();
+    let short_hash: String = self.hash.chars().take(7).collect();
+    format!("{short_hash} {subject}")
+  }
+
+  fn summarize_author(&self) -> Option<String> {
+    Some(self.author.clone())
+  }
+
+  fn timestamp(&self) -> u64 {
+    self.timestamp
+  }
+}
+
+#[derive(Default)]
+pub struct Feed {
+  items: Vec<Box<dyn Summary>>,
+}
+
+impl Feed {
+  pub fn new() -> Feed {
+    Feed { items: Vec::new() }
+  }
+
+  // `'static` means the item may not borrow anything short lived, since the feed keeps it around
+  pub fn push(&mut self, item: impl Summary + 'static) {
+    self.items.push(Box::new(item));
+  }
+
+  pub fn len(&self) -> usize {
+    self.items.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.items.is_empty()
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &dyn Summary> {
+    self.items.iter().map(|item| item.as_ref())
+  }
+
+  // Summaries of every item in the current order
+  pub fn summaries(&self) -> Vec<String> {
+    self.iter().map(|item| item.summarize()).collect()
+  }
+
+  pub fn sort_by(&mut self, compare: impl Fn(&dyn Summary, &dyn Summary) -> Ordering) {
+    self.items.sort_by(|a, b| compare(a.as_ref(), b.as_ref()));
+  }
+
+  // Latest items first. The sort is stable, so items with the same timestamp keep their order.
+  pub fn sort_newest_first(&mut self) {
+    self.sort_by(|a, b| b.timestamp().cmp(&a.timestamp()));
+  }
+
+  // Items without an author go last
+  pub fn sort_by_author(&mut self) {
+    let key = |item: &dyn Summary| {
+      let author = item.summarize_author();
+      (author.is_none(), author)
+    };
+    self.sort_by(|a, b| key(a).cmp(&key(b)));
+  }
+
+  // The items for which `keep` returns true, the feed itself is left as it is
+  pub fn filter(&self, keep: impl Fn(&dyn Summary) -> bool) -> Vec<&dyn Summary> {
+    self.iter().filter(|item| keep(*item)).collect()
+  }
+
+  pub fn by_author(&self, author: &str) -> Vec<&dyn Summary> {
+    self.filter(|item| item.summarize_author().as_deref() == Some(author))
+  }
+
+  // Drops the items for which `keep` returns false
+  pub fn retain(&mut self, keep: impl Fn(&dyn Summary) -> bool) {
+    self.items.retain(|item| keep(item.as_ref()));
+  }
+}
+
+// `{count}` and `{items}`, a list of maps with `summary`, `author` (empty when there is none), `timestamp` and
+// `recent` (since 2020)
+impl Fields for Feed {
+  fn field(&self, name: &str) -> Option<Value> {
+    match name {
+      "count" => Some(Value::Integer(self.len() as i64)),
+      "items" => Some(Value::List(
+        self
+          .iter()
+          .map(|item| {
+            Value::Map(vec![
+              (String::from("summary"), Value::Text(item.summarize())),
+              (String::from("author"), Value::Text(item.summarize_author().unwrap_or_default())),
+              (String::from("timestamp"), Value::Integer(item.timestamp() as i64)),
+              (String::from("recent"), Value::Bool(item.timestamp() >= SINCE_2020)),
+            ])
+          })
+          .collect(),
+      )),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::User;
+
+  // One item of every kind, all by the same author, plus a few by others
+  fn mixed_feed() -> Feed {
+    let mut feed = Feed::new();
+    feed.push(Tweet { username: String::from("sanchet"), content: String::from("hi"), retweets: 0, timestamp: 3 });
+    feed.push(Tweet { username: String::from("rustlang"), content: String::from("1.0"), retweets: 9, timestamp: 1 });
+    feed.push(Article {
+      headline: String::from("Traits"),
+      author: String::from("sanchet"),
+      location: String::from("Pune"),
+      timestamp: 2,
+    });
+    feed.push(Commit { hash: String::from("abcdef12"), author: String::from("sanchet"), message: String::from("Fix"), timestamp: 4 });
+    feed.push(Commit { hash: String::from("12345678"), author: String::from("@sanchet"), message: String::from("Other"), timestamp: 5 });
+    feed.push(User { name: String::from("sanchet"), age: 23 });
+    feed
+  }
+
+  #[test]
+  fn by_author_finds_every_kind_of_item() {
+    let feed = mixed_feed();
+    let found: Vec<String> = feed.by_author("sanchet").iter().map(|item| item.summarize()).collect();
+    assert_eq!(
+      found,
+      vec!["@sanchet: hi (0 retweets)", "Traits, by sanchet (Pune)", "abcdef1 Fix", "User has name sanchet and age 23"]
+    );
+    // the @ is only part of how a tweet is shown, so this finds the one author really named "@sanchet"
+    assert_eq!(feed.by_author("@sanchet").len(), 1);
+    assert_eq!(feed.by_author("rustlang").len(), 1);
+    assert!(feed.by_author("nobody").is_empty());
+  }
+
+  #[test]
+  fn sorting_keeps_items_without_an_author_last() {
+    struct Anonymous;
+    impl Summary for Anonymous {
+      fn summarize(&self) -> String {
+        String::from("anonymous")
+      }
+    }
+    let mut feed = mixed_feed();
+    feed.push(Anonymous);
+    feed.sort_by_author();
+    let authors: Vec<Option<String>> = feed.iter().map(|item| item.summarize_author()).collect();
+    assert_eq!(authors.first(), Some(&Some(String::from("@sanchet"))));
+    assert_eq!(authors.last(), Some(&None));
+
+    feed.sort_newest_first();
+    let timestamps: Vec<u64> = feed.iter().map(|item| item.timestamp()).collect();
+    assert_eq!(timestamps, vec![5, 4, 3, 2, 1, 0, 0]);
+  }
+}
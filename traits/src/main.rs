@@ -4,14 +4,57 @@
 
 // Whatever struct implements a trait, it must have a definition for the function specified in the trait
 
+mod escape;
+mod feed;
+mod notifier;
+mod registry;
+mod render;
+mod template;
+mod unicode;
+mod webhook;
+
+use std::{env, process};
+
+use feed::{Article, Commit, Feed, Tweet, SINCE_2020};
+use summary_derive::Summary;
+use template::{Escape, Fields, Template, Value};
+use unicode::Measure;
+use notifier::{FileSink, FlakySink, MemorySink, Notifier, RouteOptions, StdoutSink};
+use registry::Registry;
+use render::{Format, Render};
+use webhook::{WebhookServer, WebhookSink};
+
 pub trait Summary {
   fn summarize(&self) -> String;
+
+  // Default methods, implementors only override them when they have something better to offer.
+  // None when the item doesn't say who wrote it.
+  fn summarize_author(&self) -> Option<String> {
+    None
+  }
+
+  // When the item was created, in seconds since the unix epoch. Used to sort feeds.
+  fn timestamp(&self) -> u64 {
+    0
+  }
+
+  // The summary cut to at most `max_chars` characters, ending in "…" when something was cut off.
+  // Characters here are what a reader sees as one character, an emoji with a skin tone counts once.
+  fn summarize_limited(&self, max_chars: usize) -> String {
+    self.summarize_limited_by(max_chars, Measure::Graphemes)
+  }
+
+  // Same, but `max` can also be the number of terminal columns (Measure::DisplayWidth), where CJK and emoji
+  // take two columns each
+  fn summarize_limited_by(&self, max: usize, measure: Measure) -> String {
+    unicode::truncate(&self.summarize(), max, measure)
+  }
 }
 
 // This has a default implementation of summarize method. If the struct has an implementation that defines this method, it will override the default method otherwise the struct will still have this method even though it need not be implemented explicitly.
 pub trait SummaryDefault {
   fn summarize_default(&self) -> String {
-    return String::from("Summarize");
+    String::from("Summarize")
   }
 }
 
@@ -23,26 +66,192 @@ struct User {
 // User struct implements the Summary trait
 impl Summary for User {
   fn summarize(&self) -> String {
-    return format!("User has name {} and age {}", self.name, self.age);
+    format!("User has name {} and age {}", self.name, self.age)
+  }
+
+  fn summarize_author(&self) -> Option<String> {
+    Some(self.name.clone())
   }
 }
 
 impl SummaryDefault for User {}
 
+// Lets templates use `{name}` and `{age}`
+impl Fields for User {
+  fn field(&self, name: &str) -> Option<Value> {
+    match name {
+      "name" => Some(Value::Text(self.name.clone())),
+      "age" => Some(Value::Integer(self.age as i64)),
+      _ => None,
+    }
+  }
+}
+
+// The same user in every output format, the name goes through String's impl so it is escaped for each one
+impl Render for User {
+  fn render(&self, format: Format) -> String {
+    let name = self.name.render(format);
+    match format {
+      Format::Plain => format!("{name} ({})", self.age),
+      Format::Markdown => format!("**{name}**, {} years old", self.age),
+      Format::Html => format!("<span class=\"user\"><strong>{name}</strong> ({})</span>", self.age),
+      Format::Json => format!("{{\"name\": {name}, \"age\": {}}}", self.age),
+    }
+  }
+}
+
+// The derive macro from the summary-derive crate writes the `impl Summary` block.
+// It only generates `summarize`, the other methods keep their defaults.
+#[derive(Summary)]
+#[summary(template = "{title} by {author}, {pages} pages")]
+struct Book {
+  title: String,
+  author: String,
+  pages: u32,
+}
+
+// Without a template every field is listed: "Account: login = sanchet, plan = free"
+#[derive(Summary)]
+struct Account {
+  #[summary(rename = "login")]
+  name: String,
+  plan: String,
+  #[summary(skip)]
+  _password_hash: String,
+}
+
 // Traits as parameters
 // When we want to pass a struct reference to a function but only those struct instances must be passed that implement the given trait
 pub fn notify(item: &impl Summary) -> String {
-  return format!("Notification: {}", item.summarize());
+  format!("Notification: {}", item.summarize())
 }
 
 // `impl Trait` is actually syntax sugar for `trait bounds`
 // The following syntax explanation is: define a function `notify_trait_bound` which takes in an argument `item` of a generic type T however T is constrained on implementing the Summary and SummaryDefault traits, i.e. only accept instances of that struct which has implemented the Summary and SummaryDefault traits.
 // The generic T is bound to the traits Summary and SummaryDefault
 pub fn notify_trait_bound<T: Summary + SummaryDefault>(item: &T) -> String {
-  return format!("Trait Bound Notification: {}", item.summarize());
+  format!("Trait Bound Notification: {}", item.summarize())
+}
+
+// A few items of different types, used by the demo and the `format` command
+fn sample_feed() -> Feed {
+  let mut feed = Feed::new();
+  feed.push(Tweet {
+    username: String::from("rustlang"),
+    content: String::from("Rust 1.0 is here!"),
+    retweets: 1500,
+    timestamp: 1431648000,
+  });
+  feed.push(Article {
+    headline: String::from("Penguins win the Stanley Cup Championship!"),
+    author: String::from("Iceburgh"),
+    location: String::from("Pittsburgh, PA, USA"),
+    timestamp: 1465430400,
+  });
+  feed.push(Commit {
+    hash: String::from("4cda6e4f0b1c2d3e"),
+    author: String::from("sanchet"),
+    message: String::from("Add traits examples\n\nShows trait objects and trait bounds."),
+    timestamp: 1700000000,
+  });
+  feed.push(Tweet {
+    username: String::from("sanchet"),
+    content: String::from("learning about trait objects"),
+    retweets: 2,
+    timestamp: 1700000100,
+  });
+  feed
+}
+
+// `cargo run -- format [--format <names>]` prints the sample feed with a chain of formatters, like
+// `--format dated,summary`. Without the flag the chain is read from SUMMARY_FORMAT, and defaults to `summary`.
+fn run_format(args: &[String]) -> Result<(), String> {
+  let registry = Registry::with_builtins();
+  let spec = match args {
+    [] => env::var("SUMMARY_FORMAT").unwrap_or_else(|_| String::from("summary")),
+    [flag, spec] if flag == "--format" => spec.clone(),
+    _ => return Err(String::from("usage: traits format [--format <name>[,<fallback>...]]")),
+  };
+  let chain = registry.chain(&spec)?;
+  let mut feed = sample_feed();
+  feed.push(User { name: String::from("sanchet"), age: 23 });
+  for item in feed.iter() {
+    println!("{}", registry.format(&chain, item)?);
+  }
+  Ok(())
+}
+
+// Sends the sample feed through a notifier. It writes a log file to the temp dir and starts a local webhook
+// server, so it only runs when asked for.
+fn run_notify() -> Result<(), String> {
+  let feed = sample_feed();
+  // Everything goes to stdout, only sanchet's items to the file, and recent items are posted to the local
+  // webhook three at a time
+  let log_path = std::env::temp_dir().join("traits_notifications.log");
+  let log_path = log_path.to_string_lossy().into_owned();
+  let _ = std::fs::remove_file(&log_path);
+  let server = WebhookServer::start().map_err(|e| format!("could not start the local webhook server: {e}"))?;
+  let memory = MemorySink::new();
+  let received = memory.received();
+
+  let mut notifier = Notifier::new();
+  notifier.add_sink(StdoutSink, RouteOptions::default());
+  notifier.add_route(
+    FileSink { path: log_path.clone() },
+    |item| item.summarize_author().as_deref() == Some("sanchet"),
+    RouteOptions::default(),
+  );
+  notifier.add_route(
+    WebhookSink { addr: server.addr(), path: String::from("/hooks/feed") },
+    |item| item.timestamp() >= SINCE_2020,
+    RouteOptions { batch_size: 3, ..RouteOptions::default() },
+  );
+  // fails twice, the third attempt gets through
+  notifier.add_sink(FlakySink { inner: memory, failures: 2 }, RouteOptions { batch_size: 2, max_attempts: 3, ..RouteOptions::default() });
+  // never gets through, the batch comes back as an error
+  notifier.add_sink(FlakySink { inner: MemorySink::new(), failures: u32::MAX }, RouteOptions { batch_size: 10, max_attempts: 2, ..RouteOptions::default() });
+
+  for item in feed.iter() {
+    if let Err(errors) = notifier.notify(item) {
+      for e in errors {
+        eprintln!("{} failed after {} attempts: {}", e.sink, e.attempts, e.error);
+      }
+    }
+  }
+  if let Err(errors) = notifier.flush() {
+    for e in errors {
+      println!("{} gave up after {} attempts ({}), {} notifications not delivered", e.sink, e.attempts, e.error, e.notifications.len());
+    }
+  }
+
+  println!("memory sink got {} notifications", received.borrow().len());
+  let log = std::fs::read_to_string(&log_path).unwrap_or_default();
+  println!("{log_path} has {} lines", log.lines().count());
+  for body in server.received() {
+    println!("webhook received {body}");
+  }
+  Ok(())
 }
 
 fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  if !args.is_empty() {
+    let result = match args.as_slice() {
+      [command] if command == "list" => {
+        print!("{}", Registry::with_builtins().list());
+        Ok(())
+      },
+      [command, rest @ ..] if command == "format" => run_format(rest),
+      [command] if command == "notify" => run_notify(),
+      _ => Err(String::from("usage: traits list | traits format [--format <name>[,<fallback>...]] | traits notify")),
+    };
+    if let Err(e) = result {
+      eprintln!("error: {e}");
+      process::exit(1);
+    }
+    return;
+  }
+
   let user = User {
     name: String::from("sanchet"),
     age: 23
@@ -55,4 +264,127 @@ fn main() {
 
   let notification_trait_bound = notify_trait_bound(&user);
   println!("{notification_trait_bound}");
-}
\ No newline at end of file
+
+  // summarize_default always says "Summarize", a template decides the text at runtime instead
+  println!("{} -> {}", user.summarize_default(), template::render("{name} is {age} years old", &user).unwrap());
+  let adult = Template::parse("{name} is {#if !age}a newborn{#else}{age} years old{/if}").unwrap();
+  println!("{}", adult.render(&user).unwrap());
+  let html = Template::parse("<b>{name}</b>").unwrap().escape(Escape::Html);
+  println!("{}", html.render(&User { name: String::from("Tom & <Jerry>"), age: 80 }).unwrap());
+  for bad in ["{name} is {years} years old", "{#if age}{age}", "{name}}", "{#each name}{.}{/each}"] {
+    match template::render(bad, &user) {
+      Ok(text) => println!("{bad:?} rendered as {text:?}"),
+      Err(e) => println!("{bad:?}: {e}"),
+    }
+  }
+
+  let book = Book { title: String::from("The Rust Programming Language"), author: String::from("Klabnik and Nichols"), pages: 560 };
+  let account = Account { name: String::from("sanchet"), plan: String::from("free"), _password_hash: String::from("5f4dcc3b") };
+  println!("{}", notify(&book));
+  println!("{}", notify(&account));
+
+  // Short summaries, never cutting a character in half
+  println!("{}", book.summarize_limited(24));
+  let greeting = Tweet {
+    username: String::from("yuki"),
+    content: String::from("こんにちは世界 👋🏽 🇯🇵 café"),
+    retweets: 0,
+    timestamp: 0,
+  };
+  println!("{}", greeting.summarize());
+  for max in [12, 16, 20] {
+    let by_chars = greeting.summarize_limited(max);
+    let by_width = greeting.summarize_limited_by(max, Measure::DisplayWidth);
+    println!("{max:>2} characters: {by_chars}\n{max:>2} columns:    {by_width} ({} wide)", unicode::display_width(&by_width));
+  }
+
+  // One value, four formats. Vec and Option get Render from the blanket impls in render.rs.
+  let team = vec![
+    Some(User { name: String::from("sanchet"), age: 23 }),
+    None,
+    Some(User { name: String::from("<ferris>"), age: 9 }),
+  ];
+  for format in Format::ALL {
+    println!("{format:?}:\n{}", team.render(format));
+  }
+  println!("{}", user.render(Format::Json));
+
+  // A feed holding different types that all implement Summary
+  let mut feed = sample_feed();
+  feed.push(user);
+  feed.sort_newest_first();
+  println!("feed has {} items, newest first:", feed.len());
+  for summary in feed.summaries() {
+    println!("  {summary}");
+  }
+
+  feed.sort_by_author();
+  println!("sorted by author: {:?}", feed.iter().map(|item| item.summarize_author()).collect::<Vec<_>>());
+
+  for item in feed.by_author("sanchet") {
+    println!("by sanchet: {}", item.summarize());
+  }
+  let recent = feed.filter(|item| item.timestamp() >= SINCE_2020);
+  println!("{} items since 2020", recent.len());
+
+  // Feed exposes its items to templates as a list of maps
+  let listing = Template::parse("<h1>{count} items</h1>\n{#each items}<li>{@index}. {summary} <i>{author}</i>{#if recent} (new){/if}</li>\n{/each}")
+    .unwrap()
+    .escape(Escape::Html);
+  print!("{}", listing.render(&feed).unwrap());
+
+  // Formatters picked by name, with fallbacks for items a formatter can't handle
+  let mut registry = Registry::with_builtins();
+  registry.register("upper", "the summary in capitals", |item| Ok(item.summarize().to_uppercase())).unwrap();
+  if let Err(e) = registry.register("short", "another formatter called short", |item| Ok(item.summarize())) {
+    println!("not registered: {e}");
+  }
+  let chain = registry.chain("dated, author, upper").unwrap();
+  for item in feed.iter() {
+    println!("{}", registry.format(&chain, item).unwrap());
+  }
+  println!("{}", registry.chain("dated,fancy").unwrap_err());
+  // a Book has neither a timestamp nor an author, so only the last formatter of the chain works for it
+  let untitled = Book { title: String::from("Untitled"), author: String::from("nobody"), pages: 0 };
+  println!("{}", registry.format(&chain, &untitled).unwrap());
+  println!("{}", registry.format(&registry.chain("dated").unwrap(), &untitled).unwrap_err());
+
+  feed.retain(|item| item.timestamp() > 0);
+  println!("items with a timestamp: {}, feed empty: {}", feed.len(), feed.is_empty());
+
+  // `cargo run -- notify` sends the feed through a notifier, writing a log file and starting a local webhook server
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn book() -> Book {
+    Book { title: String::from("The Rust Programming Language"), author: String::from("Klabnik and Nichols"), pages: 560 }
+  }
+
+  #[test]
+  fn derived_summary_fills_in_the_template() {
+    assert_eq!(book().summarize(), "The Rust Programming Language by Klabnik and Nichols, 560 pages");
+    let braces = Book { title: String::from("{title}"), author: String::from("}{"), pages: 0 };
+    // values are put in as they are, a placeholder inside a value is not replaced again
+    assert_eq!(braces.summarize(), "{title} by }{, 0 pages");
+  }
+
+  #[test]
+  fn derived_summary_lists_renamed_fields_and_skips_hidden_ones() {
+    let account = Account { name: String::from("sanchet"), plan: String::from("free"), _password_hash: String::from("5f4dcc3b") };
+    assert_eq!(account.summarize(), "Account: login = sanchet, plan = free");
+    assert!(!account.summarize().contains("5f4dcc3b"));
+  }
+
+  #[test]
+  fn derived_types_keep_the_default_methods() {
+    let book = book();
+    assert_eq!(book.summarize_author(), None);
+    assert_eq!(book.timestamp(), 0);
+    assert_eq!(book.summarize_limited(24), "The Rust Programming La…");
+    assert_eq!(book.summarize_limited(100), book.summarize());
+    assert_eq!(notify(&book), "Notification: The Rust Programming Language by Klabnik and Nichols, 560 pages");
+  }
+}
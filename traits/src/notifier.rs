@@ -0,0 +1,370 @@
+// Sending Summary items to places where people read them.
+// Every destination implements the Sink trait. A Notifier holds a list of routes, each route being a sink plus
+// a filter deciding which items go to it. Items are collected per route and handed to the sink in batches,
+// and a batch the sink fails to take is retried a few times, waiting longer before each attempt, before it is
+// given back to the caller.
+
+use std::{
+  cell::RefCell,
+  fs::OpenOptions,
+  io::Write,
+  rc::Rc,
+  thread,
+  time::Duration,
+};
+
+use crate::Summary;
+
+// What a sink receives: the Summary item turned into plain data, since the item itself is only borrowed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+  pub author: Option<String>,
+  pub timestamp: u64,
+  pub text: String,
+}
+
+impl Notification {
+  pub fn from_item(item: &dyn Summary) -> Notification {
+    Notification { author: item.summarize_author(), timestamp: item.timestamp(), text: item.summarize() }
+  }
+}
+
+pub trait Sink {
+  fn name(&self) -> String;
+
+  // Delivers the whole batch or fails, a failed batch is sent again in full
+  fn deliver(&mut self, batch: &[Notification]) -> Result<(), String>;
+}
+
+// Prints every notification on its own line
+pub struct StdoutSink;
+
+impl Sink for StdoutSink {
+  fn name(&self) -> String {
+    String::from("stdout")
+  }
+
+  fn deliver(&mut self, batch: &[Notification]) -> Result<(), String> {
+    for notification in batch {
+      match &notification.author {
+        Some(author) => println!("[{author}] {}", notification.text),
+        None => println!("{}", notification.text),
+      }
+    }
+    Ok(())
+  }
+}
+
+// Appends one line per notification to a file, creating it if needed
+pub struct FileSink {
+  pub path: String,
+}
+
+impl Sink for FileSink {
+  fn name(&self) -> String {
+    format!("file {}", self.path)
+  }
+
+  fn deliver(&mut self, batch: &[Notification]) -> Result<(), String> {
+    let mut lines = String::new();
+    for notification in batch {
+      let author = notification.author.as_deref().unwrap_or_default();
+      lines.push_str(&format!("{}\t{}\t{}\n", notification.timestamp, author, notification.text));
+    }
+    // written in one go so a failure can't leave half a batch in the file
+    let mut file = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.path)
+      .map_err(|e| format!("could not open {}: {e}", self.path))?;
+    file.write_all(lines.as_bytes()).map_err(|e| format!("could not write to {}: {e}", self.path))
+  }
+}
+
+// Keeps notifications in memory. The sink is moved into the Notifier, so the list lives behind an
+// `Rc<RefCell<..>>` and `received()` hands out a second handle to it that can be looked at later.
+#[derive(Default)]
+pub struct MemorySink {
+  received: Rc<RefCell<Vec<Notification>>>,
+}
+
+impl MemorySink {
+  pub fn new() -> MemorySink {
+    MemorySink::default()
+  }
+
+  pub fn received(&self) -> Rc<RefCell<Vec<Notification>>> {
+    Rc::clone(&self.received)
+  }
+}
+
+impl Sink for MemorySink {
+  fn name(&self) -> String {
+    String::from("memory")
+  }
+
+  fn deliver(&mut self, batch: &[Notification]) -> Result<(), String> {
+    self.received.borrow_mut().extend_from_slice(batch);
+    Ok(())
+  }
+}
+
+// Wraps another sink and makes its first `failures` deliveries fail, to try out the retry logic
+pub struct FlakySink<S: Sink> {
+  pub inner: S,
+  pub failures: u32,
+}
+
+impl<S: Sink> Sink for FlakySink<S> {
+  fn name(&self) -> String {
+    format!("flaky {}", self.inner.name())
+  }
+
+  fn deliver(&mut self, batch: &[Notification]) -> Result<(), String> {
+    if self.failures > 0 {
+      self.failures -= 1;
+      return Err(String::from("simulated failure"));
+    }
+    self.inner.deliver(batch)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteOptions {
+  // how many notifications are collected before they are delivered together
+  pub batch_size: usize,
+  // how many times a batch is offered to the sink before giving up
+  pub max_attempts: u32,
+  // the wait before the second attempt, it doubles for every attempt after that so a struggling sink
+  // isn't hammered with the same batch
+  pub retry_delay: Duration,
+}
+
+impl Default for RouteOptions {
+  fn default() -> RouteOptions {
+    RouteOptions { batch_size: 1, max_attempts: 3, retry_delay: Duration::from_millis(50) }
+  }
+}
+
+// A batch the sink still refused after every attempt
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryError {
+  pub sink: String,
+  pub attempts: u32,
+  pub error: String,
+  pub notifications: Vec<Notification>,
+}
+
+// Decides whether an item goes to a route's sink
+type Filter = Box<dyn Fn(&dyn Summary) -> bool>;
+
+struct Route {
+  sink: Box<dyn Sink>,
+  filter: Filter,
+  options: RouteOptions,
+  pending: Vec<Notification>,
+}
+
+impl Route {
+  fn flush(&mut self) -> Result<(), DeliveryError> {
+    if self.pending.is_empty() {
+      return Ok(());
+    }
+    let batch = std::mem::take(&mut self.pending);
+    let attempts = self.options.max_attempts.max(1);
+    let mut last_error = String::new();
+    let mut delay = self.options.retry_delay;
+    for attempt in 0..attempts {
+      if attempt > 0 {
+        thread::sleep(delay);
+        delay = delay.saturating_mul(2);
+      }
+      match self.sink.deliver(&batch) {
+        Ok(()) => return Ok(()),
+        Err(e) => last_error = e,
+      }
+    }
+    Err(DeliveryError { sink: self.sink.name(), attempts, error: last_error, notifications: batch })
+  }
+}
+
+#[derive(Default)]
+pub struct Notifier {
+  routes: Vec<Route>,
+}
+
+impl Notifier {
+  pub fn new() -> Notifier {
+    Notifier::default()
+  }
+
+  // Items for which `filter` returns true are sent to `sink`
+  pub fn add_route(
+    &mut self,
+    sink: impl Sink + 'static,
+    filter: impl Fn(&dyn Summary) -> bool + 'static,
+    options: RouteOptions,
+  ) {
+    self.routes.push(Route { sink: Box::new(sink), filter: Box::new(filter), options, pending: Vec::new() });
+  }
+
+  // Same as add_route for a sink that should get every item
+  pub fn add_sink(&mut self, sink: impl Sink + 'static, options: RouteOptions) {
+    self.add_route(sink, |_| true, options);
+  }
+
+  // Queues the item on every route that accepts it and delivers the routes whose batch is now full
+  pub fn notify(&mut self, item: &dyn Summary) -> Result<(), Vec<DeliveryError>> {
+    let notification = Notification::from_item(item);
+    let mut errors = Vec::new();
+    for route in self.routes.iter_mut().filter(|route| (route.filter)(item)) {
+      route.pending.push(notification.clone());
+      if route.pending.len() >= route.options.batch_size {
+        if let Err(e) = route.flush() {
+          errors.push(e);
+        }
+      }
+    }
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      Err(errors)
+    }
+  }
+
+  // Delivers whatever is still waiting for its batch to fill up
+  pub fn flush(&mut self) -> Result<(), Vec<DeliveryError>> {
+    let errors: Vec<DeliveryError> = self.routes.iter_mut().filter_map(|route| route.flush().err()).collect();
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      Err(errors)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Instant;
+
+  use super::*;
+  use crate::feed::Tweet;
+
+  fn tweet(username: &str, timestamp: u64) -> Tweet {
+    Tweet { username: String::from(username), content: format!("tweet {timestamp}"), retweets: 0, timestamp }
+  }
+
+  // No waiting between attempts, so the tests stay fast
+  fn options(batch_size: usize, max_attempts: u32) -> RouteOptions {
+    RouteOptions { batch_size, max_attempts, retry_delay: Duration::ZERO }
+  }
+
+  // A sink recording every batch it was offered, failing the first `failures` of them
+  struct Recorder {
+    failures: u32,
+    offered: Rc<RefCell<Vec<Vec<u64>>>>,
+  }
+
+  impl Sink for Recorder {
+    fn name(&self) -> String {
+      String::from("recorder")
+    }
+
+    fn deliver(&mut self, batch: &[Notification]) -> Result<(), String> {
+      self.offered.borrow_mut().push(batch.iter().map(|n| n.timestamp).collect());
+      if self.failures > 0 {
+        self.failures -= 1;
+        return Err(format!("{} failures to go", self.failures));
+      }
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn batches_are_delivered_once_full() {
+    let sink = MemorySink::new();
+    let received = sink.received();
+    let mut notifier = Notifier::new();
+    notifier.add_sink(sink, options(3, 1));
+
+    for timestamp in 1..=7 {
+      notifier.notify(&tweet("a", timestamp)).unwrap();
+      // nothing arrives until a batch is full
+      assert_eq!(received.borrow().len(), timestamp as usize / 3 * 3);
+    }
+    notifier.flush().unwrap();
+    let timestamps: Vec<u64> = received.borrow().iter().map(|n| n.timestamp).collect();
+    assert_eq!(timestamps, (1..=7).collect::<Vec<_>>());
+    // flushing again has nothing left to send
+    notifier.flush().unwrap();
+    assert_eq!(received.borrow().len(), 7);
+  }
+
+  #[test]
+  fn filters_drop_items_that_do_not_match() {
+    let (everything, only_b) = (MemorySink::new(), MemorySink::new());
+    let (all_received, b_received) = (everything.received(), only_b.received());
+    let mut notifier = Notifier::new();
+    notifier.add_sink(everything, options(1, 1));
+    notifier.add_route(only_b, |item| item.summarize_author().as_deref() == Some("b"), options(1, 1));
+
+    for (author, timestamp) in [("a", 1), ("b", 2), ("c", 3), ("b", 4)] {
+      notifier.notify(&tweet(author, timestamp)).unwrap();
+    }
+    assert_eq!(all_received.borrow().len(), 4);
+    let expected = vec![
+      Notification { author: Some(String::from("b")), timestamp: 2, text: String::from("@b: tweet 2 (0 retweets)") },
+      Notification { author: Some(String::from("b")), timestamp: 4, text: String::from("@b: tweet 4 (0 retweets)") },
+    ];
+    assert_eq!(*b_received.borrow(), expected);
+  }
+
+  #[test]
+  fn a_failing_sink_is_retried_then_reported() {
+    let offered = Rc::new(RefCell::new(Vec::new()));
+    let mut notifier = Notifier::new();
+    // fails twice, the third attempt gets through
+    notifier.add_sink(Recorder { failures: 2, offered: Rc::clone(&offered) }, options(2, 3));
+    notifier.notify(&tweet("a", 1)).unwrap();
+    notifier.notify(&tweet("a", 2)).unwrap();
+    assert_eq!(*offered.borrow(), vec![vec![1, 2]; 3]);
+
+    // fails more often than it is tried, the whole batch comes back
+    let offered = Rc::new(RefCell::new(Vec::new()));
+    let mut notifier = Notifier::new();
+    notifier.add_sink(Recorder { failures: 5, offered: Rc::clone(&offered) }, options(2, 3));
+    notifier.notify(&tweet("a", 1)).unwrap();
+    let errors = notifier.notify(&tweet("a", 2)).unwrap_err();
+    assert_eq!(offered.borrow().len(), 3);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].sink, "recorder");
+    assert_eq!(errors[0].attempts, 3);
+    assert_eq!(errors[0].error, "2 failures to go");
+    assert_eq!(errors[0].notifications.iter().map(|n| n.timestamp).collect::<Vec<_>>(), vec![1, 2]);
+
+    // the failed batch is not kept around, the next one starts empty
+    notifier.notify(&tweet("a", 3)).unwrap();
+    notifier.flush().unwrap();
+    assert_eq!(offered.borrow().last(), Some(&vec![3]));
+  }
+
+  #[test]
+  fn zero_attempts_still_tries_once() {
+    let mut notifier = Notifier::new();
+    notifier.add_sink(FlakySink { inner: MemorySink::new(), failures: 1 }, options(1, 0));
+    let errors = notifier.notify(&tweet("a", 1)).unwrap_err();
+    assert_eq!((errors[0].sink.as_str(), errors[0].attempts), ("flaky memory", 1));
+  }
+
+  #[test]
+  fn retries_wait_longer_each_time() {
+    let offered = Rc::new(RefCell::new(Vec::new()));
+    let mut notifier = Notifier::new();
+    let retry_delay = Duration::from_millis(10);
+    notifier.add_sink(Recorder { failures: 3, offered }, RouteOptions { batch_size: 1, max_attempts: 4, retry_delay });
+    let started = Instant::now();
+    notifier.notify(&tweet("a", 1)).unwrap();
+    // 10 + 20 + 40 ms between the four attempts
+    assert!(started.elapsed() >= retry_delay * 7, "{:?}", started.elapsed());
+  }
+}
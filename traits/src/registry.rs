@@ -0,0 +1,234 @@
+// Summary formatters registered under a name, so which one is used can be decided at runtime
+// (`cargo run -- format --format dated,summary`) instead of being fixed in the code.
+// A formatter may fail for an item, for example when the item has no timestamp to show. Formatters are therefore
+// picked as a chain: the first one that succeeds is used, and the later ones are fallbacks.
+
+use crate::{escape::json_string, Summary};
+
+pub type Formatter = Box<dyn Fn(&dyn Summary) -> Result<String, String>>;
+
+// The formatters below are plain functions
+type Builtin = fn(&dyn Summary) -> Result<String, String>;
+
+struct Entry {
+  name: String,
+  description: String,
+  formatter: Formatter,
+}
+
+#[derive(Default)]
+pub struct Registry {
+  // kept in registration order, which is also the order `list` shows them in
+  entries: Vec<Entry>,
+}
+
+impl Registry {
+  pub fn new() -> Registry {
+    Registry::default()
+  }
+
+  // A registry holding the formatters defined at the bottom of this file
+  pub fn with_builtins() -> Registry {
+    let mut registry = Registry::new();
+    let builtins: [(&str, &str, Builtin); 5] = [
+      ("summary", "the item's own summary", |item| Ok(item.summarize())),
+      ("short", "the summary cut to 40 characters", |item| Ok(item.summarize_limited(40))),
+      ("author", "the author in front of the summary", format_author),
+      ("dated", "the date in front of the summary", format_dated),
+      ("json", "author, timestamp and summary as a JSON object", format_json),
+    ];
+    for (name, description, formatter) in builtins {
+      registry.register(name, description, formatter).expect("builtin formatter names are unique");
+    }
+    registry
+  }
+
+  // Fails when the name is taken, the formatter registered first stays
+  pub fn register(
+    &mut self,
+    name: &str,
+    description: &str,
+    formatter: impl Fn(&dyn Summary) -> Result<String, String> + 'static,
+  ) -> Result<(), String> {
+    if name.is_empty() || name.contains(',') || name.contains(char::is_whitespace) {
+      return Err(format!("`{name}` can't be used as a formatter name, it must be one word without commas"));
+    }
+    if self.entries.iter().any(|entry| entry.name == name) {
+      return Err(format!("a formatter named `{name}` is already registered"));
+    }
+    self.entries.push(Entry {
+      name: name.to_string(),
+      description: description.to_string(),
+      formatter: Box::new(formatter),
+    });
+    Ok(())
+  }
+
+  pub fn names(&self) -> Vec<&str> {
+    self.entries.iter().map(|entry| entry.name.as_str()).collect()
+  }
+
+  // One line per formatter with its description, for the `list` command
+  pub fn list(&self) -> String {
+    let width = self.entries.iter().map(|entry| entry.name.len()).max().unwrap_or(0);
+    self.entries.iter().map(|entry| format!("{:<width$}  {}\n", entry.name, entry.description)).collect()
+  }
+
+  // Turns "dated, summary" into the names of the chain, checking they are all registered
+  pub fn chain<'a>(&self, spec: &'a str) -> Result<Vec<&'a str>, String> {
+    let names: Vec<&str> = spec.split(',').map(str::trim).filter(|name| !name.is_empty()).collect();
+    if names.is_empty() {
+      return Err(String::from("no formatter given"));
+    }
+    for name in &names {
+      if !self.names().contains(name) {
+        return Err(format!("unknown formatter `{name}`, available: {}", self.names().join(", ")));
+      }
+    }
+    Ok(names)
+  }
+
+  // Runs the formatters of the chain in order and returns the first success.
+  // When all of them fail the error lists why each one did.
+  pub fn format(&self, chain: &[&str], item: &dyn Summary) -> Result<String, String> {
+    let mut failures = Vec::new();
+    for name in chain {
+      match self.entries.iter().find(|entry| entry.name == *name) {
+        Some(entry) => match (entry.formatter)(item) {
+          Ok(text) => return Ok(text),
+          Err(e) => failures.push(format!("{name}: {e}")),
+        },
+        None => failures.push(format!("{name}: not registered")),
+      }
+    }
+    Err(format!("every formatter failed ({})", failures.join("; ")))
+  }
+}
+
+fn format_author(item: &dyn Summary) -> Result<String, String> {
+  match item.summarize_author() {
+    None => Err(String::from("the author is unknown")),
+    Some(author) => Ok(format!("{author}: {}", item.summarize())),
+  }
+}
+
+fn format_dated(item: &dyn Summary) -> Result<String, String> {
+  match item.timestamp() {
+    0 => Err(String::from("the item has no timestamp")),
+    timestamp => Ok(format!("[{}] {}", date(timestamp), item.summarize())),
+  }
+}
+
+fn format_json(item: &dyn Summary) -> Result<String, String> {
+  Ok(format!(
+    "{{\"author\": {}, \"timestamp\": {}, \"summary\": {}}}",
+    item.summarize_author().map_or_else(|| String::from("null"), |author| json_string(&author)),
+    item.timestamp(),
+    json_string(&item.summarize())
+  ))
+}
+
+// Unix timestamp to YYYY-MM-DD (UTC), using Howard Hinnant's days-to-civil algorithm
+fn date(timestamp: u64) -> String {
+  let days = (timestamp / 86_400) as i64 + 719_468;
+  let era = days / 146_097;
+  let day_of_era = days - era * 146_097;
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month_index = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+  let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+  format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::feed::{Commit, Tweet};
+
+  // Has neither an author nor a timestamp
+  struct Note;
+
+  impl Summary for Note {
+    fn summarize(&self) -> String {
+      String::from("a note")
+    }
+  }
+
+  fn tweet() -> Tweet {
+    Tweet { username: String::from("rustlang"), content: String::from("1.0"), retweets: 7, timestamp: 1431648000 }
+  }
+
+  #[test]
+  fn duplicate_names_are_rejected_and_the_first_stays() {
+    let mut registry = Registry::with_builtins();
+    assert_eq!(
+      registry.register("summary", "shouting", |item| Ok(item.summarize().to_uppercase())),
+      Err(String::from("a formatter named `summary` is already registered"))
+    );
+    assert_eq!(registry.format(&["summary"], &Note), Ok(String::from("a note")));
+    assert_eq!(registry.names(), vec!["summary", "short", "author", "dated", "json"]);
+
+    registry.register("upper", "shouting", |item| Ok(item.summarize().to_uppercase())).unwrap();
+    assert!(registry.register("upper", "again", |item| Ok(item.summarize())).is_err());
+    assert_eq!(registry.format(&["upper"], &Note), Ok(String::from("A NOTE")));
+    assert!(registry.list().ends_with("upper    shouting\n"));
+  }
+
+  #[test]
+  fn names_must_be_one_word() {
+    let mut registry = Registry::new();
+    for bad in ["", "a,b", "two words", "tab\t"] {
+      assert!(registry.register(bad, "", |item| Ok(item.summarize())).is_err(), "{bad:?}");
+    }
+    assert!(registry.names().is_empty());
+  }
+
+  #[test]
+  fn chains_are_checked_against_the_registered_names() {
+    let registry = Registry::with_builtins();
+    assert_eq!(registry.chain(" dated ,summary,, "), Ok(vec!["dated", "summary"]));
+    assert_eq!(registry.chain(" , "), Err(String::from("no formatter given")));
+    assert_eq!(
+      registry.chain("dated,fancy"),
+      Err(String::from("unknown formatter `fancy`, available: summary, short, author, dated, json"))
+    );
+  }
+
+  #[test]
+  fn the_first_formatter_that_succeeds_is_used() {
+    let registry = Registry::with_builtins();
+    let chain = registry.chain("dated,author,summary").unwrap();
+    assert_eq!(registry.format(&chain, &tweet()), Ok(String::from("[2015-05-15] @rustlang: 1.0 (7 retweets)")));
+    // no timestamp, falls back to the author
+    let commit = Commit { hash: String::from("abc"), author: String::from("sanchet"), message: String::from("Fix"), timestamp: 0 };
+    assert_eq!(registry.format(&chain, &commit), Ok(String::from("sanchet: abc Fix")));
+    // neither, falls back to the plain summary
+    assert_eq!(registry.format(&chain, &Note), Ok(String::from("a note")));
+  }
+
+  #[test]
+  fn every_failure_is_reported_when_the_whole_chain_fails() {
+    let registry = Registry::with_builtins();
+    assert_eq!(
+      registry.format(&["dated", "author"], &Note),
+      Err(String::from("every formatter failed (dated: the item has no timestamp; author: the author is unknown)"))
+    );
+    // a name that was never registered is a failure too, not a panic
+    assert_eq!(registry.format(&["missing", "summary"], &Note), Ok(String::from("a note")));
+    assert_eq!(registry.format(&["missing"], &Note), Err(String::from("every formatter failed (missing: not registered)")));
+  }
+
+  #[test]
+  fn json_and_dates() {
+    let registry = Registry::with_builtins();
+    assert_eq!(
+      registry.format(&["json"], &Note),
+      Ok(String::from("{\"author\": null, \"timestamp\": 0, \"summary\": \"a note\"}"))
+    );
+    assert_eq!(date(0), "1970-01-01");
+    assert_eq!(date(951_782_400), "2000-02-29");
+    assert_eq!(date(1_709_251_199), "2024-02-29");
+  }
+}
@@ -0,0 +1,150 @@
+// A second trait next to Summary: instead of one short text, Render writes a value out in several formats.
+// The blanket impls at the bottom make every `Vec<T>` and `Option<T>` renderable as soon as `T` is, so a
+// `Vec<Option<User>>` works without anyone writing an impl for it.
+
+use crate::escape::{escape_html, json_string};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+  Plain,
+  Markdown,
+  Html,
+  Json,
+}
+
+impl Format {
+  pub const ALL: [Format; 4] = [Format::Plain, Format::Markdown, Format::Html, Format::Json];
+}
+
+pub trait Render {
+  fn render(&self, format: Format) -> String;
+}
+
+// Backslashes the characters that would otherwise start Markdown formatting
+pub fn escape_markdown(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  for ch in text.chars() {
+    if "\\`*_{}[]()#+-.!|<>".contains(ch) {
+      out.push('\\');
+    }
+    out.push(ch);
+  }
+  out
+}
+
+// Indents every line but the first, so a multi line item stays inside its list entry
+fn indent_rest(text: &str, indent: &str) -> String {
+  text.lines().collect::<Vec<_>>().join(&format!("\n{indent}"))
+}
+
+// A list of items: one per line, a bullet list, an <ul> or a JSON array
+impl<T: Render> Render for Vec<T> {
+  fn render(&self, format: Format) -> String {
+    let items = self.iter().map(|item| item.render(format));
+    match format {
+      Format::Plain => items.map(|item| indent_rest(&item, "  ")).collect::<Vec<_>>().join("\n"),
+      Format::Markdown => items.map(|item| format!("- {}", indent_rest(&item, "  "))).collect::<Vec<_>>().join("\n"),
+      Format::Html => format!("<ul>{}</ul>", items.map(|item| format!("<li>{item}</li>")).collect::<String>()),
+      Format::Json => format!("[{}]", items.collect::<Vec<_>>().join(", ")),
+    }
+  }
+}
+
+// A missing value renders as a placeholder, or `null` in JSON
+impl<T: Render> Render for Option<T> {
+  fn render(&self, format: Format) -> String {
+    match (self, format) {
+      (Some(value), _) => value.render(format),
+      (None, Format::Plain) => String::from("(none)"),
+      (None, Format::Markdown) => String::from("_none_"),
+      (None, Format::Html) => String::from("<em>none</em>"),
+      (None, Format::Json) => String::from("null"),
+    }
+  }
+}
+
+impl Render for String {
+  fn render(&self, format: Format) -> String {
+    match format {
+      Format::Plain => self.clone(),
+      Format::Markdown => escape_markdown(self),
+      Format::Html => escape_html(self),
+      Format::Json => json_string(self),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::User;
+
+  fn user(name: &str, age: u32) -> User {
+    User { name: name.to_string(), age }
+  }
+
+  // Every format of one value, in the order of Format::ALL
+  fn all(value: &impl Render) -> Vec<String> {
+    Format::ALL.iter().map(|format| value.render(*format)).collect()
+  }
+
+  #[test]
+  fn user_in_every_format() {
+    assert_eq!(
+      all(&user("sanchet", 23)),
+      vec![
+        "sanchet (23)",
+        "**sanchet**, 23 years old",
+        "<span class=\"user\"><strong>sanchet</strong> (23)</span>",
+        "{\"name\": \"sanchet\", \"age\": 23}",
+      ]
+    );
+  }
+
+  #[test]
+  fn quotes_and_angle_brackets_are_escaped_per_format() {
+    assert_eq!(
+      all(&user("<ferris> \"the\" crab", 9)),
+      vec![
+        "<ferris> \"the\" crab (9)",
+        "**\\<ferris\\> \"the\" crab**, 9 years old",
+        "<span class=\"user\"><strong>&lt;ferris&gt; &quot;the&quot; crab</strong> (9)</span>",
+        // JSON only needs the quotes escaped, angle brackets are fine inside a string
+        "{\"name\": \"<ferris> \\\"the\\\" crab\", \"age\": 9}",
+      ]
+    );
+    assert_eq!(String::from("it's a\\b\n").render(Format::Json), "\"it's a\\\\b\\n\"");
+    assert_eq!(String::from("it's").render(Format::Html), "it&#39;s");
+    assert_eq!(escape_markdown("*a_b* [x](y) #1"), "\\*a\\_b\\* \\[x\\]\\(y\\) \\#1");
+  }
+
+  #[test]
+  fn option_in_every_format() {
+    assert_eq!(all(&None::<User>), vec!["(none)", "_none_", "<em>none</em>", "null"]);
+    assert_eq!(all(&Some(user("a", 1))), all(&user("a", 1)));
+  }
+
+  #[test]
+  fn vec_in_every_format() {
+    let team = vec![Some(user("a", 1)), None, Some(user("<b>", 2))];
+    assert_eq!(
+      all(&team),
+      vec![
+        "a (1)\n(none)\n<b> (2)",
+        "- **a**, 1 years old\n- _none_\n- **\\<b\\>**, 2 years old",
+        "<ul><li><span class=\"user\"><strong>a</strong> (1)</span></li><li><em>none</em></li><li><span class=\"user\"><strong>&lt;b&gt;</strong> (2)</span></li></ul>",
+        "[{\"name\": \"a\", \"age\": 1}, null, {\"name\": \"<b>\", \"age\": 2}]",
+      ]
+    );
+    assert_eq!(all(&Vec::<User>::new()), vec!["", "", "<ul></ul>", "[]"]);
+  }
+
+  #[test]
+  fn nested_lists_stay_inside_their_entry() {
+    let nested = vec![vec![String::from("a*b"), String::from("c")], vec![]];
+    assert_eq!(nested.render(Format::Markdown), "- - a\\*b\n  - c\n- ");
+    assert_eq!(nested.render(Format::Plain), "a*b\n  c\n");
+    assert_eq!(nested.render(Format::Json), "[[\"a*b\", \"c\"], []]");
+    assert_eq!(nested.render(Format::Html), "<ul><li><ul><li>a*b</li><li>c</li></ul></li><li><ul></ul></li></ul>");
+  }
+}
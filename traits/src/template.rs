@@ -0,0 +1,485 @@
+// Summaries from templates written at runtime, like "{name} is {age} years old".
+// A type opts in by implementing Fields, which looks values up by name. The template is parsed once into a
+// list of nodes and can then be rendered against any number of values.
+//
+// Template syntax:
+//   {name}                          the value of a field, `{author.name}` looks inside a map value
+//   {#if name}...{#else}...{/if}    the first part if the field is truthy, `{#if !name}` flips it
+//   {#each list}...{/each}          the body once per item, `{.}` is the item and `{@index}` counts from 0.
+//                                   When the items are maps their keys can be used like fields.
+//   {{ and }}                       literal braces
+
+use std::fmt;
+
+use crate::escape::escape_html;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Text(String),
+  Integer(i64),
+  Bool(bool),
+  List(Vec<Value>),
+  Map(Vec<(String, Value)>),
+}
+
+impl Value {
+  // false, 0, "" and empty lists or maps are falsy
+  fn is_truthy(&self) -> bool {
+    match self {
+      Value::Text(text) => !text.is_empty(),
+      Value::Integer(number) => *number != 0,
+      Value::Bool(flag) => *flag,
+      Value::List(items) => !items.is_empty(),
+      Value::Map(entries) => !entries.is_empty(),
+    }
+  }
+
+  fn get(&self, key: &str) -> Option<&Value> {
+    match self {
+      Value::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Value::Text(text) => write!(f, "{text}"),
+      Value::Integer(number) => write!(f, "{number}"),
+      Value::Bool(flag) => write!(f, "{flag}"),
+      Value::List(items) => {
+        let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+        write!(f, "{}", items.join(", "))
+      },
+      Value::Map(entries) => {
+        let entries: Vec<String> = entries.iter().map(|(k, v)| format!("{k}: {v}")).collect();
+        write!(f, "{}", entries.join(", "))
+      },
+    }
+  }
+}
+
+// Types whose fields can be used in a template
+pub trait Fields {
+  fn field(&self, name: &str) -> Option<Value>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escape {
+  None,
+  // & < > " ' are replaced by entities, for templates producing HTML
+  Html,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+  UnknownPlaceholder(String),
+  NotAList(String),
+  UnclosedTag,
+  UnclosedBlock(String),
+  UnexpectedClose(String),
+  UnmatchedBrace,
+  InvalidTag(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+  pub line: usize,
+  pub column: usize,
+  pub kind: ErrorKind,
+}
+
+impl fmt::Display for TemplateError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "line {}, column {}: ", self.line, self.column)?;
+    match &self.kind {
+      ErrorKind::UnknownPlaceholder(name) => write!(f, "unknown placeholder `{name}`"),
+      ErrorKind::NotAList(name) => write!(f, "`{name}` is not a list and can't be used with #each"),
+      ErrorKind::UnclosedTag => write!(f, "`{{` is never closed, write `{{{{` for a literal brace"),
+      ErrorKind::UnclosedBlock(block) => write!(f, "{{#{block}}} is never closed with {{/{block}}}"),
+      ErrorKind::UnexpectedClose(tag) => write!(f, "`{{{tag}}}` does not close any open block"),
+      ErrorKind::UnmatchedBrace => write!(f, "`}}` without a matching `{{`, write `}}}}` for a literal brace"),
+      ErrorKind::InvalidTag(tag) => write!(f, "`{{{tag}}}` is not a valid tag"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position {
+  line: usize,
+  column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+  Text(String),
+  Field { path: String, at: Position },
+  If { path: String, negate: bool, then: Vec<Node>, otherwise: Vec<Node>, at: Position },
+  Each { path: String, body: Vec<Node>, at: Position },
+}
+
+// A tag between braces, before being put in the tree
+enum Token {
+  Text(String),
+  Field(String),
+  If(String, bool),
+  Else,
+  Each(String),
+  EndIf,
+  EndEach,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, Position)>, TemplateError> {
+  let mut tokens = Vec::new();
+  let mut text = String::new();
+  let mut at = Position { line: 1, column: 1 };
+  let mut chars = source.chars().peekable();
+
+  // moves the position past `ch`
+  let advance = |at: &mut Position, ch: char| {
+    if ch == '\n' {
+      at.line += 1;
+      at.column = 1;
+    } else {
+      at.column += 1;
+    }
+  };
+
+  while let Some(ch) = chars.next() {
+    let start = at;
+    advance(&mut at, ch);
+    match ch {
+      '{' if chars.peek() == Some(&'{') => {
+        chars.next();
+        advance(&mut at, '{');
+        text.push('{');
+      },
+      '}' if chars.peek() == Some(&'}') => {
+        chars.next();
+        advance(&mut at, '}');
+        text.push('}');
+      },
+      '}' => return Err(TemplateError { line: start.line, column: start.column, kind: ErrorKind::UnmatchedBrace }),
+      '{' => {
+        let mut tag = String::new();
+        loop {
+          match chars.next() {
+            Some('}') => {
+              advance(&mut at, '}');
+              break;
+            },
+            Some(c) => {
+              advance(&mut at, c);
+              tag.push(c);
+            },
+            None => return Err(TemplateError { line: start.line, column: start.column, kind: ErrorKind::UnclosedTag }),
+          }
+        }
+        if !text.is_empty() {
+          tokens.push((Token::Text(std::mem::take(&mut text)), start));
+        }
+        let token = parse_tag(tag.trim())
+          .ok_or_else(|| TemplateError { line: start.line, column: start.column, kind: ErrorKind::InvalidTag(tag.clone()) })?;
+        tokens.push((token, start));
+      },
+      c => text.push(c),
+    }
+  }
+  if !text.is_empty() {
+    tokens.push((Token::Text(text), at));
+  }
+  Ok(tokens)
+}
+
+fn is_path(text: &str) -> bool {
+  text == "." || text == "@index" || (!text.is_empty() && text.split('.').all(|part| {
+    !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+  }))
+}
+
+fn parse_tag(tag: &str) -> Option<Token> {
+  let token = match tag.split_once(char::is_whitespace) {
+    Some(("#if", path)) => match path.trim().strip_prefix('!') {
+      Some(path) => Token::If(path.trim().to_string(), true),
+      None => Token::If(path.trim().to_string(), false),
+    },
+    Some(("#each", path)) => Token::Each(path.trim().to_string()),
+    Some(_) => return None,
+    None => match tag {
+      "#else" => Token::Else,
+      "/if" => Token::EndIf,
+      "/each" => Token::EndEach,
+      _ => Token::Field(tag.to_string()),
+    },
+  };
+  match &token {
+    Token::Field(path) | Token::If(path, _) | Token::Each(path) if !is_path(path) => None,
+    _ => Some(token),
+  }
+}
+
+// The nodes of a block and the tag that ended it, None at the end of the template
+type Block = (Vec<Node>, Option<(Token, Position)>);
+
+// Builds the tree out of the tokens. `tokens` is consumed up to the tag closing the current block, which is
+// returned so the caller can check it is the one it expected.
+fn build(tokens: &mut std::vec::IntoIter<(Token, Position)>) -> Result<Block, TemplateError> {
+  let mut nodes = Vec::new();
+  while let Some((token, at)) = tokens.next() {
+    match token {
+      Token::Text(text) => nodes.push(Node::Text(text)),
+      Token::Field(path) => nodes.push(Node::Field { path, at }),
+      Token::If(path, negate) => {
+        let (then, end) = build(tokens)?;
+        let (otherwise, end) = match end {
+          Some((Token::Else, _)) => build(tokens)?,
+          end => (Vec::new(), end),
+        };
+        match end {
+          Some((Token::EndIf, _)) => nodes.push(Node::If { path, negate, then, otherwise, at }),
+          Some((token, end_at)) => return Err(unexpected_close(end_at, &token)),
+          None => {
+            return Err(TemplateError { line: at.line, column: at.column, kind: ErrorKind::UnclosedBlock(String::from("if")) })
+          },
+        }
+      },
+      Token::Each(path) => {
+        let (body, end) = build(tokens)?;
+        match end {
+          Some((Token::EndEach, _)) => nodes.push(Node::Each { path, body, at }),
+          Some((token, end_at)) => return Err(unexpected_close(end_at, &token)),
+          None => {
+            return Err(TemplateError { line: at.line, column: at.column, kind: ErrorKind::UnclosedBlock(String::from("each")) })
+          },
+        }
+      },
+      Token::Else | Token::EndIf | Token::EndEach => return Ok((nodes, Some((token, at)))),
+    }
+  }
+  Ok((nodes, None))
+}
+
+// `token` is one of the tags ending a block
+fn unexpected_close(at: Position, token: &Token) -> TemplateError {
+  let tag = match token {
+    Token::Else => "#else",
+    Token::EndIf => "/if",
+    _ => "/each",
+  };
+  TemplateError { line: at.line, column: at.column, kind: ErrorKind::UnexpectedClose(tag.to_string()) }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+  nodes: Vec<Node>,
+  escape: Escape,
+}
+
+// What names resolve to while rendering: the loop items from the innermost loop outwards, then the value
+struct Scope<'a> {
+  data: &'a dyn Fields,
+  items: Vec<(&'a Value, usize)>,
+}
+
+impl Scope<'_> {
+  fn lookup(&self, path: &str) -> Option<Value> {
+    if path == "." {
+      return self.items.last().map(|(item, _)| (*item).clone());
+    }
+    if path == "@index" {
+      return self.items.last().map(|(_, idx)| Value::Integer(*idx as i64));
+    }
+    let mut parts = path.split('.');
+    let first = parts.next()?;
+    let mut value = self
+      .items
+      .iter()
+      .rev()
+      .find_map(|(item, _)| item.get(first).cloned())
+      .or_else(|| self.data.field(first))?;
+    for part in parts {
+      value = value.get(part)?.clone();
+    }
+    Some(value)
+  }
+}
+
+impl Template {
+  pub fn parse(source: &str) -> Result<Template, TemplateError> {
+    let mut tokens = tokenize(source)?.into_iter();
+    match build(&mut tokens)? {
+      (nodes, None) => Ok(Template { nodes, escape: Escape::None }),
+      (_, Some((token, at))) => Err(unexpected_close(at, &token)),
+    }
+  }
+
+  pub fn escape(mut self, escape: Escape) -> Template {
+    self.escape = escape;
+    self
+  }
+
+  pub fn render(&self, data: &dyn Fields) -> Result<String, TemplateError> {
+    let mut out = String::new();
+    let mut scope = Scope { data, items: Vec::new() };
+    self.render_nodes(&self.nodes, &mut scope, &mut out)?;
+    Ok(out)
+  }
+
+  fn render_nodes(&self, nodes: &[Node], scope: &mut Scope, out: &mut String) -> Result<(), TemplateError> {
+    for node in nodes {
+      match node {
+        Node::Text(text) => out.push_str(text),
+        Node::Field { path, at } => {
+          let value = scope.lookup(path).ok_or_else(|| unknown(path, *at))?;
+          match self.escape {
+            Escape::None => out.push_str(&value.to_string()),
+            Escape::Html => out.push_str(&escape_html(&value.to_string())),
+          }
+        },
+        Node::If { path, negate, then, otherwise, at } => {
+          let value = scope.lookup(path).ok_or_else(|| unknown(path, *at))?;
+          let branch = if value.is_truthy() != *negate { then } else { otherwise };
+          self.render_nodes(branch, scope, out)?;
+        },
+        Node::Each { path, body, at } => {
+          let items = match scope.lookup(path).ok_or_else(|| unknown(path, *at))? {
+            Value::List(items) => items,
+            _ => return Err(TemplateError { line: at.line, column: at.column, kind: ErrorKind::NotAList(path.clone()) }),
+          };
+          for (idx, item) in items.iter().enumerate() {
+            // a nested scope borrowing this loop's items, with the outer loop items copied in front
+            let mut inner = Scope { data: scope.data, items: scope.items.clone() };
+            inner.items.push((item, idx));
+            self.render_nodes(body, &mut inner, out)?;
+          }
+        },
+      }
+    }
+    Ok(())
+  }
+}
+
+fn unknown(path: &str, at: Position) -> TemplateError {
+  TemplateError { line: at.line, column: at.column, kind: ErrorKind::UnknownPlaceholder(path.to_string()) }
+}
+
+// Parses and renders in one go, for templates used only once
+pub fn render(source: &str, data: &dyn Fields) -> Result<String, TemplateError> {
+  Template::parse(source)?.render(data)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Fields looked up from a list of name / value pairs
+  struct Data(Vec<(&'static str, Value)>);
+
+  impl Fields for Data {
+    fn field(&self, name: &str) -> Option<Value> {
+      self.0.iter().find(|(key, _)| *key == name).map(|(_, value)| value.clone())
+    }
+  }
+
+  fn text(value: &str) -> Value {
+    Value::Text(value.to_string())
+  }
+
+  fn data() -> Data {
+    Data(vec![
+      ("name", text("sanchet")),
+      ("age", Value::Integer(23)),
+      ("admin", Value::Bool(false)),
+      ("empty", text("")),
+      ("tags", Value::List(vec![text("rust"), text("traits")])),
+      ("none", Value::List(vec![])),
+      (
+        "posts",
+        Value::List(vec![
+          Value::Map(vec![(String::from("title"), text("Hello")), (String::from("likes"), Value::Integer(3))]),
+          Value::Map(vec![(String::from("title"), text("Again")), (String::from("likes"), Value::Integer(0))]),
+        ]),
+      ),
+      ("owner", Value::Map(vec![(String::from("name"), text("ferris"))])),
+    ])
+  }
+
+  fn error(source: &str) -> TemplateError {
+    render(source, &data()).unwrap_err()
+  }
+
+  #[test]
+  fn fields_and_literal_braces() {
+    assert_eq!(render("{name} is {age} years old", &data()).unwrap(), "sanchet is 23 years old");
+    assert_eq!(render("{{{name}}}", &data()).unwrap(), "{sanchet}");
+    assert_eq!(render("{ name }, {owner.name}", &data()).unwrap(), "sanchet, ferris");
+    assert_eq!(render("{tags} / {admin}", &data()).unwrap(), "rust, traits / false");
+    assert_eq!(render("no tags at all", &data()).unwrap(), "no tags at all");
+  }
+
+  #[test]
+  fn conditionals() {
+    assert_eq!(render("{#if age}{age}{#else}newborn{/if}", &data()).unwrap(), "23");
+    assert_eq!(render("{#if !age}newborn{#else}{age} years{/if}", &data()).unwrap(), "23 years");
+    assert_eq!(render("{#if admin}admin{#else}user{/if}", &data()).unwrap(), "user");
+    assert_eq!(render("[{#if empty}not empty{/if}]", &data()).unwrap(), "[]");
+    assert_eq!(render("{#if none}some{#else}none{/if}", &data()).unwrap(), "none");
+    assert_eq!(render("{#if tags}{#if !admin}tagged{/if}{/if}", &data()).unwrap(), "tagged");
+  }
+
+  #[test]
+  fn loops_over_collections() {
+    assert_eq!(render("{#each tags}{@index}:{.} {/each}", &data()).unwrap(), "0:rust 1:traits ");
+    assert_eq!(render("[{#each none}{.}{/each}]", &data()).unwrap(), "[]");
+    // keys of map items are used like fields, and outer fields are still visible
+    assert_eq!(
+      render("{#each posts}{title} by {name}{#if likes} ({likes}){/if}; {/each}", &data()).unwrap(),
+      "Hello by sanchet (3); Again by sanchet; "
+    );
+    // nested loops see the innermost item as `.`
+    assert_eq!(render("{#each posts}{#each tags}{title}/{.} {/each}{/each}", &data()).unwrap(), "Hello/rust Hello/traits Again/rust Again/traits ");
+  }
+
+  #[test]
+  fn html_escaping_only_applies_to_values() {
+    let data = Data(vec![("name", text("Tom & <Jerry> \"'")), ("list", Value::List(vec![text("<b>")]))]);
+    let template = Template::parse("<b>{name}</b>{#each list}<i>{.}</i>{/each}").unwrap();
+    assert_eq!(template.render(&data).unwrap(), "<b>Tom & <Jerry> \"'</b><i><b></i>");
+    assert_eq!(
+      template.escape(Escape::Html).render(&data).unwrap(),
+      "<b>Tom &amp; &lt;Jerry&gt; &quot;&#39;</b><i>&lt;b&gt;</i>"
+    );
+  }
+
+  #[test]
+  fn unknown_placeholders_name_the_field_and_where_it_is() {
+    let e = error("{name} is {years} years old");
+    assert_eq!(e, TemplateError { line: 1, column: 11, kind: ErrorKind::UnknownPlaceholder(String::from("years")) });
+    assert_eq!(e.to_string(), "line 1, column 11: unknown placeholder `years`");
+    assert_eq!(error("first\n  {owner.age}").to_string(), "line 2, column 3: unknown placeholder `owner.age`");
+    assert_eq!(error("{#if missing}x{/if}").kind, ErrorKind::UnknownPlaceholder(String::from("missing")));
+    // outside a loop there is no `.`
+    assert_eq!(error("{.}").kind, ErrorKind::UnknownPlaceholder(String::from(".")));
+  }
+
+  #[test]
+  fn unclosed_tags_and_blocks() {
+    let e = error("hello {name");
+    assert_eq!(e, TemplateError { line: 1, column: 7, kind: ErrorKind::UnclosedTag });
+    assert_eq!(e.to_string(), "line 1, column 7: `{` is never closed, write `{{` for a literal brace");
+    assert_eq!(error("{#if age}{age}").to_string(), "line 1, column 1: {#if} is never closed with {/if}");
+    assert_eq!(error("a\n{#each tags}{.}").to_string(), "line 2, column 1: {#each} is never closed with {/each}");
+    assert_eq!(error("{#if age}{/each}").to_string(), "line 1, column 10: `{/each}` does not close any open block");
+    assert_eq!(error("{/if}").kind, ErrorKind::UnexpectedClose(String::from("/if")));
+    assert_eq!(error("{name}}").to_string(), "line 1, column 7: `}` without a matching `{`, write `}}` for a literal brace");
+  }
+
+  #[test]
+  fn invalid_tags() {
+    assert_eq!(error("{two words}").kind, ErrorKind::InvalidTag(String::from("two words")));
+    assert_eq!(error("{}").kind, ErrorKind::InvalidTag(String::new()));
+    assert_eq!(error("{a..b}").kind, ErrorKind::InvalidTag(String::from("a..b")));
+    assert_eq!(error("{#each name}{.}{/each}").to_string(), "line 1, column 1: `name` is not a list and can't be used with #each");
+  }
+}
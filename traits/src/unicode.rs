@@ -0,0 +1,226 @@
+// Cutting text to a maximum length without breaking it.
+// A Rust `char` is one Unicode code point, but what a reader sees as one character (a grapheme) can be several
+// code points: "é" may be written as "e" plus a combining accent, "👍🏽" is a thumb plus a skin tone and "👨‍👩‍👧" is
+// three people glued together with zero width joiners. Cutting between those code points leaves a broken
+// character behind, so text is split into graphemes first and only cut between them.
+//
+// The splitting below covers the common cases (combining marks, variation selectors, emoji modifiers, zero width
+// joiner sequences, flags and CR LF), not every rule of Unicode's grapheme algorithm.
+
+// How the length of a text is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+  // every grapheme counts as one
+  Graphemes,
+  // columns taken up in a terminal, CJK characters and most emoji take two
+  DisplayWidth,
+}
+
+const ELLIPSIS: &str = "…";
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+// Code points that attach to the character in front of them
+fn is_extend(ch: char) -> bool {
+  matches!(ch,
+    '\u{0300}'..='\u{036F}'        // combining diacritical marks
+    | '\u{1AB0}'..='\u{1AFF}'
+    | '\u{1DC0}'..='\u{1DFF}'
+    | '\u{20D0}'..='\u{20FF}'      // combining marks for symbols, like the keycap in 1️⃣
+    | '\u{FE20}'..='\u{FE2F}'
+    | '\u{FE00}'..='\u{FE0F}'      // variation selectors, FE0F asks for the emoji look
+    | '\u{E0100}'..='\u{E01EF}'
+    | '\u{1F3FB}'..='\u{1F3FF}'    // skin tones
+    | '\u{E0020}'..='\u{E007F}'    // tags, used by subdivision flags like 🏴󠁧󠁢󠁳󠁣󠁴󠁿
+    | ZERO_WIDTH_JOINER
+  )
+}
+
+// Flags are written as two of these letters, 🇯🇵 is J followed by P
+fn is_regional_indicator(ch: char) -> bool {
+  ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch)
+}
+
+pub fn graphemes(text: &str) -> Vec<&str> {
+  let mut result = Vec::new();
+  let mut start = 0;
+  let mut previous: Option<char> = None;
+  // regional indicators seen in a row in the current grapheme, they pair up two by two
+  let mut indicators = 0;
+
+  for (idx, ch) in text.char_indices() {
+    let joins = match previous {
+      None => false,
+      Some('\r') => ch == '\n',
+      Some(ZERO_WIDTH_JOINER) => true,
+      Some(prev) if is_regional_indicator(prev) && is_regional_indicator(ch) => indicators % 2 == 1,
+      Some(_) => is_extend(ch),
+    };
+    if !joins && idx > 0 {
+      result.push(&text[start..idx]);
+      start = idx;
+      indicators = 0;
+    }
+    if is_regional_indicator(ch) {
+      indicators += 1;
+    }
+    previous = Some(ch);
+  }
+  if start < text.len() {
+    result.push(&text[start..]);
+  }
+  result
+}
+
+fn is_wide(ch: char) -> bool {
+  matches!(ch,
+    '\u{1100}'..='\u{115F}'        // Hangul jamo
+    | '\u{2E80}'..='\u{303E}'      // CJK radicals and punctuation
+    | '\u{3041}'..='\u{33FF}'      // kana and CJK symbols
+    | '\u{3400}'..='\u{4DBF}'
+    | '\u{4E00}'..='\u{9FFF}'      // CJK ideographs
+    | '\u{A000}'..='\u{A4CF}'
+    | '\u{AC00}'..='\u{D7A3}'      // Hangul syllables
+    | '\u{F900}'..='\u{FAFF}'
+    | '\u{FE30}'..='\u{FE4F}'
+    | '\u{FF00}'..='\u{FF60}'      // fullwidth forms
+    | '\u{FFE0}'..='\u{FFE6}'
+    | '\u{1F300}'..='\u{1F64F}'    // emoji
+    | '\u{1F680}'..='\u{1F6FF}'
+    | '\u{1F900}'..='\u{1F9FF}'
+    | '\u{1FA70}'..='\u{1FAFF}'
+    | '\u{20000}'..='\u{3FFFD}'
+  )
+}
+
+// Terminal columns a single grapheme takes: decided by its first code point, except that asking for the
+// emoji look (FE0F) or being a flag makes it two wide
+fn grapheme_width(grapheme: &str) -> usize {
+  let first = match grapheme.chars().next() {
+    Some(ch) => ch,
+    None => return 0,
+  };
+  if first.is_control() || is_extend(first) {
+    0
+  } else if is_wide(first) || is_regional_indicator(first) || grapheme.contains('\u{FE0F}') {
+    2
+  } else {
+    1
+  }
+}
+
+pub fn display_width(text: &str) -> usize {
+  graphemes(text).into_iter().map(grapheme_width).sum()
+}
+
+fn measure(grapheme: &str, measure: Measure) -> usize {
+  match measure {
+    Measure::Graphemes => 1,
+    Measure::DisplayWidth => grapheme_width(grapheme),
+  }
+}
+
+// The text cut so that, with the ellipsis added, it is at most `max` long. Text that already fits is returned
+// as it is. Whitespace in front of the ellipsis is dropped, "hello …" looks like a cut in the wrong place.
+pub fn truncate(text: &str, max: usize, by: Measure) -> String {
+  let parts = graphemes(text);
+  let total: usize = parts.iter().map(|g| measure(g, by)).sum();
+  if total <= max {
+    return text.to_string();
+  }
+  let room = match max.checked_sub(measure(ELLIPSIS, by)) {
+    Some(room) => room,
+    // not even the ellipsis fits
+    None => return String::new(),
+  };
+
+  let mut end = 0;
+  let mut used = 0;
+  for grapheme in parts {
+    let size = measure(grapheme, by);
+    if used + size > room {
+      break;
+    }
+    used += size;
+    end += grapheme.len();
+  }
+  format!("{}{ELLIPSIS}", text[..end].trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const FAMILY: &str = "👨\u{200D}👩\u{200D}👧";
+  const RAINBOW_FLAG: &str = "🏳\u{FE0F}\u{200D}🌈";
+  const THUMBS_UP: &str = "👍🏽";
+
+  #[test]
+  fn zero_width_joiner_sequences_are_one_grapheme() {
+    assert_eq!(graphemes(FAMILY), vec![FAMILY]);
+    assert_eq!(graphemes(RAINBOW_FLAG), vec![RAINBOW_FLAG]);
+    let text = format!("a{FAMILY}b{THUMBS_UP}{RAINBOW_FLAG}");
+    assert_eq!(graphemes(&text), vec!["a", FAMILY, "b", THUMBS_UP, RAINBOW_FLAG]);
+    assert_eq!(display_width(FAMILY), 2);
+    assert_eq!(display_width(&text), 8);
+  }
+
+  #[test]
+  fn regional_indicators_pair_up_into_flags() {
+    // 🇯🇵🇫🇷🇩🇪: three flags in a row, not one long grapheme and not two split in the wrong places
+    let flags = "\u{1F1EF}\u{1F1F5}\u{1F1EB}\u{1F1F7}\u{1F1E9}\u{1F1EA}";
+    assert_eq!(graphemes(flags), vec!["\u{1F1EF}\u{1F1F5}", "\u{1F1EB}\u{1F1F7}", "\u{1F1E9}\u{1F1EA}"]);
+    assert_eq!(display_width(flags), 6);
+    // an odd one out at the end stays on its own
+    assert_eq!(graphemes("\u{1F1EF}\u{1F1F5}\u{1F1EB}"), vec!["\u{1F1EF}\u{1F1F5}", "\u{1F1EB}"]);
+    // letters in between start the pairing over
+    assert_eq!(graphemes("\u{1F1EF}x\u{1F1F5}\u{1F1EB}"), vec!["\u{1F1EF}", "x", "\u{1F1F5}\u{1F1EB}"]);
+    assert_eq!(truncate(flags, 5, Measure::DisplayWidth), "\u{1F1EF}\u{1F1F5}\u{1F1EB}\u{1F1F7}…");
+    assert_eq!(truncate(flags, 4, Measure::DisplayWidth), "\u{1F1EF}\u{1F1F5}…");
+  }
+
+  #[test]
+  fn combining_marks_stay_with_their_letter() {
+    let decomposed = "e\u{0301}te\u{0301}";
+    assert_eq!(graphemes(decomposed), vec!["e\u{0301}", "t", "e\u{0301}"]);
+    assert_eq!(display_width(decomposed), 3);
+    // several marks on one letter, like in Vietnamese ệ
+    assert_eq!(graphemes("e\u{0323}\u{0302}x"), vec!["e\u{0323}\u{0302}", "x"]);
+    assert_eq!(truncate("cafe\u{0301} noir", 5, Measure::Graphemes), "cafe\u{0301}…");
+    assert_eq!(truncate("cafe\u{0301} noir", 4, Measure::Graphemes), "caf…");
+    // a mark with nothing in front of it takes no room
+    assert_eq!(display_width("\u{0301}a"), 1);
+    assert_eq!(graphemes("\r\nx"), vec!["\r\n", "x"]);
+  }
+
+  #[test]
+  fn truncating_inside_a_wide_character_leaves_it_out() {
+    // every character is two columns wide
+    let text = "日本語のテキスト";
+    assert_eq!(display_width(text), 16);
+    // 6 columns: 5 for text and 1 for the ellipsis, the third character would end at column 6
+    assert_eq!(truncate(text, 6, Measure::DisplayWidth), "日本…");
+    assert_eq!(display_width(&truncate(text, 6, Measure::DisplayWidth)), 5);
+    assert_eq!(truncate(text, 7, Measure::DisplayWidth), "日本語…");
+    assert_eq!(truncate(text, 2, Measure::DisplayWidth), "…");
+    assert_eq!(truncate(text, 0, Measure::DisplayWidth), "");
+    assert_eq!(truncate(text, 16, Measure::DisplayWidth), text);
+    // by graphemes every character counts as one
+    assert_eq!(truncate(text, 6, Measure::Graphemes), "日本語のテ…");
+    for max in 0..20 {
+      assert!(display_width(&truncate(text, max, Measure::DisplayWidth)) <= max);
+    }
+  }
+
+  #[test]
+  fn truncating_never_splits_an_emoji_sequence() {
+    let text = format!("hi {FAMILY}{FAMILY} there");
+    assert_eq!(truncate(&text, 6, Measure::DisplayWidth), format!("hi {FAMILY}…"));
+    assert_eq!(truncate(&text, 5, Measure::DisplayWidth), "hi…");
+    for max in 0..text.len() {
+      let cut = truncate(&text, max, Measure::DisplayWidth);
+      assert!(display_width(&cut) <= max);
+      let kept = cut.trim_end_matches('…');
+      assert!(graphemes(kept).iter().all(|g| graphemes(&text).contains(g)), "{cut:?}");
+    }
+  }
+}
@@ -0,0 +1,116 @@
+// A sink posting notifications as JSON to an HTTP endpoint, and a tiny local HTTP server that stands in for
+// the real webhook receiver. Both only use std::net, speaking just enough HTTP/1.1 for one request per connection.
+
+use std::{
+  io::{BufRead, BufReader, Read, Write},
+  net::{SocketAddr, TcpListener, TcpStream},
+  sync::{Arc, Mutex},
+  thread,
+  time::Duration,
+};
+
+use crate::{
+  escape::json_string,
+  notifier::{Notification, Sink},
+};
+
+pub struct WebhookSink {
+  pub addr: SocketAddr,
+  pub path: String,
+}
+
+// [{"author": "...", "timestamp": 123, "text": "..."}, ...], the author is null when the item has none
+fn to_json(batch: &[Notification]) -> String {
+  let items: Vec<String> = batch
+    .iter()
+    .map(|n| {
+      format!("{{\"author\": {}, \"timestamp\": {}, \"text\": {}}}", n.author.as_deref().map_or_else(|| String::from("null"), json_string), n.timestamp, json_string(&n.text))
+    })
+    .collect();
+  format!("[{}]", items.join(", "))
+}
+
+impl Sink for WebhookSink {
+  fn name(&self) -> String {
+    format!("webhook http://{}{}", self.addr, self.path)
+  }
+
+  fn deliver(&mut self, batch: &[Notification]) -> Result<(), String> {
+    let body = to_json(batch);
+    let mut stream = TcpStream::connect_timeout(&self.addr, Duration::from_secs(2)).map_err(|e| format!("could not connect: {e}"))?;
+    stream.set_read_timeout(Some(Duration::from_secs(2))).map_err(|e| e.to_string())?;
+    let request = format!(
+      "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+      self.path,
+      self.addr,
+      body.len(),
+      body
+    );
+    stream.write_all(request.as_bytes()).map_err(|e| format!("could not send request: {e}"))?;
+
+    // Only the status line matters: "HTTP/1.1 200 OK"
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line).map_err(|e| format!("could not read response: {e}"))?;
+    let status = status_line.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok());
+    match status {
+      Some(code) if (200..300).contains(&code) => Ok(()),
+      Some(code) => Err(format!("webhook answered with status {code}")),
+      None => Err(format!("invalid response `{}`", status_line.trim())),
+    }
+  }
+}
+
+// Accepts requests on a free local port in a background thread and keeps the body of every request.
+// The thread runs until the program ends.
+pub struct WebhookServer {
+  addr: SocketAddr,
+  received: Arc<Mutex<Vec<String>>>,
+}
+
+impl WebhookServer {
+  pub fn start() -> std::io::Result<WebhookServer> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let bodies = Arc::clone(&received);
+    thread::spawn(move || {
+      for stream in listener.incoming().flatten() {
+        handle_request(stream, &bodies);
+      }
+    });
+    Ok(WebhookServer { addr, received })
+  }
+
+  pub fn addr(&self) -> SocketAddr {
+    self.addr
+  }
+
+  pub fn received(&self) -> Vec<String> {
+    self.received.lock().unwrap().clone()
+  }
+}
+
+// Reads one request, stores its body and answers 200. Broken requests are dropped.
+// The body is stored before answering, so once a sink got its 200 the body is in `received`.
+fn handle_request(stream: TcpStream, bodies: &Mutex<Vec<String>>) -> Option<()> {
+  let mut reader = BufReader::new(stream.try_clone().ok()?);
+  let mut content_length = 0;
+  loop {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let line = line.trim_end();
+    if line.is_empty() {
+      break;
+    }
+    if let Some((name, value)) = line.split_once(':') {
+      if name.eq_ignore_ascii_case("content-length") {
+        content_length = value.trim().parse().ok()?;
+      }
+    }
+  }
+  let mut body = vec![0; content_length];
+  reader.read_exact(&mut body).ok()?;
+  bodies.lock().unwrap().push(String::from_utf8(body).ok()?);
+  let mut stream = stream;
+  stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").ok()
+}
@@ -0,0 +1,266 @@
+//! `#[derive(Summary)]` writes the `impl Summary for ...` block of the traits crate for a struct with named fields.
+//!
+//! Without attributes the summary lists every field as `label = value`, using the field's Display output.
+//! Attributes tweak that:
+//! - `#[summary(skip)]` on a field leaves it out
+//! - `#[summary(rename = "...")]` on a field changes its label
+//! - `#[summary(template = "...")]` on the struct replaces the whole text, `{field}` is replaced by the field's value
+//!   and `{{` / `}}` stand for literal braces
+//!
+//! The generated impl names the trait as plain `Summary`, so the trait has to be in scope where the derive is used.
+//!
+//! ```
+//! use summary_derive::Summary;
+//!
+//! trait Summary {
+//!   fn summarize(&self) -> String;
+//! }
+//!
+//! #[derive(Summary)]
+//! struct User {
+//!   #[summary(rename = "username")]
+//!   name: String,
+//!   age: u32,
+//!   #[summary(skip)]
+//!   _password: String,
+//! }
+//!
+//! #[derive(Summary)]
+//! #[summary(template = "{name} ({age})")]
+//! struct Person {
+//!   name: String,
+//!   age: u32,
+//! }
+//!
+//! let user = User { name: String::from("sanchet"), age: 23, _password: String::from("hunter2") };
+//! assert_eq!(user.summarize(), "User: username = sanchet, age = 23");
+//! let person = Person { name: String::from("sanchet"), age: 23 };
+//! assert_eq!(person.summarize(), "sanchet (23)");
+//! ```
+//!
+//! These don't compile. Each one is the example above with a single mistake.
+//!
+//! Enums are rejected, there is no single set of fields to summarize:
+//! ```compile_fail
+//! use summary_derive::Summary;
+//! trait Summary { fn summarize(&self) -> String; }
+//!
+//! #[derive(Summary)]
+//! enum Shape { Circle(f64), Square(f64) }
+//! ```
+//!
+//! Tuple structs are rejected, their fields have no names to use as labels or placeholders:
+//! ```compile_fail
+//! use summary_derive::Summary;
+//! trait Summary { fn summarize(&self) -> String; }
+//!
+//! #[derive(Summary)]
+//! struct Meters(f64);
+//! ```
+//!
+//! A template placeholder has to name a field:
+//! ```compile_fail
+//! use summary_derive::Summary;
+//! trait Summary { fn summarize(&self) -> String; }
+//!
+//! #[derive(Summary)]
+//! #[summary(template = "{name} ({years})")]
+//! struct Person { name: String, age: u32 }
+//! ```
+//!
+//! ... that isn't skipped:
+//! ```compile_fail
+//! use summary_derive::Summary;
+//! trait Summary { fn summarize(&self) -> String; }
+//!
+//! #[derive(Summary)]
+//! #[summary(template = "{name} ({age})")]
+//! struct Person { name: String, #[summary(skip)] age: u32 }
+//! ```
+//!
+//! Unknown attributes are errors instead of being ignored:
+//! ```compile_fail
+//! use summary_derive::Summary;
+//! trait Summary { fn summarize(&self) -> String; }
+//!
+//! #[derive(Summary)]
+//! struct Person { #[summary(hide)] name: String }
+//! ```
+//!
+//! Every field that ends up in the summary needs to implement Display:
+//! ```compile_fail
+//! use summary_derive::Summary;
+//! trait Summary { fn summarize(&self) -> String; }
+//!
+//! #[derive(Summary)]
+//! struct Team { members: Vec<String> }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitStr, Result};
+
+#[proc_macro_derive(Summary, attributes(summary))]
+pub fn derive_summary(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  // errors become `compile_error!` invocations pointing at the offending tokens
+  expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+struct Field {
+  ident: Ident,
+  label: String,
+  skip: bool,
+}
+
+// One piece of a template: text copied as is, or a field to insert
+enum Piece {
+  Text(String),
+  Field(String),
+}
+
+fn expand(input: &DeriveInput) -> Result<proc_macro2::TokenStream> {
+  let name = &input.ident;
+  let fields = match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Named(named) => named.named.iter().map(parse_field).collect::<Result<Vec<Field>>>()?,
+      Fields::Unnamed(_) => {
+        return Err(Error::new_spanned(
+          name,
+          "#[derive(Summary)] does not support tuple structs, the fields need names to be used in the summary",
+        ))
+      },
+      Fields::Unit => Vec::new(),
+    },
+    Data::Enum(data) => {
+      return Err(Error::new_spanned(data.enum_token, "#[derive(Summary)] only supports structs, not enums"))
+    },
+    Data::Union(data) => {
+      return Err(Error::new_spanned(data.union_token, "#[derive(Summary)] only supports structs, not unions"))
+    },
+  };
+
+  let mut template = None;
+  for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("summary")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("template") {
+        template = Some(meta.value()?.parse::<LitStr>()?);
+        Ok(())
+      } else {
+        Err(meta.error("unknown summary attribute on a struct, expected `template = \"...\"`"))
+      }
+    })?;
+  }
+
+  // Everything is turned into a format string plus the fields to fill it with
+  let mut format = String::new();
+  let mut args = Vec::new();
+  match &template {
+    Some(template) => {
+      for piece in parse_template(template)? {
+        match piece {
+          Piece::Text(text) => format.push_str(&text.replace('{', "{{").replace('}', "}}")),
+          Piece::Field(placeholder) => {
+            let field = fields.iter().find(|field| field.ident == placeholder).ok_or_else(|| {
+              Error::new(template.span(), format!("template placeholder `{{{placeholder}}}` is not a field of {name}"))
+            })?;
+            if field.skip {
+              return Err(Error::new(
+                template.span(),
+                format!("template placeholder `{{{placeholder}}}` names a field marked #[summary(skip)]"),
+              ));
+            }
+            format.push_str("{}");
+            args.push(field.ident.clone());
+          },
+        }
+      }
+    },
+    None => {
+      // User: name = sanchet, age = 23
+      format.push_str(&name.to_string());
+      let shown: Vec<&Field> = fields.iter().filter(|field| !field.skip).collect();
+      for (idx, field) in shown.iter().enumerate() {
+        format.push_str(if idx == 0 { ": " } else { ", " });
+        format.push_str(&field.label.replace('{', "{{").replace('}', "}}"));
+        format.push_str(" = {}");
+        args.push(field.ident.clone());
+      }
+    },
+  }
+
+  let format = LitStr::new(&format, Span::call_site());
+  let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+  Ok(quote! {
+    impl #impl_generics Summary for #name #type_generics #where_clause {
+      fn summarize(&self) -> String {
+        format!(#format, #(self.#args),*)
+      }
+    }
+  })
+}
+
+fn parse_field(field: &syn::Field) -> Result<Field> {
+  let ident = field.ident.clone().expect("named fields have an identifier");
+  let mut label = ident.to_string();
+  let mut skip = false;
+  for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("summary")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("skip") {
+        skip = true;
+        Ok(())
+      } else if meta.path.is_ident("rename") {
+        label = meta.value()?.parse::<LitStr>()?.value();
+        Ok(())
+      } else {
+        Err(meta.error("unknown summary attribute on a field, expected `skip` or `rename = \"...\"`"))
+      }
+    })?;
+  }
+  Ok(Field { ident, label, skip })
+}
+
+// "{name} ({age})" -> [Field(name), Text(" ("), Field(age), Text(")")]
+fn parse_template(template: &LitStr) -> Result<Vec<Piece>> {
+  let text = template.value();
+  let mut pieces = Vec::new();
+  let mut literal = String::new();
+  let mut chars = text.chars().peekable();
+  while let Some(ch) = chars.next() {
+    match ch {
+      '{' if chars.peek() == Some(&'{') => {
+        chars.next();
+        literal.push('{');
+      },
+      '}' if chars.peek() == Some(&'}') => {
+        chars.next();
+        literal.push('}');
+      },
+      '{' => {
+        let mut placeholder = String::new();
+        loop {
+          match chars.next() {
+            Some('}') => break,
+            Some(c) => placeholder.push(c),
+            None => return Err(Error::new(template.span(), "unclosed `{` in template, use `{{` for a literal brace")),
+          }
+        }
+        let placeholder = placeholder.trim().to_string();
+        if placeholder.is_empty() {
+          return Err(Error::new(template.span(), "empty `{}` in template, put a field name between the braces"));
+        }
+        if !literal.is_empty() {
+          pieces.push(Piece::Text(std::mem::take(&mut literal)));
+        }
+        pieces.push(Piece::Field(placeholder));
+      },
+      '}' => return Err(Error::new(template.span(), "unmatched `}` in template, use `}}` for a literal brace")),
+      c => literal.push(c),
+    }
+  }
+  if !literal.is_empty() {
+    pieces.push(Piece::Text(literal));
+  }
+  Ok(pieces)
+}